@@ -0,0 +1,4 @@
+-- 合约交易字段
+ALTER TABLE trades ADD COLUMN is_short INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE trades ADD COLUMN leverage TEXT NOT NULL DEFAULT '1';
+ALTER TABLE trades ADD COLUMN liquidation_price TEXT;
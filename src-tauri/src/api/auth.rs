@@ -0,0 +1,196 @@
+use crate::config::ApiServerConfig;
+use crate::error::{AppError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Lifetime of tokens returned by `/token/login`, matching freqtrade
+const ACCESS_TOKEN_LIFETIME: Duration = Duration::minutes(15);
+const REFRESH_TOKEN_LIFETIME: Duration = Duration::days(30);
+
+/// JOSE header of every issued token
+const JWT_HEADER: &str = r#"{"alg":"HS256","typ":"JWT"}"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    Access,
+    Refresh,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// Username the token was issued to
+    pub sub: String,
+    /// Expiry as unix seconds
+    pub exp: i64,
+    #[serde(rename = "type")]
+    pub kind: TokenKind,
+}
+
+/// Credentials checked by the API server: HTTP basic auth or HS256 bearer tokens
+pub struct Authenticator {
+    username: String,
+    password: String,
+    secret: Vec<u8>,
+}
+
+impl Authenticator {
+    pub fn new(username: &str, password: &str, secret: &[u8]) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+            secret: secret.to_vec(),
+        }
+    }
+
+    pub fn from_config(config: &ApiServerConfig) -> Result<Self> {
+        if config.username.is_empty() || config.password.is_empty() {
+            return Err(AppError::Config(
+                "api_server.username and api_server.password must be set to enable the API server".to_string(),
+            ));
+        }
+
+        let secret = if config.jwt_secret_key.is_empty() {
+            format!("{}{}", uuid::Uuid::new_v4(), uuid::Uuid::new_v4()).into_bytes()
+        } else {
+            config.jwt_secret_key.clone().into_bytes()
+        };
+        Ok(Self::new(&config.username, &config.password, &secret))
+    }
+
+    /// Check the value of an `Authorization: Basic ...` header
+    pub fn check_basic(&self, credentials: &str) -> bool {
+        let Ok(decoded) = STANDARD.decode(credentials.trim()) else {
+            return false;
+        };
+        let Ok(decoded) = String::from_utf8(decoded) else {
+            return false;
+        };
+        let Some((username, password)) = decoded.split_once(':') else {
+            return false;
+        };
+        // Compare both so the response time does not reveal whether the username matched
+        let username_ok = constant_time_eq(username.as_bytes(), self.username.as_bytes());
+        let password_ok = constant_time_eq(password.as_bytes(), self.password.as_bytes());
+        username_ok & password_ok
+    }
+
+    pub fn issue(&self, kind: TokenKind) -> String {
+        let lifetime = match kind {
+            TokenKind::Access => ACCESS_TOKEN_LIFETIME,
+            TokenKind::Refresh => REFRESH_TOKEN_LIFETIME,
+        };
+        self.encode(&Claims {
+            sub: self.username.clone(),
+            exp: (Utc::now() + lifetime).timestamp(),
+            kind,
+        })
+    }
+
+    pub fn encode(&self, claims: &Claims) -> String {
+        let header = URL_SAFE_NO_PAD.encode(JWT_HEADER);
+        // Claims only hold strings and integers, serialization cannot fail
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).unwrap_or_default());
+        let signing_input = format!("{}.{}", header, payload);
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(&signing_input).finalize().into_bytes());
+        format!("{}.{}", signing_input, signature)
+    }
+
+    /// Verify signature, expiry and kind of a bearer token
+    pub fn verify(&self, token: &str, kind: TokenKind) -> Result<Claims> {
+        let invalid = || AppError::InvalidInput("Invalid token".to_string());
+
+        let (signing_input, signature) = token.rsplit_once('.').ok_or_else(invalid)?;
+        let (header, payload) = signing_input.split_once('.').ok_or_else(invalid)?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+        self.mac(signing_input)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        let header: serde_json::Value =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header).map_err(|_| invalid())?).map_err(|_| invalid())?;
+        if header["alg"] != "HS256" {
+            return Err(invalid());
+        }
+
+        let claims: Claims =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?).map_err(|_| invalid())?;
+        if claims.kind != kind {
+            return Err(invalid());
+        }
+        if claims.exp <= Utc::now().timestamp() {
+            return Err(AppError::InvalidInput("Token expired".to_string()));
+        }
+        Ok(claims)
+    }
+
+    fn mac(&self, input: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC can take key of any size");
+        mac.update(input.as_bytes());
+        mac
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth() -> Authenticator {
+        Authenticator::new("freqtrader", "secret", b"jwt-key")
+    }
+
+    #[test]
+    fn test_check_basic() {
+        let auth = auth();
+        assert!(auth.check_basic(&STANDARD.encode("freqtrader:secret")));
+        assert!(!auth.check_basic(&STANDARD.encode("freqtrader:wrong")));
+        assert!(!auth.check_basic(&STANDARD.encode("other:secret")));
+        assert!(!auth.check_basic("not base64!"));
+    }
+
+    #[test]
+    fn test_token_roundtrip_and_rejections() {
+        let auth = auth();
+
+        let access = auth.issue(TokenKind::Access);
+        assert_eq!(auth.verify(&access, TokenKind::Access).unwrap().sub, "freqtrader");
+        // A refresh token is not accepted as an access token
+        let refresh = auth.issue(TokenKind::Refresh);
+        assert!(auth.verify(&refresh, TokenKind::Access).is_err());
+        assert!(auth.verify(&refresh, TokenKind::Refresh).is_ok());
+
+        let other_key = Authenticator::new("freqtrader", "secret", b"other-key");
+        assert!(other_key.verify(&access, TokenKind::Access).is_err());
+
+        let expired = auth.encode(&Claims {
+            sub: "freqtrader".to_string(),
+            exp: Utc::now().timestamp() - 1,
+            kind: TokenKind::Access,
+        });
+        assert!(auth.verify(&expired, TokenKind::Access).is_err());
+        assert!(auth.verify("a.b", TokenKind::Access).is_err());
+    }
+
+    #[test]
+    fn test_from_config_requires_credentials() {
+        assert!(Authenticator::from_config(&ApiServerConfig::default()).is_err());
+
+        let config = ApiServerConfig {
+            username: "freqtrader".to_string(),
+            password: "secret".to_string(),
+            ..ApiServerConfig::default()
+        };
+        let auth = Authenticator::from_config(&config).unwrap();
+        assert!(auth.verify(&auth.issue(TokenKind::Access), TokenKind::Access).is_ok());
+    }
+}
@@ -0,0 +1,353 @@
+//! REST API compatible with freqtrade's `/api/v1`, so tools such as FreqUI can control the bot.
+//!
+//! Every route except `/api/v1/ping` and the token endpoints requires either HTTP basic auth with
+//! the configured `api_server.username`/`password` or a bearer token obtained from
+//! `/api/v1/token/login`. Bot events are streamed over the `/api/v1/message/ws` WebSocket.
+
+pub mod auth;
+pub mod state;
+mod v1;
+mod ws;
+
+pub use auth::{Authenticator, TokenKind};
+pub use state::AppState;
+
+use crate::error::{AppError, Result};
+use axum::Router;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+#[derive(Clone)]
+pub(crate) struct ApiContext {
+    pub app: AppState,
+    pub auth: Arc<Authenticator>,
+}
+
+/// Build the API router; exposed separately from [`serve`] so it can be mounted or tested
+pub fn router(app: AppState, auth: Arc<Authenticator>) -> Router {
+    let context = ApiContext { app, auth };
+    let protected = v1::protected_routes().layer(middleware::from_fn_with_state(context.clone(), require_auth));
+
+    Router::new()
+        .nest("/api/v1", v1::public_routes().merge(protected))
+        .with_state(context)
+}
+
+/// Serve the API on `api_server.listen_ip:listen_port` until the process exits
+pub async fn serve(app: AppState) -> Result<()> {
+    let config = app.config.read().await.api_server.clone();
+    let auth = Arc::new(Authenticator::from_config(&config)?);
+
+    let listener = TcpListener::bind((config.listen_ip.as_str(), config.listen_port)).await?;
+    tracing::info!("API server listening on {}", listener.local_addr()?);
+    serve_on(listener, app, auth).await
+}
+
+pub async fn serve_on(listener: TcpListener, app: AppState, auth: Arc<Authenticator>) -> Result<()> {
+    axum::serve(listener, router(app, auth)).await?;
+    Ok(())
+}
+
+/// Start the server in the background when `api_server.enabled` is set
+pub async fn spawn_if_enabled(app: &AppState) {
+    if !app.config.read().await.api_server.enabled {
+        return;
+    }
+    let app = app.clone();
+    tokio::spawn(async move {
+        if let Err(e) = serve(app).await {
+            eprintln!("API server not started: {}", e);
+        }
+    });
+}
+
+async fn require_auth(State(context): State<ApiContext>, request: Request, next: Next) -> Response {
+    if is_authorized(&context.auth, request.headers()) {
+        next.run(request).await
+    } else {
+        unauthorized()
+    }
+}
+
+/// Accept basic auth or a bearer access token in the `Authorization` header
+fn is_authorized(auth: &Authenticator, headers: &HeaderMap) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| match value.split_once(' ') {
+            Some(("Basic", credentials)) => auth.check_basic(credentials),
+            Some(("Bearer", token)) => auth.verify(token, TokenKind::Access).is_ok(),
+            _ => false,
+        })
+}
+
+pub(crate) fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Basic")],
+        axum::Json(serde_json::json!({ "detail": "Unauthorized" })),
+    )
+        .into_response()
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::InvalidInput(_) | AppError::Config(_) | AppError::Parse(_) => StatusCode::BAD_REQUEST,
+            AppError::Bot(_) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        // freqtrade (FastAPI) reports errors as {"detail": ...}
+        (status, axum::Json(serde_json::json!({ "detail": self.to_string() }))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::persistence::Repository;
+    use base64::Engine;
+    use serde_json::Value;
+
+    struct TestServer {
+        url: String,
+        client: reqwest::Client,
+        app: AppState,
+        _dir: tempfile::TempDir,
+    }
+
+    impl TestServer {
+        async fn start() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let repository = Arc::new(Repository::new(dir.path().join("api.db")).await.unwrap());
+            let app = AppState::new(AppConfig::default(), repository);
+            let auth = Arc::new(Authenticator::new("freqtrader", "secret", b"jwt-key"));
+
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/api/v1", listener.local_addr().unwrap());
+            tokio::spawn(serve_on(listener, app.clone(), auth));
+
+            Self {
+                url,
+                client: reqwest::Client::new(),
+                app,
+                _dir: dir,
+            }
+        }
+
+        fn get(&self, path: &str) -> reqwest::RequestBuilder {
+            self.client.get(format!("{}{}", self.url, path))
+        }
+
+        fn post(&self, path: &str) -> reqwest::RequestBuilder {
+            self.client.post(format!("{}{}", self.url, path))
+        }
+
+        fn delete(&self, path: &str) -> reqwest::RequestBuilder {
+            self.client.delete(format!("{}{}", self.url, path))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_is_public_and_other_routes_need_auth() {
+        let server = TestServer::start().await;
+
+        let ping: Value = server.get("/ping").send().await.unwrap().json().await.unwrap();
+        assert_eq!(ping["status"], "pong");
+
+        let response = server.get("/status").send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let response = server
+            .get("/status")
+            .basic_auth("freqtrader", Some("wrong"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let response = server
+            .get("/status")
+            .basic_auth("freqtrader", Some("secret"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let trades: Value = response.json().await.unwrap();
+        assert_eq!(trades, serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_login_and_refresh_tokens() {
+        let server = TestServer::start().await;
+
+        let tokens: Value = server
+            .post("/token/login")
+            .basic_auth("freqtrader", Some("secret"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let access = tokens["access_token"].as_str().unwrap();
+        let refresh = tokens["refresh_token"].as_str().unwrap();
+
+        let config: Value = server
+            .get("/show_config")
+            .bearer_auth(access)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(config["strategy"], "SimpleStrategy");
+        assert_eq!(config["state"], "stopped");
+
+        // 刷新令牌只能用于换取新的访问令牌
+        let response = server.get("/show_config").bearer_auth(refresh).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let refreshed: Value = server
+            .post("/token/refresh")
+            .bearer_auth(refresh)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let response = server
+            .get("/count")
+            .bearer_auth(refreshed["access_token"].as_str().unwrap())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        let response = server
+            .post("/token/login")
+            .basic_auth("freqtrader", Some("x"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_protection_routes() {
+        let server = TestServer::start().await;
+        let basic = format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode("freqtrader:secret")
+        );
+
+        let response = server
+            .post("/protections/cooldown")
+            .header(header::AUTHORIZATION, &basic)
+            .json(&serde_json::json!({ "stop_duration": 60, "lookback_period": 1440, "stop_after_losses": 2 }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        let names: Vec<String> = server
+            .get("/protections")
+            .header(header::AUTHORIZATION, &basic)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(names.len(), 1);
+        assert_eq!(server.app.risk_manager().await.unwrap().list_protections().await, names);
+
+        let removed: bool = server
+            .delete(&format!("/protections/{}", names[0]))
+            .header(header::AUTHORIZATION, &basic)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(removed);
+
+        let locks: Value = server
+            .get("/locks")
+            .header(header::AUTHORIZATION, &basic)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(locks["lock_count"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_message_ws_streams_bot_events() {
+        use crate::bot::events::BotEvent;
+        use futures::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let server = TestServer::start().await;
+        let ws_url = format!("{}/message/ws", server.url.replacen("http", "ws", 1));
+
+        // 未携带令牌时握手被拒绝
+        assert!(tokio_tungstenite::connect_async(ws_url.as_str()).await.is_err());
+
+        let tokens: Value = server
+            .post("/token/login")
+            .basic_auth("freqtrader", Some("secret"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let url = format!("{}?token={}", ws_url, tokens["access_token"].as_str().unwrap());
+        let (mut socket, _) = tokio_tungstenite::connect_async(url.as_str()).await.unwrap();
+
+        server
+            .app
+            .events
+            .send(BotEvent::Status {
+                status: crate::types::BotStatus::Running,
+                message: None,
+            })
+            .unwrap();
+
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), socket.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let Message::Text(text) = message else {
+            panic!("expected text frame, got {:?}", message);
+        };
+        let event: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(event["type"], "status");
+        assert_eq!(event["data"]["status"], "running");
+    }
+
+    #[tokio::test]
+    async fn test_stop_without_bot_is_conflict() {
+        let server = TestServer::start().await;
+        let response = server
+            .post("/stop")
+            .basic_auth("freqtrader", Some("secret"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CONFLICT);
+        let body: Value = response.json().await.unwrap();
+        assert!(body["detail"].as_str().unwrap().contains("not running"));
+    }
+}
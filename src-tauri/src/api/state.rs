@@ -0,0 +1,288 @@
+use crate::backtest::{BacktestConfig, BacktestEngine, BacktestRequest, load_candles};
+use crate::bot::TradingBot;
+use crate::bot::events::{BotEvent, EVENT_CHANNEL_CAPACITY};
+use crate::config::AppConfig;
+use crate::error::{AppError, Result};
+use crate::persistence::Repository;
+use crate::risk::RiskManager;
+use crate::types::*;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock, broadcast};
+
+/// 配置文件保存路径
+pub const CONFIG_PATH: &str = "config/default.toml";
+
+/// Tauri 命令与 REST API 共享的应用状态，两者通过这里的方法执行相同的操作
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<RwLock<AppConfig>>,
+    pub repository: Arc<Repository>,
+    pub bot: Arc<Mutex<Option<TradingBot>>>,
+    pub risk_manager: Arc<RwLock<Option<Arc<RiskManager>>>>,
+    /// 机器人事件通道，跨机器人重启保持不变，供前端和 WebSocket 订阅
+    pub events: broadcast::Sender<BotEvent>,
+}
+
+impl AppState {
+    pub fn new(config: AppConfig, repository: Arc<Repository>) -> Self {
+        let risk_manager = Arc::new(RiskManager::new(repository.clone()));
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            repository,
+            bot: Arc::new(Mutex::new(None)),
+            risk_manager: Arc::new(RwLock::new(Some(risk_manager))),
+            events,
+        }
+    }
+
+    pub async fn bot_status(&self) -> BotStatus {
+        match self.bot.lock().await.as_ref() {
+            Some(bot) => bot.get_status().await,
+            None => BotStatus::Stopped,
+        }
+    }
+
+    pub async fn start_bot(&self) -> Result<String> {
+        let mut bot_guard = self.bot.lock().await;
+
+        if let Some(bot) = bot_guard.as_ref()
+            && bot.get_status().await == BotStatus::Running
+        {
+            return Err(AppError::Bot("Bot is already running".to_string()));
+        }
+
+        let config = self.config.read().await.clone();
+        let bot = TradingBot::from_config(&config, self.repository.clone())
+            .await?
+            .with_events(self.events.clone());
+
+        // 保存 risk_manager 到 state
+        *self.risk_manager.write().await = bot.risk_manager();
+
+        let bot_arc = Arc::new(bot.clone());
+        *bot_guard = Some(bot);
+        drop(bot_guard);
+
+        tokio::spawn(async move {
+            let _ = bot_arc.start().await;
+        });
+
+        Ok("Bot started with risk management".to_string())
+    }
+
+    pub async fn stop_bot(&self) -> Result<String> {
+        let bot_guard = self.bot.lock().await;
+        if let Some(bot) = bot_guard.as_ref() {
+            bot.stop().await?;
+            Ok("Bot stopped".to_string())
+        } else {
+            Err(AppError::Bot("Bot is not running".to_string()))
+        }
+    }
+
+    pub async fn dashboard_stats(&self) -> Result<DashboardStats> {
+        let bot = self.bot.lock().await.clone();
+        let dry_run_wallet = self.config.read().await.bot.dry_run_wallet;
+
+        // 运行中使用钱包实际估值，否则以 dry_run_wallet 加已实现收益估算
+        let wallet_value = match bot {
+            Some(bot) => match bot.wallets().total_value().await {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    eprintln!("Failed to value wallets: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let dry_run_start = Decimal::try_from(dry_run_wallet).unwrap_or(Decimal::ZERO);
+        let mut stats = self
+            .repository
+            .get_dashboard_stats(dry_run_start)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get dashboard stats: {}", e)))?;
+
+        if let Some(value) = wallet_value {
+            // 回撤以钱包估值扣除已实现收益后的初始余额为基准
+            let starting_balance = value - Decimal::try_from(stats.total_profit).unwrap_or(Decimal::ZERO);
+            stats = self
+                .repository
+                .get_dashboard_stats(starting_balance)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to get dashboard stats: {}", e)))?;
+            stats.total_balance = value.to_f64().unwrap_or(0.0);
+        }
+        Ok(stats)
+    }
+
+    pub async fn equity_curve(&self, timeframe: &str) -> Result<Vec<EquityPoint>> {
+        let _trades = self
+            .repository
+            .get_all_trades()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get trades: {}", e)))?;
+
+        // Simplified: Generate mock curve data based on time
+        let days_to_show = match timeframe {
+            "1d" => 1,
+            "1w" => 7,
+            _ => 1,
+        };
+
+        let mut equity = 10000.0_f64;
+        let mut points = Vec::new();
+
+        let start_date = chrono::Utc::now() - chrono::Duration::days(days_to_show as i64);
+
+        for i in 0..days_to_show {
+            let time = (start_date + chrono::Duration::days(i as i64))
+                .format("%H:%M")
+                .to_string();
+
+            // Simulate fluctuation (can be replaced with real data from DB later)
+            let change = (i as f64 * 0.5 - 1.75) * 50.0;
+            equity += change;
+
+            points.push(EquityPoint { time, value: equity });
+        }
+
+        Ok(points)
+    }
+
+    pub async fn update_config(&self, config: AppConfig) -> Result<()> {
+        let mut state_config = self.config.write().await;
+        *state_config = config.clone();
+
+        // Save to file
+        let toml_str = toml::to_string_pretty(&config)
+            .map_err(|e| AppError::Config(format!("Failed to serialize config: {}", e)))?;
+
+        tokio::fs::write(CONFIG_PATH, toml_str)
+            .await
+            .map_err(|e| AppError::Config(format!("Failed to save config: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// 从数据库加载K线执行回测，结果保存后返回
+    pub async fn run_backtest(&self, request: BacktestRequest) -> Result<BacktestResult> {
+        let config = self.config.read().await.clone();
+        let pairs = if request.pairs.is_empty() {
+            config.bot.trading_pairs.clone()
+        } else {
+            request.pairs
+        };
+        let timeframe = match request.timeframe {
+            Some(timeframe) => timeframe,
+            None => config.bot.timeframe.parse()?,
+        };
+        let timerange = request
+            .timerange
+            .filter(|t| !t.is_empty())
+            .map(|t| crate::data::downloader::TimeRange::parse(&t))
+            .transpose()?;
+        let strategy = crate::strategy::load_strategy(request.strategy.as_deref().unwrap_or(&config.strategy.name))?;
+
+        let data = load_candles(
+            &self.repository,
+            &pairs,
+            timeframe.as_str(),
+            timerange.as_ref(),
+            strategy.startup_candle_count(),
+        )
+        .await?;
+        let mut backtest_config = BacktestConfig::from_bot(&config.bot, timeframe, &data, timerange.as_ref());
+        if let Some(stake_amount) = request.stake_amount {
+            backtest_config.stake_amount = stake_amount;
+        }
+        if let Some(starting_balance) = request.starting_balance {
+            backtest_config.starting_balance = starting_balance;
+        }
+        if let Some(commission) = request.commission {
+            backtest_config.commission = commission;
+        }
+        if let Some(slippage) = request.slippage {
+            backtest_config.slippage = slippage;
+        }
+        backtest_config.stoploss = request.stoploss;
+        backtest_config.take_profit = request.take_profit;
+
+        let mut engine = BacktestEngine::new(backtest_config, strategy, data);
+        if let Some(detail_timeframe) = request.timeframe_detail {
+            let detail = load_candles(
+                &self.repository,
+                &pairs,
+                detail_timeframe.as_str(),
+                timerange.as_ref(),
+                0,
+            )
+            .await?;
+            engine = engine.with_detail(detail_timeframe, detail);
+        }
+        let result = engine.run().await?;
+        self.repository.save_backtest_result(&result).await?;
+        Ok(result)
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<BotEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn risk_manager(&self) -> Option<Arc<RiskManager>> {
+        self.risk_manager.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_run_backtest_uses_stored_candles_and_saves_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Arc::new(Repository::new(dir.path().join("state.db")).await.unwrap());
+        let start = Utc::now() - Duration::hours(300);
+        let klines: Vec<OHLCV> = (0..200)
+            .map(|i| OHLCV {
+                timestamp: start + Duration::hours(i),
+                open: Decimal::from(100),
+                high: Decimal::from(101),
+                low: Decimal::from(99),
+                close: Decimal::from(100),
+                volume: Decimal::ONE,
+            })
+            .collect();
+        repository.save_klines("BTC/USDT", "1h", &klines).await.unwrap();
+        let state = AppState::new(AppConfig::default(), repository.clone());
+
+        let result = state
+            .run_backtest(crate::backtest::BacktestRequest {
+                pairs: vec!["BTC/USDT".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.strategy, "SimpleStrategy");
+        assert_eq!(result.start_date, klines[0].timestamp);
+        assert_eq!(result.end_date, klines[199].timestamp);
+
+        let runs = repository.get_backtest_results().await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].pair, "BTC/USDT");
+
+        // 没有数据的交易对直接报错，不保存结果
+        let missing = state
+            .run_backtest(crate::backtest::BacktestRequest {
+                pairs: vec!["ETH/USDT".to_string()],
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+        assert_eq!(repository.get_backtest_results().await.unwrap().len(), 1);
+    }
+}
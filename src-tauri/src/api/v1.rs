@@ -0,0 +1,330 @@
+//! `/api/v1` routes. Names and response shapes follow freqtrade's REST API where an equivalent
+//! exists; `/dashboard`, `/equity_curve`, `/config` and `/protections` expose the remaining
+//! Tauri commands.
+
+use super::{ApiContext, TokenKind, unauthorized};
+use crate::config::{AppConfig, REDACTED};
+use crate::error::Result;
+use crate::risk::{
+    CooldownPeriod, CooldownPeriodConfig, LowProfitPairs, LowProfitPairsConfig, MaxDrawdownProtection,
+    MaxDrawdownProtectionConfig, StoplossGuard, StoplossGuardConfig,
+};
+use crate::types::*;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+pub(super) fn public_routes() -> Router<ApiContext> {
+    Router::new()
+        .route("/ping", get(ping))
+        .route("/token/login", post(token_login))
+        .route("/token/refresh", post(token_refresh))
+        // Authenticates itself so a token can be passed as a query parameter
+        .route("/message/ws", get(super::ws::message_ws))
+}
+
+pub(super) fn protected_routes() -> Router<ApiContext> {
+    Router::new()
+        .route("/version", get(version))
+        .route("/show_config", get(show_config))
+        .route("/start", post(start))
+        .route("/stop", post(stop))
+        .route("/status", get(status))
+        .route("/trades", get(trades))
+        .route("/count", get(count))
+        .route("/profit", get(profit))
+        .route("/locks", get(locks))
+        .route("/dashboard", get(dashboard))
+        .route("/equity_curve", get(equity_curve))
+        .route("/config", get(get_config).put(update_config))
+        .route("/protections", get(list_protections))
+        .route("/protections/cooldown", post(add_cooldown))
+        .route("/protections/low_profit", post(add_low_profit))
+        .route("/protections/max_drawdown", post(add_max_drawdown))
+        .route("/protections/stoploss_guard", post(add_stoploss_guard))
+        .route("/protections/{name}", delete(remove_protection))
+}
+
+async fn ping() -> Json<Value> {
+    Json(json!({ "status": "pong" }))
+}
+
+fn authorization<'a>(headers: &'a HeaderMap, scheme: &str) -> Option<&'a str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix(scheme)?
+        .strip_prefix(' ')
+}
+
+async fn token_login(State(context): State<ApiContext>, headers: HeaderMap) -> Response {
+    match authorization(&headers, "Basic") {
+        Some(credentials) if context.auth.check_basic(credentials) => Json(json!({
+            "access_token": context.auth.issue(TokenKind::Access),
+            "refresh_token": context.auth.issue(TokenKind::Refresh),
+        }))
+        .into_response(),
+        _ => unauthorized(),
+    }
+}
+
+async fn token_refresh(State(context): State<ApiContext>, headers: HeaderMap) -> Response {
+    match authorization(&headers, "Bearer") {
+        Some(token) if context.auth.verify(token, TokenKind::Refresh).is_ok() => {
+            Json(json!({ "access_token": context.auth.issue(TokenKind::Access) })).into_response()
+        }
+        _ => unauthorized(),
+    }
+}
+
+async fn version() -> Json<Value> {
+    Json(json!({ "version": env!("CARGO_PKG_VERSION") }))
+}
+
+fn state_name(status: BotStatus) -> &'static str {
+    match status {
+        BotStatus::Running => "running",
+        BotStatus::Stopped => "stopped",
+        BotStatus::Paused => "paused",
+        BotStatus::Error => "error",
+    }
+}
+
+async fn show_config(State(context): State<ApiContext>) -> Json<Value> {
+    let status = context.app.bot_status().await;
+    let config = context.app.config.read().await;
+    Json(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "api_version": 1.0,
+        "dry_run": config.bot.dry_run,
+        "trading_mode": if config.exchange.name == "binance_futures" { "futures" } else { "spot" },
+        "stake_currency": config.bot.stake_currency,
+        "stake_amount": config.bot.stake_amount,
+        "max_open_trades": config.bot.max_open_trades,
+        "dry_run_wallet": config.bot.dry_run_wallet,
+        "timeframe": config.bot.timeframe,
+        "exchange": config.exchange.name,
+        "strategy": config.strategy.name,
+        "pairs": config.bot.trading_pairs,
+        "state": state_name(status),
+        "runmode": if config.bot.dry_run { "dry_run" } else { "live" },
+    }))
+}
+
+async fn start(State(context): State<ApiContext>) -> Result<Json<Value>> {
+    let message = context.app.start_bot().await?;
+    Ok(Json(json!({ "status": message })))
+}
+
+async fn stop(State(context): State<ApiContext>) -> Result<Json<Value>> {
+    let message = context.app.stop_bot().await?;
+    Ok(Json(json!({ "status": message })))
+}
+
+async fn status(State(context): State<ApiContext>) -> Result<Json<Vec<Trade>>> {
+    Ok(Json(context.app.repository.get_open_trades().await?))
+}
+
+#[derive(Deserialize)]
+struct TradesQuery {
+    #[serde(default = "default_trades_limit")]
+    limit: usize,
+    #[serde(default)]
+    offset: usize,
+}
+
+fn default_trades_limit() -> usize {
+    500
+}
+
+async fn trades(State(context): State<ApiContext>, Query(query): Query<TradesQuery>) -> Result<Json<Value>> {
+    let all = context.app.repository.get_all_trades().await?;
+    let total_trades = all.len();
+    let page: Vec<Trade> = all.into_iter().skip(query.offset).take(query.limit).collect();
+    Ok(Json(json!({
+        "trades_count": page.len(),
+        "offset": query.offset,
+        "total_trades": total_trades,
+        "trades": page,
+    })))
+}
+
+async fn count(State(context): State<ApiContext>) -> Result<Json<Value>> {
+    let open = context.app.repository.get_open_trades().await?;
+    let total_stake: Decimal = open.iter().map(|t| t.stake_amount).sum();
+    let max = context.app.config.read().await.bot.max_open_trades;
+    Ok(Json(json!({
+        "current": open.len(),
+        "max": max,
+        "total_stake": total_stake.to_f64().unwrap_or(0.0),
+    })))
+}
+
+async fn profit(State(context): State<ApiContext>) -> Result<Json<Value>> {
+    let trades = context.app.repository.get_all_trades().await?;
+    let stats = context.app.dashboard_stats().await?;
+
+    let closed: Vec<&Trade> = trades.iter().filter(|t| !t.is_open).collect();
+    let profit_closed: Decimal = closed.iter().filter_map(|t| t.profit_abs).sum();
+    let winning_trades = closed
+        .iter()
+        .filter(|t| t.profit_abs.is_some_and(|p| p > Decimal::ZERO))
+        .count();
+    let losing_trades = closed
+        .iter()
+        .filter(|t| t.profit_abs.is_some_and(|p| p < Decimal::ZERO))
+        .count();
+
+    Ok(Json(json!({
+        "profit_closed_coin": profit_closed.to_f64().unwrap_or(0.0),
+        "profit_all_coin": stats.total_profit,
+        "trade_count": trades.len(),
+        "closed_trade_count": closed.len(),
+        "winning_trades": winning_trades,
+        "losing_trades": losing_trades,
+        "winrate": stats.win_rate,
+        "max_drawdown": stats.max_drawdown,
+        "first_trade_timestamp": trades.iter().map(|t| t.open_date.timestamp_millis()).min().unwrap_or(0),
+        "latest_trade_timestamp": trades.iter().map(|t| t.open_date.timestamp_millis()).max().unwrap_or(0),
+    })))
+}
+
+async fn locks(State(context): State<ApiContext>) -> Result<Json<Value>> {
+    let Some(risk_manager) = context.app.risk_manager().await else {
+        return Ok(Json(json!({ "lock_count": 0, "locks": [] })));
+    };
+    let pairs = context.app.config.read().await.bot.trading_pairs.clone();
+
+    let mut locks = Vec::new();
+    if let Some(stop) = risk_manager.check_global_stop().await? {
+        locks.push(("*".to_string(), stop));
+    }
+    for pair in pairs {
+        if let Some(stop) = risk_manager.check_pair_stop(&pair).await? {
+            locks.push((pair, stop));
+        }
+    }
+
+    let locks: Vec<Value> = locks
+        .into_iter()
+        .map(|(pair, stop)| {
+            json!({
+                "pair": pair,
+                "lock_end_time": stop.until.to_rfc3339(),
+                "lock_end_timestamp": stop.until.timestamp_millis(),
+                "reason": stop.reason,
+                "protection": stop.protection,
+                "active": true,
+            })
+        })
+        .collect();
+    Ok(Json(json!({ "lock_count": locks.len(), "locks": locks })))
+}
+
+async fn dashboard(State(context): State<ApiContext>) -> Result<Json<DashboardStats>> {
+    Ok(Json(context.app.dashboard_stats().await?))
+}
+
+#[derive(Deserialize)]
+struct EquityCurveQuery {
+    #[serde(default = "default_equity_timeframe")]
+    timeframe: String,
+}
+
+fn default_equity_timeframe() -> String {
+    "1d".to_string()
+}
+
+async fn equity_curve(
+    State(context): State<ApiContext>,
+    Query(query): Query<EquityCurveQuery>,
+) -> Result<Json<Vec<EquityPoint>>> {
+    Ok(Json(context.app.equity_curve(&query.timeframe).await?))
+}
+
+async fn get_config(State(context): State<ApiContext>) -> Json<AppConfig> {
+    Json(context.app.config.read().await.redacted())
+}
+
+/// Credentials still set to the placeholder from `GET /config` keep their current values
+async fn update_config(State(context): State<ApiContext>, Json(mut config): Json<AppConfig>) -> Result<()> {
+    {
+        let current = context.app.config.read().await;
+        let keep = |value: &mut String, current: &String| {
+            if value == REDACTED {
+                *value = current.clone();
+            }
+        };
+        keep(&mut config.exchange.key, &current.exchange.key);
+        keep(&mut config.exchange.secret, &current.exchange.secret);
+        keep(&mut config.api_server.password, &current.api_server.password);
+        keep(
+            &mut config.api_server.jwt_secret_key,
+            &current.api_server.jwt_secret_key,
+        );
+        if config.exchange.password.as_deref() == Some(REDACTED) {
+            config.exchange.password = current.exchange.password.clone();
+        }
+    }
+    context.app.update_config(config).await
+}
+
+async fn list_protections(State(context): State<ApiContext>) -> Json<Vec<String>> {
+    match context.app.risk_manager().await {
+        Some(risk_manager) => Json(risk_manager.list_protections().await),
+        None => Json(vec![]),
+    }
+}
+
+async fn add_cooldown(State(context): State<ApiContext>, Json(config): Json<CooldownPeriodConfig>) -> Result<()> {
+    if let Some(risk_manager) = context.app.risk_manager().await {
+        risk_manager
+            .add_protection(Box::new(CooldownPeriod::new(config)))
+            .await?;
+    }
+    Ok(())
+}
+
+async fn add_low_profit(State(context): State<ApiContext>, Json(config): Json<LowProfitPairsConfig>) -> Result<()> {
+    if let Some(risk_manager) = context.app.risk_manager().await {
+        risk_manager
+            .add_protection(Box::new(LowProfitPairs::new(config)))
+            .await?;
+    }
+    Ok(())
+}
+
+async fn add_max_drawdown(
+    State(context): State<ApiContext>,
+    Json(config): Json<MaxDrawdownProtectionConfig>,
+) -> Result<()> {
+    if let Some(risk_manager) = context.app.risk_manager().await {
+        risk_manager
+            .add_protection(Box::new(MaxDrawdownProtection::new(config)))
+            .await?;
+    }
+    Ok(())
+}
+
+async fn add_stoploss_guard(State(context): State<ApiContext>, Json(config): Json<StoplossGuardConfig>) -> Result<()> {
+    if let Some(risk_manager) = context.app.risk_manager().await {
+        risk_manager
+            .add_protection(Box::new(StoplossGuard::new(config)))
+            .await?;
+    }
+    Ok(())
+}
+
+async fn remove_protection(State(context): State<ApiContext>, Path(name): Path<String>) -> Result<Json<bool>> {
+    match context.app.risk_manager().await {
+        Some(risk_manager) => Ok(Json(risk_manager.remove_protection(&name).await?)),
+        None => Ok(Json(false)),
+    }
+}
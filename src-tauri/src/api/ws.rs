@@ -0,0 +1,61 @@
+//! `/api/v1/message/ws`: pushes every [`BotEvent`] to the client as a JSON text frame.
+//!
+//! Browsers cannot set headers on a WebSocket handshake, so besides the usual `Authorization`
+//! header an access token may be passed as `?token=...`, as freqtrade does.
+
+use super::{ApiContext, TokenKind, is_authorized, unauthorized};
+use crate::bot::events::BotEvent;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum::response::Response;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+#[derive(Deserialize)]
+pub(super) struct WsQuery {
+    token: Option<String>,
+}
+
+pub(super) async fn message_ws(
+    State(context): State<ApiContext>,
+    Query(query): Query<WsQuery>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let query_ok = query
+        .token
+        .is_some_and(|token| context.auth.verify(&token, TokenKind::Access).is_ok());
+    if !query_ok && !is_authorized(&context.auth, &headers) {
+        return unauthorized();
+    }
+
+    // Subscribe before upgrading so no event is missed between handshake and first poll
+    let events = context.app.subscribe_events();
+    upgrade.on_upgrade(move |socket| forward_events(socket, events))
+}
+
+async fn forward_events(mut socket: WebSocket, mut events: broadcast::Receiver<BotEvent>) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow client only misses the oldest events
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum; other client messages are ignored
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,672 @@
+//! Export of backtest results to files for external analysis, and import of those files.
+//!
+//! JSON files follow freqtrade's `backtest-results` layout: results are keyed by strategy under
+//! `strategy`, with the trades, per-pair and exit reason tables and the summary numbers of each
+//! run. The equity curve is added as `equity_curve`. Amounts are written as JSON numbers like
+//! freqtrade's, so imports are exact to the precision of an f64. CSV export writes the trades,
+//! equity curve and per-pair summary as separate files.
+
+use super::BacktestConfig;
+use super::metrics;
+use super::report::build_report;
+use crate::error::{AppError, Result};
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%:z";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(format!("unknown export format '{}', expected json or csv", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportFile {
+    strategy: BTreeMap<String, StrategyExport>,
+    #[serde(default)]
+    strategy_comparison: Vec<ExportRow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StrategyExport {
+    trades: Vec<ExportTrade>,
+    #[serde(default)]
+    results_per_pair: Vec<ExportRow>,
+    #[serde(default)]
+    exit_reason_summary: Vec<ExportRow>,
+    total_trades: usize,
+    wins: usize,
+    losses: usize,
+    winrate: f64,
+    /// Total profit relative to the starting balance
+    profit_total: f64,
+    profit_total_abs: Decimal,
+    starting_balance: f64,
+    final_balance: f64,
+    /// Exchange notation, e.g. "1h"
+    timeframe: String,
+    backtest_start: String,
+    backtest_end: String,
+    backtest_start_ts: i64,
+    backtest_end_ts: i64,
+    market_change: f64,
+    max_drawdown_account: f64,
+    sharpe: f64,
+    profit_factor: f64,
+    #[serde(default)]
+    equity_curve: Vec<EquityRow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportTrade {
+    pair: String,
+    stake_amount: Decimal,
+    amount: Decimal,
+    open_date: String,
+    close_date: Option<String>,
+    /// Milliseconds since the epoch
+    open_timestamp: i64,
+    close_timestamp: Option<i64>,
+    open_rate: Decimal,
+    close_rate: Option<Decimal>,
+    /// Minutes
+    trade_duration: Option<i64>,
+    profit_ratio: Option<Decimal>,
+    profit_abs: Option<Decimal>,
+    exit_reason: Option<String>,
+    stop_loss_abs: Option<Decimal>,
+    #[serde(default)]
+    take_profit_abs: Option<Decimal>,
+    is_open: bool,
+    #[serde(default)]
+    is_short: bool,
+    #[serde(default = "one")]
+    leverage: Decimal,
+}
+
+fn one() -> Decimal {
+    Decimal::ONE
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportRow {
+    key: String,
+    trades: usize,
+    /// Mean profit ratio per trade
+    profit_mean: f64,
+    profit_total_abs: Decimal,
+    profit_total: f64,
+    /// Minutes
+    duration_avg: i64,
+    wins: usize,
+    losses: usize,
+    winrate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EquityRow {
+    date: String,
+    balance: Decimal,
+}
+
+const EXIT_TYPES: [ExitType; 7] = [
+    ExitType::Signal,
+    ExitType::StopLoss,
+    ExitType::TakeProfit,
+    ExitType::StopLossOnExchange,
+    ExitType::ForceExit,
+    ExitType::EmergencyExit,
+    ExitType::Custom,
+];
+
+/// freqtrade's names for the exit reasons it shares with us; take-profit exits correspond to its
+/// ROI exits
+fn exit_reason_name(reason: ExitType) -> &'static str {
+    match reason {
+        ExitType::Signal => "exit_signal",
+        ExitType::StopLoss => "stop_loss",
+        ExitType::TakeProfit => "roi",
+        ExitType::StopLossOnExchange => "stoploss_on_exchange",
+        ExitType::ForceExit => "force_exit",
+        ExitType::EmergencyExit => "emergency_exit",
+        ExitType::Custom => "custom_exit",
+    }
+}
+
+fn parse_exit_reason(name: &str) -> ExitType {
+    match name {
+        "exit_signal" | "signal" => ExitType::Signal,
+        "stop_loss" | "trailing_stop_loss" => ExitType::StopLoss,
+        "roi" | "take_profit" => ExitType::TakeProfit,
+        "stoploss_on_exchange" | "stop_loss_on_exchange" => ExitType::StopLossOnExchange,
+        "force_exit" => ExitType::ForceExit,
+        "emergency_exit" | "liquidation" => ExitType::EmergencyExit,
+        _ => ExitType::Custom,
+    }
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+fn parse_date(timestamp: Option<i64>, date: Option<&str>) -> Result<Option<DateTime<Utc>>> {
+    if let Some(ms) = timestamp {
+        return DateTime::from_timestamp_millis(ms)
+            .map(Some)
+            .ok_or_else(|| AppError::Parse(format!("Invalid timestamp {}", ms)));
+    }
+    date.map(|d| {
+        DateTime::parse_from_str(d, DATE_FORMAT)
+            .map(|d| d.with_timezone(&Utc))
+            .map_err(|e| AppError::Parse(format!("Invalid date '{}': {}", d, e)))
+    })
+    .transpose()
+}
+
+/// Balance after every closed trade, starting from the starting balance
+fn equity_curve(result: &BacktestResult) -> Vec<(DateTime<Utc>, Decimal)> {
+    let mut balance = Decimal::try_from(result.starting_balance).unwrap_or(Decimal::ZERO);
+    let mut closed: Vec<&Trade> = result.trades.iter().filter(|t| !t.is_open).collect();
+    closed.sort_by_key(|t| t.close_date);
+
+    let mut curve = vec![(result.start_date, balance)];
+    for trade in closed {
+        if let Some(close_date) = trade.close_date {
+            balance += trade.profit_abs.unwrap_or(Decimal::ZERO);
+            curve.push((close_date, balance));
+        }
+    }
+    curve
+}
+
+fn export_row(row: &ReportRow) -> ExportRow {
+    ExportRow {
+        key: row.key.clone(),
+        trades: row.trades,
+        profit_mean: row.avg_profit_ratio,
+        profit_total_abs: row.total_profit,
+        profit_total: row.total_profit_ratio,
+        duration_avg: row.avg_duration_secs / 60,
+        wins: row.wins,
+        losses: row.losses,
+        winrate: row.win_rate,
+    }
+}
+
+fn export_trade(trade: &Trade) -> ExportTrade {
+    ExportTrade {
+        pair: trade.pair.clone(),
+        stake_amount: trade.stake_amount,
+        amount: trade.amount,
+        open_date: format_date(trade.open_date),
+        close_date: trade.close_date.map(format_date),
+        open_timestamp: trade.open_date.timestamp_millis(),
+        close_timestamp: trade.close_date.map(|d| d.timestamp_millis()),
+        open_rate: trade.open_rate,
+        close_rate: trade.close_rate,
+        trade_duration: trade.close_date.map(|d| (d - trade.open_date).num_minutes()),
+        profit_ratio: trade.profit_ratio,
+        profit_abs: trade.profit_abs,
+        exit_reason: trade.exit_reason.map(|r| exit_reason_name(r).to_string()),
+        stop_loss_abs: trade.stop_loss,
+        take_profit_abs: trade.take_profit,
+        is_open: trade.is_open,
+        is_short: trade.is_short,
+        leverage: trade.leverage,
+    }
+}
+
+fn strategy_export(result: &BacktestResult) -> StrategyExport {
+    let report = build_report(result);
+    StrategyExport {
+        trades: result.trades.iter().map(export_trade).collect(),
+        results_per_pair: report
+            .per_pair
+            .iter()
+            .chain(std::iter::once(&report.total))
+            .map(export_row)
+            .collect(),
+        exit_reason_summary: report
+            .exit_reasons
+            .iter()
+            .map(|row| ExportRow {
+                key: EXIT_TYPES
+                    .into_iter()
+                    .find(|r| r.to_string() == row.key)
+                    .map_or(row.key.clone(), |r| exit_reason_name(r).to_string()),
+                ..export_row(row)
+            })
+            .collect(),
+        total_trades: result.total_trades,
+        wins: result.winning_trades,
+        losses: result.losing_trades,
+        winrate: result.win_rate,
+        profit_total: report.total.total_profit_ratio,
+        profit_total_abs: result.total_profit,
+        starting_balance: result.starting_balance,
+        final_balance: report.final_balance,
+        timeframe: result.timeframe.as_str().to_string(),
+        backtest_start: format_date(result.start_date),
+        backtest_end: format_date(result.end_date),
+        backtest_start_ts: result.start_date.timestamp_millis(),
+        backtest_end_ts: result.end_date.timestamp_millis(),
+        market_change: result.market_change,
+        max_drawdown_account: result.max_drawdown,
+        sharpe: result.sharpe_ratio,
+        profit_factor: result.profit_factor,
+        equity_curve: equity_curve(result)
+            .into_iter()
+            .map(|(date, balance)| EquityRow {
+                date: format_date(date),
+                balance,
+            })
+            .collect(),
+    }
+}
+
+fn trades_csv(result: &BacktestResult) -> String {
+    let mut csv = String::from(
+        "pair,is_short,open_date,close_date,open_rate,close_rate,amount,stake_amount,profit_abs,profit_ratio,exit_reason,trade_duration\n",
+    );
+    let optional = |value: Option<Decimal>| value.map(|v| v.normalize().to_string()).unwrap_or_default();
+    for trade in &result.trades {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            trade.pair,
+            trade.is_short,
+            format_date(trade.open_date),
+            trade.close_date.map(format_date).unwrap_or_default(),
+            trade.open_rate.normalize(),
+            optional(trade.close_rate),
+            trade.amount.normalize(),
+            trade.stake_amount.normalize(),
+            optional(trade.profit_abs),
+            optional(trade.profit_ratio),
+            trade.exit_reason.map(exit_reason_name).unwrap_or_default(),
+            trade
+                .close_date
+                .map(|d| (d - trade.open_date).num_minutes().to_string())
+                .unwrap_or_default()
+        );
+    }
+    csv
+}
+
+fn equity_csv(result: &BacktestResult) -> String {
+    let mut csv = String::from("date,balance\n");
+    for (date, balance) in equity_curve(result) {
+        let _ = writeln!(csv, "{},{}", format_date(date), balance.normalize());
+    }
+    csv
+}
+
+fn summary_csv(result: &BacktestResult) -> String {
+    let report = build_report(result);
+    let mut csv =
+        String::from("key,trades,profit_mean,profit_total_abs,profit_total,duration_avg,wins,losses,winrate\n");
+    for row in report.per_pair.iter().chain(std::iter::once(&report.total)) {
+        let row = export_row(row);
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{}",
+            row.key,
+            row.trades,
+            row.profit_mean,
+            row.profit_total_abs.normalize(),
+            row.profit_total,
+            row.duration_avg,
+            row.wins,
+            row.losses,
+            row.winrate
+        );
+    }
+    csv
+}
+
+/// Default export directory, `backtest_results` next to the database like freqtrade's `user_data`
+pub fn default_export_dir(database_path: &str) -> PathBuf {
+    Path::new(database_path)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("backtest_results")
+}
+
+/// Write `result` into `dir` in each of `formats`, returning the paths of the written files.
+/// Files are named after the export time like freqtrade's, e.g. `backtest-result-2024-01-31_12-00-00.json`.
+pub async fn export_result(result: &BacktestResult, dir: &Path, formats: &[ExportFormat]) -> Result<Vec<PathBuf>> {
+    tokio::fs::create_dir_all(dir).await?;
+    let stem = format!("backtest-result-{}", Utc::now().format("%Y-%m-%d_%H-%M-%S"));
+    let mut files = Vec::new();
+
+    for format in formats {
+        match format {
+            ExportFormat::Json => {
+                let export = ExportFile {
+                    strategy: BTreeMap::from([(result.strategy.clone(), strategy_export(result))]),
+                    strategy_comparison: vec![ExportRow {
+                        key: result.strategy.clone(),
+                        ..export_row(&build_report(result).total)
+                    }],
+                };
+                let path = dir.join(format!("{}.json", stem));
+                tokio::fs::write(&path, serde_json::to_string_pretty(&export)?).await?;
+                files.push(path);
+            }
+            ExportFormat::Csv => {
+                for (suffix, content) in [
+                    ("trades", trades_csv(result)),
+                    ("equity", equity_csv(result)),
+                    ("summary", summary_csv(result)),
+                ] {
+                    let path = dir.join(format!("{}-{}.csv", stem, suffix));
+                    tokio::fs::write(&path, content).await?;
+                    files.push(path);
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn import_trade(trade: ExportTrade, strategy: &str, timeframe: Timeframe) -> Result<Trade> {
+    let open_date = parse_date(Some(trade.open_timestamp), None)?
+        .ok_or_else(|| AppError::Parse(format!("Trade on {} has no open date", trade.pair)))?;
+    let close_date = parse_date(trade.close_timestamp, trade.close_date.as_deref())?;
+
+    Ok(Trade {
+        id: uuid::Uuid::new_v4(),
+        pair: trade.pair,
+        is_open: trade.is_open,
+        exchange: "backtest".to_string(),
+        open_rate: trade.open_rate,
+        open_date,
+        close_rate: trade.close_rate,
+        close_date,
+        amount: trade.amount,
+        stake_amount: trade.stake_amount,
+        strategy: strategy.to_string(),
+        timeframe,
+        stop_loss: trade.stop_loss_abs,
+        take_profit: trade.take_profit_abs,
+        exit_reason: trade.exit_reason.as_deref().map(parse_exit_reason),
+        profit_abs: trade.profit_abs,
+        profit_ratio: trade.profit_ratio,
+        is_short: trade.is_short,
+        leverage: trade.leverage,
+        liquidation_price: None,
+    })
+}
+
+fn import_strategy(name: String, export: StrategyExport) -> Result<BacktestResult> {
+    let timeframe: Timeframe = export.timeframe.parse()?;
+    let trades = export
+        .trades
+        .into_iter()
+        .map(|t| import_trade(t, &name, timeframe))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut pairs: Vec<String> = export
+        .results_per_pair
+        .into_iter()
+        .map(|row| row.key)
+        .filter(|key| key != "TOTAL")
+        .collect();
+    for trade in &trades {
+        if !pairs.contains(&trade.pair) {
+            pairs.push(trade.pair.clone());
+        }
+    }
+
+    let config = BacktestConfig {
+        timeframe,
+        start_date: parse_date(Some(export.backtest_start_ts), None)?.unwrap_or_default(),
+        end_date: parse_date(Some(export.backtest_end_ts), None)?.unwrap_or_default(),
+        stake_amount: 0.0,
+        starting_balance: export.starting_balance,
+        max_open_trades: 1,
+        commission: 0.0,
+        slippage: 0.0,
+        stoploss: None,
+        take_profit: None,
+    };
+    Ok(metrics::backtest_result(
+        &name,
+        &config,
+        &pairs,
+        trades,
+        export.market_change,
+    ))
+}
+
+/// Read the results of every strategy in a JSON export, with their metrics recomputed from the
+/// trades
+pub async fn import_results(path: &Path) -> Result<Vec<BacktestResult>> {
+    let content = tokio::fs::read_to_string(path).await?;
+    let export: ExportFile = serde_json::from_str(&content)?;
+    if export.strategy.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "{} contains no backtest results",
+            path.display()
+        )));
+    }
+    export
+        .strategy
+        .into_iter()
+        .map(|(name, export)| import_strategy(name, export))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::BacktestEngine;
+    use crate::strategy::Strategy;
+    use chrono::{Duration, TimeZone};
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    /// Buys every 10th candle and sells 5 candles later
+    struct Periodic;
+
+    #[async_trait::async_trait]
+    impl Strategy for Periodic {
+        fn name(&self) -> &str {
+            "Periodic"
+        }
+
+        fn timeframes(&self) -> &[Timeframe] {
+            &[Timeframe::OneHour]
+        }
+
+        fn startup_candle_count(&self) -> usize {
+            100
+        }
+
+        async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn populate_buy_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            let i = data.len() - 1;
+            Ok(if i.is_multiple_of(10) {
+                vec![signal(i, SignalType::Buy)]
+            } else {
+                vec![]
+            })
+        }
+
+        async fn populate_sell_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            let i = data.len() - 1;
+            Ok(if i % 10 == 5 {
+                vec![signal(i, SignalType::Sell)]
+            } else {
+                vec![]
+            })
+        }
+    }
+
+    fn signal(index: usize, r#type: SignalType) -> Signal {
+        Signal {
+            index,
+            r#type,
+            strength: 1.0,
+        }
+    }
+
+    async fn backtest() -> BacktestResult {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let candles = |base: i64| -> Vec<OHLCV> {
+            (0..200)
+                .map(|i| {
+                    let price = Decimal::from(base + (i % 7) * 3 - (i % 11));
+                    OHLCV {
+                        timestamp: start + Duration::hours(i),
+                        open: price,
+                        high: price + Decimal::ONE,
+                        low: price - Decimal::ONE,
+                        close: price,
+                        volume: Decimal::ONE,
+                    }
+                })
+                .collect()
+        };
+        let data = BTreeMap::from([
+            ("BTC/USDT".to_string(), candles(100)),
+            ("ETH/USDT".to_string(), candles(50)),
+        ]);
+        let config = BacktestConfig {
+            timeframe: Timeframe::OneHour,
+            start_date: start,
+            end_date: start + Duration::hours(199),
+            stake_amount: 100.0,
+            starting_balance: 1000.0,
+            max_open_trades: 2,
+            commission: 0.001,
+            slippage: 0.0,
+            stoploss: Some(-0.3),
+            take_profit: None,
+        };
+        BacktestEngine::new(config, Arc::new(Periodic), data)
+            .run()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_export_roundtrip() {
+        let result = backtest().await;
+        assert!(result.total_trades > 10);
+        let dir = tempdir().unwrap();
+
+        let files = export_result(&result, dir.path(), &[ExportFormat::Json]).await.unwrap();
+        assert_eq!(files.len(), 1);
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+        let strategy = &json["strategy"]["Periodic"];
+        assert_eq!(strategy["total_trades"], result.total_trades);
+        assert_eq!(strategy["timeframe"], "1h");
+        assert_eq!(strategy["backtest_start"], "2024-01-01 00:00:00+00:00");
+        assert_eq!(strategy["trades"][0]["exit_reason"], "exit_signal");
+        assert_eq!(strategy["trades"][0]["trade_duration"], 300);
+        assert_eq!(
+            strategy["equity_curve"].as_array().unwrap().len(),
+            result.total_trades + 1
+        );
+        assert_eq!(json["strategy_comparison"][0]["key"], "Periodic");
+
+        let imported = import_results(&files[0]).await.unwrap();
+        assert_eq!(imported.len(), 1);
+        let imported = &imported[0];
+        assert_eq!(imported.strategy, result.strategy);
+        assert_eq!(imported.pair, result.pair);
+        assert_eq!(imported.timeframe, result.timeframe);
+        assert_eq!(imported.start_date, result.start_date);
+        assert_eq!(imported.end_date, result.end_date);
+        assert_eq!(imported.total_trades, result.total_trades);
+        // Amounts are JSON numbers, exact to the precision of an f64
+        assert_eq!(imported.total_profit.round_dp(8), result.total_profit.round_dp(8));
+        assert!((imported.max_drawdown - result.max_drawdown).abs() < 1e-9);
+        assert!((imported.sharpe_ratio - result.sharpe_ratio).abs() < 1e-9);
+        assert_eq!(imported.market_change, result.market_change);
+        for (a, b) in imported.trades.iter().zip(&result.trades) {
+            assert_eq!(
+                (a.open_date, a.close_date, a.exit_reason),
+                (b.open_date, b.close_date, b.exit_reason)
+            );
+            let rounded = |t: &Trade| (t.profit_abs.map(|p| p.round_dp(8)), t.stop_loss.map(|p| p.round_dp(8)));
+            assert_eq!(rounded(a), rounded(b));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_csv_export() {
+        let result = backtest().await;
+        let dir = tempdir().unwrap();
+
+        let files = export_result(&result, dir.path(), &[ExportFormat::Csv]).await.unwrap();
+        let names: Vec<String> = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert!(names[0].starts_with("backtest-result-") && names[0].ends_with("-trades.csv"));
+        assert!(names[1].ends_with("-equity.csv"));
+        assert!(names[2].ends_with("-summary.csv"));
+
+        let trades = std::fs::read_to_string(&files[0]).unwrap();
+        assert_eq!(trades.lines().count(), result.trades.len() + 1);
+        assert!(
+            trades
+                .lines()
+                .nth(1)
+                .unwrap()
+                .starts_with("BTC/USDT,false,2024-01-05 05:00:00+00:00,")
+        );
+
+        let equity = std::fs::read_to_string(&files[1]).unwrap();
+        assert_eq!(equity.lines().nth(1), Some("2024-01-01 00:00:00+00:00,1000"));
+
+        let summary = std::fs::read_to_string(&files[2]).unwrap();
+        let keys: Vec<&str> = summary.lines().skip(1).map(|l| l.split(',').next().unwrap()).collect();
+        assert_eq!(keys, vec!["BTC/USDT", "ETH/USDT", "TOTAL"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_empty_and_invalid_files() {
+        let dir = tempdir().unwrap();
+        let empty = dir.path().join("empty.json");
+        std::fs::write(&empty, r#"{"strategy": {}}"#).unwrap();
+        assert!(matches!(import_results(&empty).await, Err(AppError::InvalidInput(_))));
+
+        let invalid = dir.path().join("invalid.json");
+        std::fs::write(&invalid, "not json").unwrap();
+        assert!(matches!(
+            import_results(&invalid).await,
+            Err(AppError::Serialization(_))
+        ));
+
+        assert!(matches!(
+            import_results(&dir.path().join("missing.json")).await,
+            Err(AppError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,368 @@
+//! Lookahead-bias analysis: finds strategies whose signals or indicators at a candle depend on the
+//! candles that come after it.
+//!
+//! The strategy is evaluated once on the full dataset and again on prefixes ending at each checked
+//! candle. Live trading only ever sees such a prefix, so any difference at the last candle of a
+//! prefix means the full-data evaluation, and every backtest built on it, used future data.
+
+use crate::error::{AppError, Result};
+use crate::strategy::Strategy;
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LookaheadConfig {
+    /// Check every `step`-th candle; candles the full evaluation signals on are always checked
+    pub step: usize,
+}
+
+impl Default for LookaheadConfig {
+    fn default() -> Self {
+        Self { step: 1 }
+    }
+}
+
+/// Signal that differs between the full dataset and the prefix ending at `index`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignalDiff {
+    pub index: usize,
+    pub timestamp: DateTime<Utc>,
+    pub signal: SignalType,
+    /// Signalled when evaluated on the full dataset
+    pub full: bool,
+    /// Signalled when evaluated on the prefix
+    pub truncated: bool,
+}
+
+/// Indicator whose value changed at one or more checked candles
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndicatorBias {
+    pub name: String,
+    pub changed_candles: usize,
+    pub first_index: usize,
+    pub first_timestamp: DateTime<Utc>,
+    /// Value at `first_index` on the full dataset
+    pub full_value: Option<Decimal>,
+    /// Value at `first_index` on the prefix ending there
+    pub truncated_value: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookaheadReport {
+    pub strategy: String,
+    pub pair: String,
+    pub candles: usize,
+    pub checked_candles: usize,
+    /// Signals of the full evaluation
+    pub buy_signals: usize,
+    pub sell_signals: usize,
+    pub biased_signals: Vec<SignalDiff>,
+    pub biased_indicators: Vec<IndicatorBias>,
+}
+
+impl LookaheadReport {
+    pub fn has_bias(&self) -> bool {
+        !self.biased_signals.is_empty() || !self.biased_indicators.is_empty()
+    }
+}
+
+fn signal_indices(signals: Vec<Signal>) -> BTreeSet<usize> {
+    signals.into_iter().map(|s| s.index).collect()
+}
+
+/// Compare the strategy's signals and indicators on `data` with those on its prefixes
+pub async fn analyze_lookahead(
+    strategy: &dyn Strategy,
+    pair: &str,
+    data: &[OHLCV],
+    config: &LookaheadConfig,
+) -> Result<LookaheadReport> {
+    if config.step == 0 {
+        return Err(AppError::InvalidInput("step must be at least 1".to_string()));
+    }
+    if data.is_empty() {
+        return Err(AppError::InvalidInput(format!("No candles to analyze for {}", pair)));
+    }
+
+    let full_buys = signal_indices(strategy.populate_buy_trend(data).await?);
+    let full_sells = signal_indices(strategy.populate_sell_trend(data).await?);
+    let full_indicators = strategy.indicator_values(data).await?;
+
+    let checkpoints: BTreeSet<usize> = (0..data.len())
+        .step_by(config.step)
+        .chain(full_buys.iter().copied())
+        .chain(full_sells.iter().copied())
+        .filter(|&i| i < data.len())
+        .collect();
+
+    let mut biased_signals = Vec::new();
+    let mut biased_indicators: BTreeMap<String, IndicatorBias> = BTreeMap::new();
+    for &index in &checkpoints {
+        let prefix = &data[..=index];
+        let timestamp = data[index].timestamp;
+
+        let buys = strategy.populate_buy_trend(prefix).await?;
+        let sells = strategy.populate_sell_trend(prefix).await?;
+        for (signal, full, truncated) in [
+            (SignalType::Buy, &full_buys, buys),
+            (SignalType::Sell, &full_sells, sells),
+        ] {
+            let full = full.contains(&index);
+            let truncated = truncated.iter().any(|s| s.index == index);
+            if full != truncated {
+                biased_signals.push(SignalDiff {
+                    index,
+                    timestamp,
+                    signal,
+                    full,
+                    truncated,
+                });
+            }
+        }
+
+        let indicators = strategy.indicator_values(prefix).await?;
+        for (name, series) in &full_indicators {
+            let full_value = series.get(index).copied().flatten();
+            let truncated_value = indicators.get(name).and_then(|s| s.get(index)).copied().flatten();
+            if full_value == truncated_value {
+                continue;
+            }
+            biased_indicators
+                .entry(name.clone())
+                .and_modify(|bias| bias.changed_candles += 1)
+                .or_insert_with(|| IndicatorBias {
+                    name: name.clone(),
+                    changed_candles: 1,
+                    first_index: index,
+                    first_timestamp: timestamp,
+                    full_value,
+                    truncated_value,
+                });
+        }
+    }
+
+    Ok(LookaheadReport {
+        strategy: strategy.name().to_string(),
+        pair: pair.to_string(),
+        candles: data.len(),
+        checked_candles: checkpoints.len(),
+        buy_signals: full_buys.len(),
+        sell_signals: full_sells.len(),
+        biased_signals,
+        biased_indicators: biased_indicators.into_values().collect(),
+    })
+}
+
+fn format_value(value: Option<Decimal>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| v.normalize().to_string())
+}
+
+/// Plain-text report of one pair's analysis, listing at most `max_rows` changed signals
+pub fn render_text(report: &LookaheadReport, max_rows: usize) -> String {
+    let mut out = String::new();
+    let verdict = if report.has_bias() {
+        "LOOKAHEAD BIAS"
+    } else {
+        "no bias found"
+    };
+    let _ = writeln!(
+        out,
+        "{} on {}: {} ({} of {} candles checked, {} buy / {} sell signals)",
+        report.strategy,
+        report.pair,
+        verdict,
+        report.checked_candles,
+        report.candles,
+        report.buy_signals,
+        report.sell_signals
+    );
+
+    if !report.biased_signals.is_empty() {
+        let _ = writeln!(
+            out,
+            "  {:>7} {:<17} {:<5} {:>5} {:>9}",
+            "Index", "Date", "Type", "Full", "Truncated"
+        );
+        for diff in report.biased_signals.iter().take(max_rows) {
+            let _ = writeln!(
+                out,
+                "  {:>7} {:<17} {:<5} {:>5} {:>9}",
+                diff.index,
+                diff.timestamp.format("%Y-%m-%d %H:%M"),
+                match diff.signal {
+                    SignalType::Buy => "buy",
+                    SignalType::Sell => "sell",
+                },
+                diff.full,
+                diff.truncated
+            );
+        }
+        if report.biased_signals.len() > max_rows {
+            let _ = writeln!(out, "  ... {} more", report.biased_signals.len() - max_rows);
+        }
+    }
+
+    for bias in &report.biased_indicators {
+        let _ = writeln!(
+            out,
+            "  indicator {} changed on {} candles, first at {} {}: {} on full data, {} truncated",
+            bias.name,
+            bias.changed_candles,
+            bias.first_index,
+            bias.first_timestamp.format("%Y-%m-%d %H:%M"),
+            format_value(bias.full_value),
+            format_value(bias.truncated_value)
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::indicators::SMA;
+    use async_trait::async_trait;
+    use chrono::{Duration, TimeZone};
+
+    fn candles(closes: &[i64]) -> Vec<OHLCV> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| OHLCV {
+                timestamp: start + Duration::hours(i as i64),
+                open: Decimal::from(close),
+                high: Decimal::from(close),
+                low: Decimal::from(close),
+                close: Decimal::from(close),
+                volume: Decimal::ONE,
+            })
+            .collect()
+    }
+
+    /// Buys when the close rises; with `peek` it compares against the next candle instead
+    struct Momentum {
+        peek: bool,
+    }
+
+    #[async_trait]
+    impl Strategy for Momentum {
+        fn name(&self) -> &str {
+            "Momentum"
+        }
+
+        fn timeframes(&self) -> &[Timeframe] {
+            &[Timeframe::OneHour]
+        }
+
+        async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn populate_buy_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok((1..data.len())
+                .filter(|&i| {
+                    if self.peek {
+                        data.get(i + 1).is_some_and(|next| next.close > data[i].close)
+                    } else {
+                        data[i].close > data[i - 1].close
+                    }
+                })
+                .map(|index| Signal {
+                    index,
+                    r#type: SignalType::Buy,
+                    strength: 1.0,
+                })
+                .collect())
+        }
+
+        async fn populate_sell_trend(&self, _data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(vec![])
+        }
+
+        async fn indicator_values(&self, data: &[OHLCV]) -> Result<BTreeMap<String, Vec<Option<Decimal>>>> {
+            let mut sma = SMA::new(3).calculate(data)?;
+            if self.peek {
+                // Centered average: shifted back so each value includes the next candle
+                sma.remove(0);
+                sma.push(None);
+            }
+            Ok(BTreeMap::from([("sma_3".to_string(), sma)]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strategy_without_lookahead_is_clean() {
+        let data = candles(&[100, 101, 99, 103, 104, 102, 105, 101]);
+        let report = analyze_lookahead(
+            &Momentum { peek: false },
+            "BTC/USDT",
+            &data,
+            &LookaheadConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!report.has_bias());
+        assert_eq!(report.candles, 8);
+        assert_eq!(report.checked_candles, 8);
+        assert_eq!(report.buy_signals, 4);
+        assert!(render_text(&report, 10).contains("no bias found"));
+    }
+
+    #[tokio::test]
+    async fn test_peeking_signals_and_indicators_are_flagged() {
+        let data = candles(&[100, 101, 99, 103, 104, 102, 105, 101]);
+        let report = analyze_lookahead(&Momentum { peek: true }, "BTC/USDT", &data, &LookaheadConfig::default())
+            .await
+            .unwrap();
+
+        assert!(report.has_bias());
+        // Full data buys on 2, 3 and 5, which the prefixes cannot see
+        let flagged: Vec<usize> = report.biased_signals.iter().map(|d| d.index).collect();
+        assert_eq!(flagged, vec![2, 3, 5]);
+        assert!(report.biased_signals.iter().all(|d| d.full && !d.truncated));
+        assert_eq!(report.biased_signals[0].timestamp, data[2].timestamp);
+
+        let sma = &report.biased_indicators[0];
+        assert_eq!(sma.name, "sma_3");
+        assert_eq!(sma.first_index, 1);
+        // (100 + 101 + 99) / 3 with the next candle, nothing yet on the prefix
+        assert_eq!(sma.full_value, Some(Decimal::from(100)));
+        assert_eq!(sma.truncated_value, None);
+        // Every candle but the first and the last differs
+        assert_eq!(sma.changed_candles, 6);
+
+        let text = render_text(&report, 2);
+        assert!(text.contains("LOOKAHEAD BIAS"));
+        assert!(text.contains("... 1 more"));
+        assert!(text.contains("indicator sma_3 changed on 6 candles"));
+    }
+
+    #[tokio::test]
+    async fn test_step_still_checks_signal_candles() {
+        let data = candles(&[100, 101, 99, 103, 104, 102, 105, 101]);
+        let config = LookaheadConfig { step: 4 };
+        let report = analyze_lookahead(&Momentum { peek: true }, "BTC/USDT", &data, &config)
+            .await
+            .unwrap();
+
+        // 0 and 4 from the step, 2, 3 and 5 from the full signals
+        assert_eq!(report.checked_candles, 5);
+        assert_eq!(report.biased_signals.len(), 3);
+
+        let invalid = analyze_lookahead(
+            &Momentum { peek: true },
+            "BTC/USDT",
+            &data,
+            &LookaheadConfig { step: 0 },
+        )
+        .await;
+        assert!(matches!(invalid, Err(AppError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,194 @@
+//! Summary statistics computed from the closed trades of a backtest.
+
+use super::BacktestConfig;
+use crate::types::*;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::BTreeMap;
+
+/// Days per year used to annualize the Sharpe ratio, as crypto markets trade every day
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Build the result of a run over `pairs` from its trades
+pub fn backtest_result(
+    strategy: &str,
+    config: &BacktestConfig,
+    pairs: &[String],
+    trades: Vec<Trade>,
+    market_change: f64,
+) -> BacktestResult {
+    let closed: Vec<&Trade> = trades.iter().filter(|t| !t.is_open).collect();
+    let profits: Vec<Decimal> = closed.iter().map(|t| t.profit_abs.unwrap_or(Decimal::ZERO)).collect();
+    let wins: Vec<Decimal> = profits.iter().copied().filter(|p| *p > Decimal::ZERO).collect();
+    let losses: Vec<Decimal> = profits.iter().copied().filter(|p| *p <= Decimal::ZERO).collect();
+
+    let starting_balance = Decimal::try_from(config.starting_balance).unwrap_or(Decimal::ZERO);
+    let per_pair = pairs
+        .iter()
+        .map(|pair| pair_result(pair, &closed, starting_balance))
+        .collect();
+
+    BacktestResult {
+        strategy: strategy.to_string(),
+        pair: pairs.join(","),
+        timeframe: config.timeframe,
+        start_date: config.start_date,
+        end_date: config.end_date,
+        total_trades: closed.len(),
+        winning_trades: wins.len(),
+        losing_trades: losses.len(),
+        win_rate: if closed.is_empty() {
+            0.0
+        } else {
+            wins.len() as f64 / closed.len() as f64
+        },
+        total_profit: profits.iter().sum(),
+        max_drawdown: max_drawdown(&trades, starting_balance),
+        sharpe_ratio: sharpe_ratio(&trades, starting_balance, config),
+        profit_factor: profit_factor(&trades),
+        avg_profit: mean(&wins),
+        avg_loss: mean(&losses),
+        trades,
+        per_pair,
+        starting_balance: config.starting_balance,
+        market_change,
+    }
+}
+
+fn pair_result(pair: &str, closed: &[&Trade], starting_balance: Decimal) -> PairResult {
+    let trades: Vec<&&Trade> = closed.iter().filter(|t| t.pair == pair).collect();
+    let winning_trades = trades
+        .iter()
+        .filter(|t| t.profit_abs.is_some_and(|p| p > Decimal::ZERO))
+        .count();
+    let total_profit: Decimal = trades.iter().filter_map(|t| t.profit_abs).sum();
+    let ratio_sum: f64 = trades
+        .iter()
+        .filter_map(|t| t.profit_ratio.and_then(|r| r.to_f64()))
+        .sum();
+
+    PairResult {
+        pair: pair.to_string(),
+        total_trades: trades.len(),
+        winning_trades,
+        losing_trades: trades.len() - winning_trades,
+        win_rate: if trades.is_empty() {
+            0.0
+        } else {
+            winning_trades as f64 / trades.len() as f64
+        },
+        total_profit,
+        avg_profit_ratio: if trades.is_empty() {
+            0.0
+        } else {
+            ratio_sum / trades.len() as f64
+        },
+        total_profit_ratio: if starting_balance.is_zero() {
+            0.0
+        } else {
+            (total_profit / starting_balance).to_f64().unwrap_or(0.0)
+        },
+    }
+}
+
+/// Closed trades ordered by close date
+fn closed_by_date(trades: &[Trade]) -> Vec<&Trade> {
+    let mut closed: Vec<&Trade> = trades.iter().filter(|t| !t.is_open && t.close_date.is_some()).collect();
+    closed.sort_by_key(|t| t.close_date);
+    closed
+}
+
+/// Mean change from the first to the last close of every pair, what buying and holding all of
+/// them in equal parts would have returned
+pub fn market_change(data: &BTreeMap<String, Vec<OHLCV>>, since: DateTime<Utc>) -> f64 {
+    let changes: Vec<f64> = data
+        .values()
+        .filter_map(|candles| {
+            let from = candles.partition_point(|c| c.timestamp < since);
+            let (first, last) = (candles[from..].first()?, candles.last()?);
+            if first.close.is_zero() {
+                return None;
+            }
+            ((last.close - first.close) / first.close).to_f64()
+        })
+        .collect();
+    if changes.is_empty() {
+        return 0.0;
+    }
+    changes.iter().sum::<f64>() / changes.len() as f64
+}
+
+/// Largest peak-to-trough decline of the balance as a ratio of the peak
+pub fn max_drawdown(trades: &[Trade], starting_balance: Decimal) -> f64 {
+    let mut balance = starting_balance;
+    let mut peak = starting_balance;
+    let mut max_drawdown = Decimal::ZERO;
+
+    for trade in closed_by_date(trades) {
+        balance += trade.profit_abs.unwrap_or(Decimal::ZERO);
+        peak = peak.max(balance);
+        if peak > Decimal::ZERO {
+            max_drawdown = max_drawdown.max((peak - balance) / peak);
+        }
+    }
+    max_drawdown.to_f64().unwrap_or(0.0)
+}
+
+/// Annualized Sharpe ratio of daily returns over the backtested period, days without exits
+/// counting as zero return
+pub fn sharpe_ratio(trades: &[Trade], starting_balance: Decimal, config: &BacktestConfig) -> f64 {
+    let Some(starting_balance) = starting_balance.to_f64().filter(|b| *b > 0.0) else {
+        return 0.0;
+    };
+
+    let mut daily: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    let mut day = config.start_date.date_naive();
+    while day <= config.end_date.date_naive() {
+        daily.insert(day, 0.0);
+        day = day.succ_opt().unwrap_or(NaiveDate::MAX);
+    }
+    for trade in closed_by_date(trades) {
+        if let Some(close_date) = trade.close_date {
+            let profit = trade.profit_abs.and_then(|p| p.to_f64()).unwrap_or(0.0);
+            *daily.entry(close_date.date_naive()).or_default() += profit / starting_balance;
+        }
+    }
+
+    let returns: Vec<f64> = daily.into_values().collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    let std_dev = variance.sqrt();
+    if std_dev == 0.0 {
+        return 0.0;
+    }
+    mean / std_dev * DAYS_PER_YEAR.sqrt()
+}
+
+/// Gross profit divided by gross loss; 0 when there are no losing trades, as freqtrade reports it
+pub fn profit_factor(trades: &[Trade]) -> f64 {
+    let (gross_profit, gross_loss) = trades.iter().filter(|t| !t.is_open).filter_map(|t| t.profit_abs).fold(
+        (Decimal::ZERO, Decimal::ZERO),
+        |(profit, loss), p| {
+            if p > Decimal::ZERO {
+                (profit + p, loss)
+            } else {
+                (profit, loss - p)
+            }
+        },
+    );
+    if gross_loss.is_zero() {
+        return 0.0;
+    }
+    (gross_profit / gross_loss).to_f64().unwrap_or(0.0)
+}
+
+fn mean(values: &[Decimal]) -> Decimal {
+    if values.is_empty() {
+        return Decimal::ZERO;
+    }
+    values.iter().sum::<Decimal>() / Decimal::from(values.len())
+}
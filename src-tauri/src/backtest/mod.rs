@@ -1,113 +1,1085 @@
-use crate::error::Result;
+pub mod export;
+pub mod lookahead;
+pub mod metrics;
+pub mod monte_carlo;
+pub mod recursive;
+pub mod report;
+
+use crate::config::BotConfig;
+use crate::data::downloader::TimeRange;
+use crate::error::{AppError, Result};
+use crate::persistence::Repository;
+use crate::persistence::repository::parse_timeframe_to_duration;
+use crate::trade::{calculate_fee, simulate_slippage};
 use crate::types::*;
-use rust_decimal::prelude::ToPrimitive;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
-#[allow(dead_code)]
+/// Fee rate used when none is given, Binance's spot taker fee
+pub const DEFAULT_COMMISSION: f64 = 0.001;
+
 pub struct BacktestEngine {
     config: BacktestConfig,
     strategy: Arc<dyn crate::strategy::Strategy>,
-    data: Vec<OHLCV>,
+    /// Candles of every backtested pair, each ordered by time
+    data: BTreeMap<String, Vec<OHLCV>>,
+    /// Lower timeframe candles used to resolve stoploss and take-profit within a candle
+    detail: Option<(Timeframe, BTreeMap<String, Vec<OHLCV>>)>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct BacktestConfig {
+    #[serde(default = "default_timeframe")]
+    pub timeframe: Timeframe,
     pub start_date: chrono::DateTime<chrono::Utc>,
     pub end_date: chrono::DateTime<chrono::Utc>,
+    /// Quote currency put into each trade; 0 splits the available balance over the free slots
     pub stake_amount: f64,
+    /// Balance shared by all pairs at the start, like `bot.dry_run_wallet`
+    #[serde(default = "default_starting_balance")]
+    pub starting_balance: f64,
+    /// Trades open at the same time across all pairs, like `bot.max_open_trades`
+    #[serde(default = "default_max_open_trades")]
+    pub max_open_trades: usize,
+    /// Fee rate charged on both entry and exit
     pub commission: f64,
+    /// Expected slippage ratio of market orders, see [`simulate_slippage`]
+    #[serde(default)]
+    pub slippage: f64,
+    /// Exit once the price falls this ratio below the entry, e.g. -0.10
+    #[serde(default)]
+    pub stoploss: Option<f64>,
+    /// Exit once the price rises this ratio above the entry, e.g. 0.04
+    #[serde(default)]
+    pub take_profit: Option<f64>,
+}
+
+impl BacktestConfig {
+    /// Stake, balance and trade limit of the bot configuration over the period covered by `data`.
+    /// With a `timerange` the period starts at its start, after the startup candles loaded before it.
+    pub fn from_bot(
+        bot: &BotConfig,
+        timeframe: Timeframe,
+        data: &BTreeMap<String, Vec<OHLCV>>,
+        timerange: Option<&TimeRange>,
+    ) -> Self {
+        let first = data.values().filter_map(|c| c.first()).map(|c| c.timestamp).min();
+        let start_date = match timerange {
+            Some(range) => first.map(|first| first.max(range.start)),
+            None => first,
+        };
+        let end_date = data.values().filter_map(|c| c.last()).map(|c| c.timestamp).max();
+        Self {
+            timeframe,
+            start_date: start_date.unwrap_or_default(),
+            end_date: end_date.unwrap_or_default(),
+            stake_amount: bot.stake_amount,
+            starting_balance: bot.dry_run_wallet,
+            max_open_trades: bot.max_open_trades,
+            commission: DEFAULT_COMMISSION,
+            slippage: 0.0,
+            stoploss: None,
+            take_profit: None,
+        }
+    }
+}
+
+/// Backtest parameters sent by the UI; anything left out falls back to the bot configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacktestRequest {
+    /// Defaults to `strategy.name`
+    #[serde(default)]
+    pub strategy: Option<String>,
+    /// Defaults to `bot.trading_pairs`
+    #[serde(default)]
+    pub pairs: Vec<String>,
+    /// Defaults to `bot.timeframe`
+    #[serde(default)]
+    pub timeframe: Option<Timeframe>,
+    /// Lower timeframe (e.g. `1m`) used to resolve stoploss and take-profit exits within a candle
+    #[serde(default)]
+    pub timeframe_detail: Option<Timeframe>,
+    /// `YYYYMMDD-YYYYMMDD`; all stored candles when omitted
+    #[serde(default)]
+    pub timerange: Option<String>,
+    #[serde(default)]
+    pub stake_amount: Option<f64>,
+    #[serde(default)]
+    pub starting_balance: Option<f64>,
+    #[serde(default)]
+    pub commission: Option<f64>,
+    #[serde(default)]
+    pub slippage: Option<f64>,
+    #[serde(default)]
+    pub stoploss: Option<f64>,
+    #[serde(default)]
+    pub take_profit: Option<f64>,
+}
+
+/// Load the stored candles of every pair within `timerange`, preceded by `startup_candles` candles
+/// from before its start so indicators have settled when it begins. Fails for pairs without data.
+pub async fn load_candles(
+    repository: &Repository,
+    pairs: &[String],
+    timeframe: &str,
+    timerange: Option<&TimeRange>,
+    startup_candles: usize,
+) -> Result<BTreeMap<String, Vec<OHLCV>>> {
+    if pairs.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No pairs given and bot.trading_pairs is empty".to_string(),
+        ));
+    }
+
+    let startup = parse_timeframe_to_duration(timeframe)? * startup_candles as i32;
+    let mut data = BTreeMap::new();
+    for pair in pairs {
+        let candles = repository
+            .get_klines_between(
+                pair,
+                timeframe,
+                timerange.map(|r| r.start - startup),
+                timerange.map(|r| r.end),
+            )
+            .await?;
+        if candles.is_empty() {
+            return Err(AppError::NotFound(format!(
+                "No {} candles stored for {}, run download-data first",
+                timeframe, pair
+            )));
+        }
+        data.insert(pair.clone(), candles);
+    }
+    Ok(data)
+}
+
+fn default_timeframe() -> Timeframe {
+    Timeframe::OneHour
+}
+
+fn default_starting_balance() -> f64 {
+    1000.0
+}
+
+fn default_max_open_trades() -> usize {
+    3
+}
+
+/// A trade while it is open, with the fee paid on entry
+struct Position {
+    trade: Trade,
+    entry_fee: Decimal,
+}
+
+/// Replay state of one pair
+#[derive(Default)]
+struct PairState {
+    /// Index of the next candle to process
+    cursor: usize,
+    position: Option<Position>,
+    enter_next: bool,
+    exit_next: bool,
+}
+
+/// Signals of one pair computed over its whole history, by candle index
+struct SignalSeries {
+    buy: Vec<bool>,
+    sell: Vec<bool>,
 }
 
 impl BacktestEngine {
-    #[allow(dead_code)]
-    pub fn new(config: BacktestConfig, strategy: Arc<dyn crate::strategy::Strategy>, data: Vec<OHLCV>) -> Self {
-        Self { config, strategy, data }
+    pub fn new(
+        config: BacktestConfig,
+        strategy: Arc<dyn crate::strategy::Strategy>,
+        data: BTreeMap<String, Vec<OHLCV>>,
+    ) -> Self {
+        Self {
+            config,
+            strategy,
+            data,
+            detail: None,
+        }
     }
 
-    #[allow(dead_code)]
+    /// Walk the `timeframe` candles within each candle of the backtested timeframe to find out
+    /// whether the stoploss or the take-profit was reached first. Candles without detail data
+    /// fall back to the old assumption that the stoploss triggered first.
+    pub fn with_detail(mut self, timeframe: Timeframe, data: BTreeMap<String, Vec<OHLCV>>) -> Self {
+        self.detail = Some((timeframe, data));
+        self
+    }
+
+    /// Backtest a single pair
+    pub fn single_pair(
+        config: BacktestConfig,
+        strategy: Arc<dyn crate::strategy::Strategy>,
+        pair: &str,
+        data: Vec<OHLCV>,
+    ) -> Self {
+        Self::new(config, strategy, BTreeMap::from([(pair.to_string(), data)]))
+    }
+
+    /// Replay all pairs on a shared timeline: signals are evaluated on closed candles and filled at
+    /// the open of the next one, stoploss and take-profit are checked against every candle's low
+    /// and high. Entries share one balance and are limited to `max_open_trades`; when several
+    /// pairs signal on the same candle they are filled in pair order.
     pub async fn run(&mut self) -> Result<BacktestResult> {
+        if self.config.max_open_trades == 0 {
+            return Err(AppError::Backtest("max_open_trades must be at least 1".to_string()));
+        }
+        let candle_duration = parse_timeframe_to_duration(self.config.timeframe.as_str())?;
+        if let Some((timeframe, _)) = &self.detail
+            && parse_timeframe_to_duration(timeframe.as_str())? >= candle_duration
+        {
+            return Err(AppError::InvalidInput(format!(
+                "timeframe_detail {} must be shorter than the timeframe {}",
+                timeframe.as_str(),
+                self.config.timeframe.as_str()
+            )));
+        }
+
+        let startup_candles = self.strategy.startup_candle_count();
+
+        // Strategies whose signals only look back are evaluated once per pair instead of on every
+        // candle's history, which is quadratic in the number of candles
+        let mut precomputed = BTreeMap::new();
+        if self.strategy.vectorized() {
+            for (pair, candles) in &self.data {
+                precomputed.insert(pair.as_str(), self.signal_series(candles).await?);
+            }
+        }
+
+        let timeline: BTreeSet<DateTime<Utc>> = self.data.values().flatten().map(|c| c.timestamp).collect();
+        let mut states: BTreeMap<&str, PairState> = self
+            .data
+            .keys()
+            .map(|pair| (pair.as_str(), PairState::default()))
+            .collect();
         let mut trades = Vec::new();
-        let mut balance = self.config.stake_amount;
+        let mut balance = Decimal::try_from(self.config.starting_balance).unwrap_or(Decimal::ZERO);
 
-        for (i, candle) in self.data.iter().enumerate() {
-            if i < 100 {
-                continue;
+        for timestamp in timeline {
+            // Pairs with a candle at this time, with that candle's index
+            let active: Vec<(&str, usize)> = states
+                .iter()
+                .filter(|(pair, state)| {
+                    self.data[**pair]
+                        .get(state.cursor)
+                        .is_some_and(|c| c.timestamp == timestamp)
+                })
+                .map(|(pair, state)| (*pair, state.cursor))
+                .collect();
+
+            // Exits first so their funds and slots are available to entries on the same candle
+            for &(pair, i) in &active {
+                let state = states.get_mut(pair).expect("active pair has a state");
+                let candle = &self.data[pair][i];
+                if state.exit_next
+                    && let Some(open) = state.position.take()
+                {
+                    let price = self.fill_price(candle.open, open.trade.amount, TradeSide::Sell, "market");
+                    trades.push(self.close(open, price, candle, ExitType::Signal, &mut balance));
+                }
             }
 
-            let data_slice = &self.data[..=i];
-
-            let buy_signals = self.strategy.populate_buy_trend(data_slice).await?;
-            let sell_signals = self.strategy.populate_sell_trend(data_slice).await?;
-
-            if !buy_signals.is_empty() {
-                let trade = Trade {
-                    id: uuid::Uuid::new_v4(),
-                    pair: "BTCUSDT".to_string(),
-                    is_open: true,
-                    exchange: "binance".to_string(),
-                    open_rate: candle.close,
-                    open_date: candle.timestamp,
-                    close_rate: None,
-                    close_date: None,
-                    amount: rust_decimal::Decimal::try_from(balance).unwrap_or(rust_decimal::Decimal::ZERO),
-                    stake_amount: rust_decimal::Decimal::try_from(balance).unwrap_or(rust_decimal::Decimal::ZERO),
-                    strategy: self.strategy.name().to_string(),
-                    timeframe: crate::types::Timeframe::OneHour,
-                    stop_loss: None,
-                    take_profit: None,
-                    exit_reason: None,
-                    profit_abs: None,
-                    profit_ratio: None,
-                };
-                trades.push(trade);
-                balance = 0.0;
-            } else if !sell_signals.is_empty() && !trades.is_empty() {
-                if let Some(trade) = trades.last_mut() {
-                    if trade.is_open {
-                        trade.is_open = false;
-                        trade.close_rate = Some(candle.close);
-                        trade.close_date = Some(candle.timestamp);
-                        trade.exit_reason = Some(ExitType::Signal);
-                        let profit = (trade.amount * candle.close).to_f64().unwrap_or(0.0);
-                        balance = profit * (1.0 - self.config.commission);
+            for &(pair, i) in &active {
+                let open_trades = states.values().filter(|s| s.position.is_some()).count();
+                let state = states.get_mut(pair).expect("active pair has a state");
+                if state.enter_next && state.position.is_none() && open_trades < self.config.max_open_trades {
+                    let candle = &self.data[pair][i];
+                    state.position = self.open(pair, candle, open_trades, &mut balance);
+                }
+            }
+
+            for &(pair, i) in &active {
+                let state = states.get_mut(pair).expect("active pair has a state");
+                let candle = &self.data[pair][i];
+                state.enter_next = false;
+                state.exit_next = false;
+                state.cursor += 1;
+
+                if let Some(open) = state.position.take() {
+                    let exit = self
+                        .intra_candles(pair, candle, candle_duration)
+                        .iter()
+                        .find_map(|c| Some((self.intra_candle_exit(&open.trade, c)?, c)));
+                    match exit {
+                        Some(((price, reason), c)) => trades.push(self.close(open, price, c, reason, &mut balance)),
+                        None => state.position = Some(open),
                     }
                 }
+
+                let candles = &self.data[pair];
+                if i < startup_candles || i + 1 == candles.len() {
+                    continue;
+                }
+                if let Some(series) = precomputed.get(pair) {
+                    state.exit_next = state.position.is_some() && series.sell[i];
+                    state.enter_next = state.position.is_none() && series.buy[i];
+                    continue;
+                }
+                let data_slice = &candles[..=i];
+                if state.position.is_some() {
+                    let sell_signals = self.strategy.populate_sell_trend(data_slice).await?;
+                    state.exit_next = sell_signals.iter().any(|s| s.index == i);
+                } else {
+                    let buy_signals = self.strategy.populate_buy_trend(data_slice).await?;
+                    state.enter_next = buy_signals.iter().any(|s| s.index == i);
+                }
             }
         }
 
-        let winning_trades = trades
-            .iter()
-            .filter(|t| !t.is_open && t.profit_abs.map(|p| p > rust_decimal::Decimal::ZERO).unwrap_or(false))
-            .count();
-        let losing_trades = trades
-            .iter()
-            .filter(|t| !t.is_open && t.profit_abs.map(|p| p <= rust_decimal::Decimal::ZERO).unwrap_or(true))
-            .count();
+        // Positions still open at the end are closed at the last close of their pair
+        for (pair, state) in states {
+            if let (Some(open), Some(last)) = (state.position, self.data[pair].last()) {
+                let price = self.fill_price(last.close, open.trade.amount, TradeSide::Sell, "market");
+                trades.push(self.close(open, price, last, ExitType::ForceExit, &mut balance));
+            }
+        }
+        trades.sort_by_key(|t| t.open_date);
 
-        Ok(BacktestResult {
-            strategy: self.strategy.name().to_string(),
-            pair: "BTCUSDT".to_string(),
-            timeframe: crate::types::Timeframe::OneHour,
-            start_date: self.config.start_date,
-            end_date: self.config.end_date,
-            total_trades: trades.len(),
-            winning_trades,
-            losing_trades,
-            win_rate: if !trades.is_empty() {
-                winning_trades as f64 / trades.len() as f64
-            } else {
-                0.0
-            },
-            total_profit: rust_decimal::Decimal::try_from(balance - self.config.stake_amount)
-                .unwrap_or(rust_decimal::Decimal::ZERO),
-            max_drawdown: 0.0,
-            sharpe_ratio: 0.0,
-            profit_factor: 0.0,
-            avg_profit: rust_decimal::Decimal::ZERO,
-            avg_loss: rust_decimal::Decimal::ZERO,
+        let pairs: Vec<String> = self.data.keys().cloned().collect();
+        Ok(metrics::backtest_result(
+            self.strategy.name(),
+            &self.config,
+            &pairs,
             trades,
+            metrics::market_change(&self.data, self.config.start_date),
+        ))
+    }
+
+    async fn signal_series(&self, candles: &[OHLCV]) -> Result<SignalSeries> {
+        let mark = |signals: Vec<Signal>| {
+            let mut marked = vec![false; candles.len()];
+            for signal in signals {
+                if let Some(slot) = marked.get_mut(signal.index) {
+                    *slot = true;
+                }
+            }
+            marked
+        };
+        Ok(SignalSeries {
+            buy: mark(self.strategy.populate_buy_trend(candles).await?),
+            sell: mark(self.strategy.populate_sell_trend(candles).await?),
         })
     }
+
+    fn fee_rate(&self) -> Decimal {
+        Decimal::try_from(self.config.commission).unwrap_or(Decimal::ZERO)
+    }
+
+    /// Price a fill with slippage against us: buys fill higher, sells lower
+    fn fill_price(&self, price: Decimal, amount: Decimal, side: TradeSide, order_type: &str) -> Decimal {
+        let slippage = Decimal::try_from(self.config.slippage).unwrap_or(Decimal::ZERO);
+        let slipped = simulate_slippage(price, amount, order_type, slippage);
+        match side {
+            TradeSide::Buy => slipped,
+            TradeSide::Sell => price - (slipped - price),
+        }
+    }
+
+    /// Stake for a new trade, following `Wallets::get_trade_stake_amount`
+    fn stake_amount(&self, balance: Decimal, open_trades: usize) -> Decimal {
+        // Keep enough of the balance to pay the entry fee
+        let available = balance / (Decimal::ONE + self.fee_rate());
+        let stake_amount = Decimal::try_from(self.config.stake_amount).unwrap_or(Decimal::ZERO);
+        if stake_amount > Decimal::ZERO {
+            stake_amount.min(available)
+        } else {
+            let free_slots = self.config.max_open_trades.saturating_sub(open_trades).max(1);
+            available / Decimal::from(free_slots)
+        }
+    }
+
+    fn open(&self, pair: &str, candle: &OHLCV, open_trades: usize, balance: &mut Decimal) -> Option<Position> {
+        let stake = self.stake_amount(*balance, open_trades);
+        if stake <= Decimal::ZERO {
+            return None;
+        }
+
+        let price = self.fill_price(candle.open, stake / candle.open, TradeSide::Buy, "market");
+        if price <= Decimal::ZERO {
+            return None;
+        }
+        let amount = stake / price;
+        let entry_fee = calculate_fee(amount, price, self.fee_rate(), "quote");
+        *balance -= stake + entry_fee;
+
+        let ratio_price = |ratio: Option<f64>| {
+            ratio
+                .and_then(|r| Decimal::try_from(r).ok())
+                .map(|r| price * (Decimal::ONE + r))
+        };
+
+        let trade = Trade {
+            id: uuid::Uuid::new_v4(),
+            pair: pair.to_string(),
+            is_open: true,
+            exchange: "backtest".to_string(),
+            open_rate: price,
+            open_date: candle.timestamp,
+            close_rate: None,
+            close_date: None,
+            amount,
+            stake_amount: stake,
+            strategy: self.strategy.name().to_string(),
+            timeframe: self.config.timeframe,
+            stop_loss: ratio_price(self.config.stoploss),
+            take_profit: ratio_price(self.config.take_profit),
+            exit_reason: None,
+            profit_abs: None,
+            profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
+        };
+        Some(Position { trade, entry_fee })
+    }
+
+    /// Detail candles covering `candle`, or the candle itself when there are none
+    fn intra_candles<'a>(&'a self, pair: &str, candle: &'a OHLCV, candle_duration: Duration) -> &'a [OHLCV] {
+        let Some(detail) = self.detail.as_ref().and_then(|(_, data)| data.get(pair)) else {
+            return std::slice::from_ref(candle);
+        };
+        let from = detail.partition_point(|c| c.timestamp < candle.timestamp);
+        let to = detail.partition_point(|c| c.timestamp < candle.timestamp + candle_duration);
+        if from == to {
+            std::slice::from_ref(candle)
+        } else {
+            &detail[from..to]
+        }
+    }
+
+    /// Stoploss or take-profit hit within the candle. When both are in range the stoploss is
+    /// assumed to trigger first; a gap through either level fills at the open.
+    fn intra_candle_exit(&self, trade: &Trade, candle: &OHLCV) -> Option<(Decimal, ExitType)> {
+        if let Some(stop) = trade.stop_loss
+            && candle.low <= stop
+        {
+            let price = self.fill_price(stop.min(candle.open), trade.amount, TradeSide::Sell, "market");
+            return Some((price, ExitType::StopLoss));
+        }
+        if let Some(target) = trade.take_profit
+            && candle.high >= target
+        {
+            let price = self.fill_price(target.max(candle.open), trade.amount, TradeSide::Sell, "limit");
+            return Some((price, ExitType::TakeProfit));
+        }
+        None
+    }
+
+    fn close(
+        &self,
+        position: Position,
+        price: Decimal,
+        candle: &OHLCV,
+        exit_reason: ExitType,
+        balance: &mut Decimal,
+    ) -> Trade {
+        let Position { mut trade, entry_fee } = position;
+        let proceeds = trade.amount * price;
+        let exit_fee = calculate_fee(trade.amount, price, self.fee_rate(), "quote");
+        let cost = trade.stake_amount + entry_fee;
+        let profit = proceeds - exit_fee - cost;
+        *balance += proceeds - exit_fee;
+
+        trade.is_open = false;
+        trade.close_rate = Some(price);
+        trade.close_date = Some(candle.timestamp);
+        trade.exit_reason = Some(exit_reason);
+        trade.profit_abs = Some(profit);
+        trade.profit_ratio = Some(if cost.is_zero() { Decimal::ZERO } else { profit / cost });
+        trade
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::{Duration, TimeZone, Utc};
+
+    /// Signals on fixed candle indices
+    struct ScriptedStrategy {
+        buys: Vec<usize>,
+        sells: Vec<usize>,
+    }
+
+    fn signals(indices: &[usize], len: usize, r#type: SignalType) -> Vec<Signal> {
+        indices
+            .iter()
+            .filter(|&&index| index < len)
+            .map(|&index| Signal {
+                index,
+                r#type,
+                strength: 1.0,
+            })
+            .collect()
+    }
+
+    #[async_trait]
+    impl crate::strategy::Strategy for ScriptedStrategy {
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn timeframes(&self) -> &[Timeframe] {
+            &[Timeframe::OneHour]
+        }
+
+        async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn populate_buy_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(signals(&self.buys, data.len(), SignalType::Buy))
+        }
+
+        async fn populate_sell_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(signals(&self.sells, data.len(), SignalType::Sell))
+        }
+    }
+
+    /// Flat candles at 100 with the given (index, open, high, low, close) overrides
+    fn candles(len: usize, overrides: &[(usize, i64, i64, i64, i64)]) -> Vec<OHLCV> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        (0..len)
+            .map(|i| {
+                let (open, high, low, close) = overrides
+                    .iter()
+                    .find(|o| o.0 == i)
+                    .map(|o| (o.1, o.2, o.3, o.4))
+                    .unwrap_or((100, 100, 100, 100));
+                OHLCV {
+                    timestamp: start + Duration::hours(i as i64),
+                    open: Decimal::from(open),
+                    high: Decimal::from(high),
+                    low: Decimal::from(low),
+                    close: Decimal::from(close),
+                    volume: Decimal::from(10),
+                }
+            })
+            .collect()
+    }
+
+    fn config(data: &[OHLCV]) -> BacktestConfig {
+        BacktestConfig {
+            timeframe: Timeframe::OneHour,
+            start_date: data[0].timestamp,
+            end_date: data[data.len() - 1].timestamp,
+            stake_amount: 100.0,
+            starting_balance: 1000.0,
+            max_open_trades: 3,
+            commission: 0.0,
+            slippage: 0.0,
+            stoploss: None,
+            take_profit: None,
+        }
+    }
+
+    async fn run(config: BacktestConfig, buys: Vec<usize>, sells: Vec<usize>, data: Vec<OHLCV>) -> BacktestResult {
+        let strategy = Arc::new(ScriptedStrategy { buys, sells });
+        BacktestEngine::single_pair(config, strategy, "ETH/USDT", data)
+            .run()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_entry_at_next_open_and_fees() {
+        let data = candles(120, &[(106, 100, 100, 100, 110), (107, 110, 112, 108, 111)]);
+        let mut config = config(&data);
+        config.commission = 0.001;
+
+        // Sell signal on 106 exits at the open of 107
+        let result = run(config, vec![105], vec![106], data).await;
+
+        assert_eq!(result.pair, "ETH/USDT");
+        assert_eq!(result.total_trades, 1);
+        let trade = &result.trades[0];
+        assert_eq!(trade.open_date, Utc.with_ymd_and_hms(2024, 1, 5, 10, 0, 0).unwrap());
+        assert_eq!(trade.open_rate, Decimal::from(100));
+        assert_eq!(trade.close_rate, Some(Decimal::from(110)));
+        assert_eq!(trade.exit_reason, Some(ExitType::Signal));
+
+        // 1 unit: bought for 100 + 0.1 fee, sold for 110 - 0.11 fee
+        assert_eq!(trade.amount, Decimal::ONE);
+        assert_eq!(trade.profit_abs, Some(Decimal::new(979, 2)));
+        assert_eq!(trade.profit_ratio, Some(Decimal::new(979, 2) / Decimal::new(1001, 1)));
+        assert_eq!(result.total_profit, Decimal::new(979, 2));
+        assert_eq!(result.winning_trades, 1);
+        assert_eq!(result.win_rate, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_intra_candle_stoploss_and_take_profit() {
+        // Candle 111 dips to 85: stopped out at 90 even though it closes higher
+        let data = candles(
+            130,
+            &[
+                (111, 100, 101, 85, 99),
+                (121, 100, 105, 99, 100),
+                (122, 100, 110, 100, 100),
+            ],
+        );
+        let mut config = config(&data);
+        config.stoploss = Some(-0.10);
+        config.take_profit = Some(0.05);
+
+        let result = run(config, vec![109, 120], vec![], data).await;
+        assert_eq!(result.total_trades, 2);
+
+        let stopped = &result.trades[0];
+        assert_eq!(stopped.exit_reason, Some(ExitType::StopLoss));
+        assert_eq!(stopped.close_rate, Some(Decimal::from(90)));
+        assert_eq!(stopped.profit_abs, Some(Decimal::from(-10)));
+
+        let target = &result.trades[1];
+        assert_eq!(target.exit_reason, Some(ExitType::TakeProfit));
+        assert_eq!(target.close_rate, Some(Decimal::from(105)));
+        assert_eq!(
+            target.close_date,
+            Some(Utc.with_ymd_and_hms(2024, 1, 6, 1, 0, 0).unwrap())
+        );
+
+        assert_eq!(result.winning_trades, 1);
+        assert_eq!(result.losing_trades, 1);
+        assert_eq!(result.profit_factor, 0.5);
+        assert_eq!(result.avg_profit, Decimal::from(5));
+        assert_eq!(result.avg_loss, Decimal::from(-10));
+        // Peak 1000, trough 990
+        assert!((result.max_drawdown - 0.01).abs() < 1e-12);
+        assert!(result.sharpe_ratio < 0.0);
+    }
+
+    /// Flat 1m candles at 100 covering hour `hour` of `candles`, with the given (minute, high, low) overrides
+    fn minute_candles(hour: usize, overrides: &[(usize, i64, i64)]) -> Vec<OHLCV> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hour as i64);
+        (0..60)
+            .map(|i| {
+                let (high, low) = overrides
+                    .iter()
+                    .find(|o| o.0 == i)
+                    .map(|o| (o.1, o.2))
+                    .unwrap_or((100, 100));
+                OHLCV {
+                    timestamp: start + Duration::minutes(i as i64),
+                    open: Decimal::from(100),
+                    high: Decimal::from(high),
+                    low: Decimal::from(low),
+                    close: Decimal::from(100),
+                    volume: Decimal::ONE,
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_timeframe_detail_resolves_stoploss_and_take_profit_order() {
+        // Candle 111 reaches both the take-profit (105) and the stoploss (90)
+        let data = candles(130, &[(111, 100, 106, 85, 99)]);
+        let mut config = config(&data);
+        config.stoploss = Some(-0.10);
+        config.take_profit = Some(0.05);
+
+        // Without detail the stoploss is assumed to trigger first
+        let result = run(config.clone(), vec![109], vec![], data.clone()).await;
+        assert_eq!(result.trades[0].exit_reason, Some(ExitType::StopLoss));
+
+        // The 1m candles show the high came first, 10 minutes into the hour
+        let detail = BTreeMap::from([(
+            "ETH/USDT".to_string(),
+            minute_candles(111, &[(10, 106, 100), (40, 100, 85)]),
+        )]);
+        let strategy = Arc::new(ScriptedStrategy {
+            buys: vec![109],
+            sells: vec![],
+        });
+        let result = BacktestEngine::single_pair(config.clone(), strategy, "ETH/USDT", data.clone())
+            .with_detail(Timeframe::OneMinute, detail)
+            .run()
+            .await
+            .unwrap();
+        let trade = &result.trades[0];
+        assert_eq!(trade.exit_reason, Some(ExitType::TakeProfit));
+        assert_eq!(trade.close_rate, Some(Decimal::from(105)));
+        assert_eq!(
+            trade.close_date,
+            Some(Utc.with_ymd_and_hms(2024, 1, 5, 15, 10, 0).unwrap())
+        );
+
+        // Candles without detail data fall back to the main candle
+        let detail = BTreeMap::from([("ETH/USDT".to_string(), minute_candles(50, &[]))]);
+        let strategy = Arc::new(ScriptedStrategy {
+            buys: vec![109],
+            sells: vec![],
+        });
+        let result = BacktestEngine::single_pair(config.clone(), strategy, "ETH/USDT", data.clone())
+            .with_detail(Timeframe::OneMinute, detail)
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(result.trades[0].exit_reason, Some(ExitType::StopLoss));
+        assert_eq!(
+            result.trades[0].close_date,
+            Some(Utc.with_ymd_and_hms(2024, 1, 5, 15, 0, 0).unwrap())
+        );
+
+        // The detail timeframe has to be lower than the backtested one
+        let strategy = Arc::new(ScriptedStrategy {
+            buys: vec![109],
+            sells: vec![],
+        });
+        let invalid = BacktestEngine::single_pair(config, strategy, "ETH/USDT", data)
+            .with_detail(Timeframe::FourHours, BTreeMap::new())
+            .run()
+            .await;
+        assert!(matches!(invalid, Err(AppError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_slippage_and_force_exit() {
+        let data = candles(110, &[(109, 100, 100, 100, 120)]);
+        let mut config = config(&data);
+        config.slippage = 0.005;
+        config.stake_amount = 0.0;
+        config.max_open_trades = 1;
+
+        let result = run(config, vec![100], vec![], data).await;
+        let trade = &result.trades[0];
+
+        // Market orders slip 1% against us; stake 0 with a single slot uses the whole balance
+        assert_eq!(trade.open_rate, Decimal::from(101));
+        assert_eq!(trade.stake_amount, Decimal::from(1000));
+        assert_eq!(trade.exit_reason, Some(ExitType::ForceExit));
+        assert_eq!(trade.close_rate, Some(Decimal::new(1188, 1)));
+        assert!(trade.profit_abs.unwrap() > Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_multi_pair_shares_slots_and_balance() {
+        let strategy = Arc::new(ScriptedStrategy {
+            buys: vec![105],
+            sells: vec![],
+        });
+        // Three pairs signal on the same candle; C starts an hour later so its signal comes last
+        let mut late = candles(121, &[]);
+        late.remove(0);
+        let data = BTreeMap::from([
+            ("A/USDT".to_string(), candles(120, &[(119, 100, 100, 100, 110)])),
+            ("B/USDT".to_string(), candles(120, &[(119, 100, 100, 100, 90)])),
+            ("C/USDT".to_string(), late),
+        ]);
+        let mut config = config(&data["A/USDT"]);
+        config.max_open_trades = 2;
+        config.stake_amount = 0.0;
+
+        let result = BacktestEngine::new(config, strategy, data).run().await.unwrap();
+
+        // Only two slots: C is never entered, A and B split the balance
+        assert_eq!(result.pair, "A/USDT,B/USDT,C/USDT");
+        assert_eq!(result.total_trades, 2);
+        assert_eq!(result.trades[0].stake_amount, Decimal::from(500));
+        assert_eq!(result.trades[1].stake_amount, Decimal::from(500));
+        assert!(result.trades.iter().all(|t| t.exit_reason == Some(ExitType::ForceExit)));
+
+        let per_pair: Vec<(&str, usize, Decimal)> = result
+            .per_pair
+            .iter()
+            .map(|p| (p.pair.as_str(), p.total_trades, p.total_profit))
+            .collect();
+        assert_eq!(
+            per_pair,
+            vec![
+                ("A/USDT", 1, Decimal::from(50)),
+                ("B/USDT", 1, Decimal::from(-50)),
+                ("C/USDT", 0, Decimal::ZERO),
+            ]
+        );
+        assert_eq!(result.per_pair[0].total_profit_ratio, 0.05);
+        assert_eq!(result.total_profit, Decimal::ZERO);
+    }
+
+    /// Close crossing its 20-candle SMA, optionally evaluated over the full history at once
+    struct SmaCross {
+        vectorized: bool,
+    }
+
+    impl SmaCross {
+        fn crossings(data: &[OHLCV], above: bool, r#type: SignalType) -> Result<Vec<Signal>> {
+            let sma = crate::strategy::indicators::SMA::new(20).calculate(data)?;
+            Ok((1..data.len())
+                .filter(|&i| match (sma[i - 1], sma[i]) {
+                    (Some(prev), Some(curr)) => {
+                        let was_above = data[i - 1].close > prev;
+                        let is_above = data[i].close > curr;
+                        was_above != is_above && is_above == above
+                    }
+                    _ => false,
+                })
+                .map(|index| Signal {
+                    index,
+                    r#type,
+                    strength: 1.0,
+                })
+                .collect())
+        }
+    }
+
+    #[async_trait]
+    impl crate::strategy::Strategy for SmaCross {
+        fn name(&self) -> &str {
+            "SmaCross"
+        }
+
+        fn timeframes(&self) -> &[Timeframe] {
+            &[Timeframe::OneHour]
+        }
+
+        async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn populate_buy_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Self::crossings(data, true, SignalType::Buy)
+        }
+
+        async fn populate_sell_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Self::crossings(data, false, SignalType::Sell)
+        }
+
+        fn vectorized(&self) -> bool {
+            self.vectorized
+        }
+    }
+
+    /// Oscillating closes so the SMA is crossed regularly
+    fn wave(len: usize) -> Vec<OHLCV> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        (0..len)
+            .map(|i| {
+                let close = Decimal::try_from(100.0 + 10.0 * (i as f64 / 7.0).sin())
+                    .unwrap()
+                    .round_dp(4);
+                OHLCV {
+                    timestamp: start + Duration::hours(i as i64),
+                    open: close,
+                    high: close + Decimal::ONE,
+                    low: close - Decimal::ONE,
+                    close,
+                    volume: Decimal::from(10),
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_vectorized_signals_match_incremental() {
+        let data = wave(400);
+        let mut config = config(&data);
+        config.commission = 0.001;
+        config.stoploss = Some(-0.05);
+
+        let mut results = Vec::new();
+        for vectorized in [false, true] {
+            let strategy = Arc::new(SmaCross { vectorized });
+            let result = BacktestEngine::single_pair(config.clone(), strategy, "ETH/USDT", data.clone())
+                .run()
+                .await
+                .unwrap();
+            results.push(result);
+        }
+
+        assert!(results[0].total_trades > 5);
+        let summary = |r: &BacktestResult| -> Vec<_> {
+            r.trades
+                .iter()
+                .map(|t| (t.open_date, t.close_date, t.exit_reason, t.profit_abs))
+                .collect()
+        };
+        assert_eq!(summary(&results[0]), summary(&results[1]));
+    }
+
+    /// Counts the candles handed to the strategy
+    struct Counting {
+        seen: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl crate::strategy::Strategy for Counting {
+        fn name(&self) -> &str {
+            "Counting"
+        }
+
+        fn timeframes(&self) -> &[Timeframe] {
+            &[Timeframe::OneHour]
+        }
+
+        async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn populate_buy_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            self.seen.fetch_add(data.len(), std::sync::atomic::Ordering::Relaxed);
+            Ok(signals(
+                &(0..data.len()).step_by(50).collect::<Vec<_>>(),
+                data.len(),
+                SignalType::Buy,
+            ))
+        }
+
+        async fn populate_sell_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            self.seen.fetch_add(data.len(), std::sync::atomic::Ordering::Relaxed);
+            Ok(signals(
+                &(25..data.len()).step_by(50).collect::<Vec<_>>(),
+                data.len(),
+                SignalType::Sell,
+            ))
+        }
+
+        fn vectorized(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vectorized_backtest_evaluates_each_candle_once() {
+        async fn run(len: usize) -> (usize, usize) {
+            let data = candles(len, &[]);
+            let strategy = Arc::new(Counting {
+                seen: std::sync::atomic::AtomicUsize::new(0),
+            });
+            let result = BacktestEngine::single_pair(config(&data), strategy.clone(), "ETH/USDT", data)
+                .run()
+                .await
+                .unwrap();
+            let seen = strategy.seen.load(std::sync::atomic::Ordering::Relaxed);
+            (seen, result.total_trades)
+        }
+
+        let (seen, trades) = run(25_000).await;
+        assert_eq!(seen, 2 * 25_000);
+        assert!(trades > 400);
+
+        let (seen, trades) = run(100_000).await;
+        // Each candle is handed to the strategy exactly once per signal side, so the work
+        // grows linearly with the candle count
+        assert_eq!(seen, 2 * 100_000);
+        assert!(trades > 1900);
+    }
+
+    #[tokio::test]
+    async fn test_max_open_trades_zero_is_rejected() {
+        let data = candles(120, &[]);
+        let mut config = config(&data);
+        config.max_open_trades = 0;
+        let strategy = Arc::new(ScriptedStrategy {
+            buys: vec![],
+            sells: vec![],
+        });
+        let result = BacktestEngine::single_pair(config, strategy, "ETH/USDT", data)
+            .run()
+            .await;
+        assert!(result.is_err());
+    }
+
+    /// Buys on every candle once its startup candles have passed
+    struct EveryCandle {
+        startup: usize,
+    }
+
+    #[async_trait]
+    impl crate::strategy::Strategy for EveryCandle {
+        fn name(&self) -> &str {
+            "EveryCandle"
+        }
+
+        fn timeframes(&self) -> &[Timeframe] {
+            &[Timeframe::OneHour]
+        }
+
+        fn startup_candle_count(&self) -> usize {
+            self.startup
+        }
+
+        async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn populate_buy_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(signals(
+                &(0..data.len()).collect::<Vec<_>>(),
+                data.len(),
+                SignalType::Buy,
+            ))
+        }
+
+        async fn populate_sell_trend(&self, _data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signals_within_startup_candles_are_ignored() {
+        let data = candles(50, &[]);
+        for startup in [0, 20] {
+            let mut config = config(&data);
+            config.max_open_trades = 1;
+            let result =
+                BacktestEngine::single_pair(config, Arc::new(EveryCandle { startup }), "ETH/USDT", data.clone())
+                    .run()
+                    .await
+                    .unwrap();
+            assert_eq!(result.total_trades, 1);
+            assert_eq!(result.trades[0].open_date, data[startup + 1].timestamp);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_candles_prepends_startup_candles() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Repository::new(dir.path().join("candles.db")).await.unwrap();
+        // The first startup candle closes at 50, the range itself is flat
+        repository
+            .save_klines("ETH/USDT", "1h", &candles(72, &[(19, 100, 100, 50, 50)]))
+            .await
+            .unwrap();
+        let pairs = vec!["ETH/USDT".to_string()];
+        let timerange = TimeRange::parse("20240102-20240103").unwrap();
+
+        let data = load_candles(&repository, &pairs, "1h", Some(&timerange), 0)
+            .await
+            .unwrap();
+        assert_eq!(data["ETH/USDT"].len(), 24);
+        assert_eq!(data["ETH/USDT"][0].timestamp, timerange.start);
+
+        let data = load_candles(&repository, &pairs, "1h", Some(&timerange), 5)
+            .await
+            .unwrap();
+        assert_eq!(data["ETH/USDT"].len(), 29);
+        assert_eq!(data["ETH/USDT"][0].timestamp, timerange.start - Duration::hours(5));
+
+        // The backtested period and its market change leave the startup candles out
+        let config = BacktestConfig::from_bot(&BotConfig::default(), Timeframe::OneHour, &data, Some(&timerange));
+        assert_eq!(config.start_date, timerange.start);
+        assert_eq!(metrics::market_change(&data, config.start_date), 0.0);
+        assert_eq!(metrics::market_change(&data, data["ETH/USDT"][0].timestamp), 1.0);
+
+        // Without a timerange all stored candles are loaded, the first ones serve as startup
+        let data = load_candles(&repository, &pairs, "1h", None, 5).await.unwrap();
+        assert_eq!(data["ETH/USDT"].len(), 72);
+    }
+
+    #[tokio::test]
+    async fn test_no_signals_no_trades() {
+        let data = candles(120, &[]);
+        let result = run(config(&data), vec![], vec![], data).await;
+        assert_eq!(result.total_trades, 0);
+        assert_eq!(result.total_profit, Decimal::ZERO);
+        assert_eq!(result.max_drawdown, 0.0);
+        assert_eq!(result.sharpe_ratio, 0.0);
+    }
 }
@@ -0,0 +1,413 @@
+//! Monte Carlo analysis of how much a backtest's outcome depends on the order and selection of
+//! its trades.
+//!
+//! Every simulation replays the closed trades in a random order (or a random sample of them),
+//! optionally charging randomly perturbed fees and slippage, and records the final profit and the
+//! maximum drawdown of the resulting balance path.
+
+use crate::error::{AppError, Result};
+use crate::types::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimulationMethod {
+    /// Replay all trades in a random order; the final profit only changes through perturbations
+    Shuffle,
+    /// Draw as many trades as the backtest had, with replacement
+    Bootstrap,
+}
+
+impl std::str::FromStr for SimulationMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "shuffle" => Ok(SimulationMethod::Shuffle),
+            "bootstrap" => Ok(SimulationMethod::Bootstrap),
+            _ => Err(format!("unknown method '{}', expected shuffle or bootstrap", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonteCarloConfig {
+    pub simulations: usize,
+    pub method: SimulationMethod,
+    /// Fee rate change drawn uniformly from `[-fee_noise, fee_noise]` per trade, charged on entry
+    /// and exit
+    pub fee_noise: f64,
+    /// Adverse slippage ratio drawn uniformly from `[0, slippage_noise]` per trade, charged on
+    /// entry and exit
+    pub slippage_noise: f64,
+    /// Loss of the starting balance, as a ratio, that counts as ruin
+    pub ruin_threshold: f64,
+    /// Confidence levels to report the worst-case profit and drawdown at, e.g. 0.95
+    pub confidence_levels: Vec<f64>,
+    pub seed: Option<u64>,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            simulations: 1000,
+            method: SimulationMethod::Shuffle,
+            fee_noise: 0.0,
+            slippage_noise: 0.0,
+            ruin_threshold: 0.5,
+            confidence_levels: vec![0.9, 0.95, 0.99],
+            seed: None,
+        }
+    }
+}
+
+/// Summary of the values a metric took across all simulations
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Distribution {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+}
+
+/// Outcome that a share `level` of the simulations did at least as well as
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfidenceInterval {
+    pub level: f64,
+    /// Final profit exceeded in `level` of the simulations
+    pub final_profit: f64,
+    /// Max drawdown not exceeded in `level` of the simulations
+    pub max_drawdown: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloResult {
+    pub simulations: usize,
+    pub method: SimulationMethod,
+    pub starting_balance: f64,
+    /// Final profit and max drawdown of the trades in their original order, without perturbations
+    pub original_profit: f64,
+    pub original_max_drawdown: f64,
+    /// Absolute profit in the stake currency
+    pub final_profit: Distribution,
+    /// Ratio of the peak balance
+    pub max_drawdown: Distribution,
+    pub confidence: Vec<ConfidenceInterval>,
+    /// Share of the simulations whose balance fell to `ruin_threshold` below the starting balance
+    pub ruin_probability: f64,
+}
+
+/// A trade reduced to what the simulation needs
+struct SimTrade {
+    profit: f64,
+    stake: f64,
+}
+
+/// Run `config.simulations` simulations of the closed trades of `result`
+pub fn run_monte_carlo(result: &BacktestResult, config: &MonteCarloConfig) -> Result<MonteCarloResult> {
+    if config.simulations == 0 {
+        return Err(AppError::InvalidInput(
+            "At least one simulation is required".to_string(),
+        ));
+    }
+    if let Some(level) = config.confidence_levels.iter().find(|l| !(0.0..=1.0).contains(*l)) {
+        return Err(AppError::InvalidInput(format!(
+            "Confidence level {} is not between 0 and 1",
+            level
+        )));
+    }
+    if config.fee_noise < 0.0 || config.slippage_noise < 0.0 {
+        return Err(AppError::InvalidInput("Noise must not be negative".to_string()));
+    }
+
+    let mut closed: Vec<&Trade> = result.trades.iter().filter(|t| !t.is_open).collect();
+    closed.sort_by_key(|t| t.close_date);
+    let trades: Vec<SimTrade> = closed
+        .iter()
+        .map(|t| SimTrade {
+            profit: t.profit_abs.and_then(|p| p.to_f64()).unwrap_or(0.0),
+            stake: t.stake_amount.to_f64().unwrap_or(0.0),
+        })
+        .collect();
+    if trades.is_empty() {
+        return Err(AppError::Backtest("No closed trades to simulate".to_string()));
+    }
+
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let starting_balance = result.starting_balance;
+    let ruin_balance = starting_balance * (1.0 - config.ruin_threshold);
+    let original: Vec<f64> = trades.iter().map(|t| t.profit).collect();
+    let (original_profit, original_max_drawdown, _) = replay(&original, starting_balance, ruin_balance);
+
+    let mut profits = Vec::with_capacity(config.simulations);
+    let mut drawdowns = Vec::with_capacity(config.simulations);
+    let mut ruined = 0;
+    let mut order: Vec<usize> = (0..trades.len()).collect();
+    for _ in 0..config.simulations {
+        match config.method {
+            SimulationMethod::Shuffle => order.shuffle(&mut rng),
+            SimulationMethod::Bootstrap => {
+                for slot in order.iter_mut() {
+                    *slot = rng.gen_range(0..trades.len());
+                }
+            }
+        }
+        let path: Vec<f64> = order
+            .iter()
+            .map(|&i| {
+                let trade = &trades[i];
+                let fee = if config.fee_noise > 0.0 {
+                    rng.gen_range(-config.fee_noise..=config.fee_noise)
+                } else {
+                    0.0
+                };
+                let slippage = if config.slippage_noise > 0.0 {
+                    rng.gen_range(0.0..=config.slippage_noise)
+                } else {
+                    0.0
+                };
+                // Entry and exit are both charged on roughly the stake
+                trade.profit - trade.stake * 2.0 * (fee + slippage)
+            })
+            .collect();
+
+        let (profit, max_drawdown, is_ruined) = replay(&path, starting_balance, ruin_balance);
+        profits.push(profit);
+        drawdowns.push(max_drawdown);
+        if is_ruined {
+            ruined += 1;
+        }
+    }
+
+    profits.sort_by(f64::total_cmp);
+    drawdowns.sort_by(f64::total_cmp);
+    let confidence = config
+        .confidence_levels
+        .iter()
+        .map(|&level| ConfidenceInterval {
+            level,
+            final_profit: quantile(&profits, 1.0 - level),
+            max_drawdown: quantile(&drawdowns, level),
+        })
+        .collect();
+
+    Ok(MonteCarloResult {
+        simulations: config.simulations,
+        method: config.method,
+        starting_balance,
+        original_profit,
+        original_max_drawdown,
+        final_profit: distribution(&profits),
+        max_drawdown: distribution(&drawdowns),
+        confidence,
+        ruin_probability: ruined as f64 / config.simulations as f64,
+    })
+}
+
+/// Final profit, max drawdown and whether the balance reached `ruin_balance` along `profits`
+fn replay(profits: &[f64], starting_balance: f64, ruin_balance: f64) -> (f64, f64, bool) {
+    let mut balance = starting_balance;
+    let mut peak = starting_balance;
+    let mut max_drawdown: f64 = 0.0;
+    let mut ruined = false;
+    for profit in profits {
+        balance += profit;
+        peak = peak.max(balance);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - balance) / peak);
+        }
+        ruined |= balance <= ruin_balance;
+    }
+    (balance - starting_balance, max_drawdown, ruined)
+}
+
+/// Linearly interpolated quantile `q` of ascending `values`
+fn quantile(values: &[f64], q: f64) -> f64 {
+    let position = q.clamp(0.0, 1.0) * (values.len() - 1) as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    values[lower] + (values[upper] - values[lower]) * (position - lower as f64)
+}
+
+/// Distribution of ascending `values`
+fn distribution(values: &[f64]) -> Distribution {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    Distribution {
+        mean,
+        std_dev: variance.sqrt(),
+        min: values[0],
+        median: quantile(values, 0.5),
+        max: values[values.len() - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use rust_decimal::Decimal;
+
+    fn result(profits: &[i64]) -> BacktestResult {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let trades = profits
+            .iter()
+            .enumerate()
+            .map(|(i, profit)| Trade {
+                id: uuid::Uuid::new_v4(),
+                pair: "BTC/USDT".to_string(),
+                is_open: false,
+                exchange: "backtest".to_string(),
+                open_rate: Decimal::from(100),
+                open_date: start + Duration::hours(i as i64),
+                close_rate: Some(Decimal::from(100 + profit)),
+                close_date: Some(start + Duration::hours(i as i64 + 1)),
+                amount: Decimal::ONE,
+                stake_amount: Decimal::from(100),
+                strategy: "Test".to_string(),
+                timeframe: Timeframe::OneHour,
+                stop_loss: None,
+                take_profit: None,
+                exit_reason: Some(ExitType::Signal),
+                profit_abs: Some(Decimal::from(*profit)),
+                profit_ratio: Some(Decimal::new(*profit, 2)),
+                is_short: false,
+                leverage: Decimal::ONE,
+                liquidation_price: None,
+            })
+            .collect();
+        BacktestResult {
+            strategy: "Test".to_string(),
+            pair: "BTC/USDT".to_string(),
+            timeframe: Timeframe::OneHour,
+            start_date: start,
+            end_date: start + Duration::hours(profits.len() as i64),
+            total_trades: profits.len(),
+            winning_trades: 0,
+            losing_trades: 0,
+            win_rate: 0.0,
+            total_profit: Decimal::from(profits.iter().sum::<i64>()),
+            max_drawdown: 0.0,
+            sharpe_ratio: 0.0,
+            profit_factor: 0.0,
+            avg_profit: Decimal::ZERO,
+            avg_loss: Decimal::ZERO,
+            trades,
+            per_pair: vec![],
+            starting_balance: 1000.0,
+            market_change: 0.0,
+        }
+    }
+
+    fn config(method: SimulationMethod) -> MonteCarloConfig {
+        MonteCarloConfig {
+            simulations: 500,
+            method,
+            seed: Some(42),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_shuffle_keeps_profit_and_spreads_drawdown() {
+        let result = result(&[50, 50, 50, -100, -100, 50, 50, -100, 50, 50]);
+        let mc = run_monte_carlo(&result, &config(SimulationMethod::Shuffle)).unwrap();
+
+        assert_eq!(mc.original_profit, 50.0);
+        assert_eq!(mc.final_profit.min, 50.0);
+        assert_eq!(mc.final_profit.max, 50.0);
+        assert_eq!(mc.final_profit.std_dev, 0.0);
+
+        // All three losses first is the worst possible order: 300 / 1000
+        assert!(mc.max_drawdown.max <= 0.3 + 1e-12);
+        assert!(mc.max_drawdown.min >= 100.0 / 1350.0 - 1e-12);
+        assert!(mc.max_drawdown.max > mc.max_drawdown.min);
+        assert_eq!(mc.ruin_probability, 0.0);
+
+        let levels: Vec<f64> = mc.confidence.iter().map(|c| c.level).collect();
+        assert_eq!(levels, vec![0.9, 0.95, 0.99]);
+        assert!(mc.confidence.windows(2).all(|w| w[0].max_drawdown <= w[1].max_drawdown));
+    }
+
+    #[test]
+    fn test_bootstrap_varies_profit_and_detects_ruin() {
+        let result = result(&[300, 300, -400, -400, 300]);
+        let mut config = config(SimulationMethod::Bootstrap);
+        config.ruin_threshold = 0.5;
+        let mc = run_monte_carlo(&result, &config).unwrap();
+
+        assert!(mc.final_profit.min < mc.final_profit.max);
+        // At least two losses before any win reach the ruin balance of 500, a likely draw
+        assert!(mc.ruin_probability > 0.05 && mc.ruin_probability < 0.95);
+        let worst = &mc.confidence[2];
+        assert!(worst.final_profit <= mc.final_profit.median);
+        assert!(worst.max_drawdown >= mc.max_drawdown.median);
+    }
+
+    #[test]
+    fn test_seed_makes_runs_reproducible() {
+        let result = result(&[30, -20, 10, -40, 60, 20, -10]);
+        let mut config = config(SimulationMethod::Bootstrap);
+        config.fee_noise = 0.001;
+        config.slippage_noise = 0.002;
+
+        let first = run_monte_carlo(&result, &config).unwrap();
+        let second = run_monte_carlo(&result, &config).unwrap();
+        assert_eq!(first.final_profit, second.final_profit);
+        assert_eq!(first.max_drawdown, second.max_drawdown);
+        assert_eq!(first.confidence, second.confidence);
+
+        config.seed = Some(43);
+        let other = run_monte_carlo(&result, &config).unwrap();
+        assert_ne!(first.final_profit, other.final_profit);
+    }
+
+    #[test]
+    fn test_slippage_noise_only_lowers_profit() {
+        let result = result(&[10, 10, 10, 10]);
+        let mut config = config(SimulationMethod::Shuffle);
+        config.slippage_noise = 0.01;
+        let mc = run_monte_carlo(&result, &config).unwrap();
+
+        // Up to 2% of the 100 stake per trade
+        assert!(mc.final_profit.max < 40.0);
+        assert!(mc.final_profit.min >= 40.0 - 4.0 * 2.0);
+    }
+
+    #[test]
+    fn test_rejects_invalid_input() {
+        let empty = result(&[]);
+        assert!(matches!(
+            run_monte_carlo(&empty, &MonteCarloConfig::default()),
+            Err(AppError::Backtest(_))
+        ));
+
+        let result = result(&[10]);
+        let config = MonteCarloConfig {
+            simulations: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            run_monte_carlo(&result, &config),
+            Err(AppError::InvalidInput(_))
+        ));
+
+        let config = MonteCarloConfig {
+            confidence_levels: vec![95.0],
+            ..Default::default()
+        };
+        assert!(matches!(
+            run_monte_carlo(&result, &config),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert_eq!("bootstrap".parse::<SimulationMethod>(), Ok(SimulationMethod::Bootstrap));
+    }
+}
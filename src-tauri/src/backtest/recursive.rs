@@ -0,0 +1,350 @@
+//! Startup-candle analysis: finds how much history a strategy's indicators need before their
+//! values stop depending on where the data starts.
+//!
+//! Recursive indicators such as RSI or EMA are seeded from the first candles they are given and
+//! then smoothed, so the same candle gets a different value depending on how many candles came
+//! before it. The analysis evaluates the indicators at the last candle with increasing amounts of
+//! history and compares them with the value computed from all available candles.
+
+use crate::error::{AppError, Result};
+use crate::strategy::Strategy;
+use crate::types::*;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecursiveConfig {
+    /// Largest relative difference from the full-history value that counts as converged
+    pub tolerance: f64,
+    /// Startup candle counts to try; counts that need more candles than available are skipped
+    pub startup_candles: Vec<usize>,
+}
+
+impl Default for RecursiveConfig {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.001,
+            startup_candles: vec![10, 25, 50, 100, 200, 300, 500, 1000],
+        }
+    }
+}
+
+/// Indicator value at the last candle with `startup_candles` candles of history before it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StartupDeviation {
+    pub startup_candles: usize,
+    pub value: Option<Decimal>,
+    /// Relative difference from the full-history value; `None` when either has no value
+    pub deviation: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndicatorConvergence {
+    pub name: String,
+    /// Value at the last candle computed from all candles
+    pub reference: Option<Decimal>,
+    pub deviations: Vec<StartupDeviation>,
+    /// Smallest tried startup candle count from which on every larger one stays within the
+    /// tolerance; `None` if even the largest one does not
+    pub converged_at: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecursiveReport {
+    pub strategy: String,
+    pub pair: String,
+    pub candles: usize,
+    pub tolerance: f64,
+    /// The strategy's `startup_candle_count`
+    pub declared_startup_candles: usize,
+    pub indicators: Vec<IndicatorConvergence>,
+}
+
+impl RecursiveReport {
+    /// Startup candles needed for every indicator to converge, `None` if one never does
+    pub fn required_startup_candles(&self) -> Option<usize> {
+        self.indicators
+            .iter()
+            .try_fold(0, |required, indicator| Some(required.max(indicator.converged_at?)))
+    }
+
+    pub fn is_sufficient(&self) -> bool {
+        self.required_startup_candles()
+            .is_some_and(|required| self.declared_startup_candles >= required)
+    }
+}
+
+fn relative_deviation(value: Option<Decimal>, reference: Option<Decimal>) -> Option<f64> {
+    let (value, reference) = (value?.to_f64()?, reference?.to_f64()?);
+    let scale = reference.abs().max(f64::EPSILON);
+    Some((value - reference).abs() / scale)
+}
+
+/// Evaluate the strategy's indicators at the last candle of `data` with each configured amount
+/// of history and find where each converges to the value computed from all of `data`
+pub async fn analyze_startup_candles(
+    strategy: &dyn Strategy,
+    pair: &str,
+    data: &[OHLCV],
+    config: &RecursiveConfig,
+) -> Result<RecursiveReport> {
+    if data.is_empty() {
+        return Err(AppError::InvalidInput(format!("No candles to analyze for {}", pair)));
+    }
+    if config.tolerance.is_nan() || config.tolerance < 0.0 {
+        return Err(AppError::InvalidInput("tolerance must not be negative".to_string()));
+    }
+
+    let last = data.len() - 1;
+    let mut startup_candles = config.startup_candles.clone();
+    startup_candles.sort_unstable();
+    startup_candles.dedup();
+    startup_candles.retain(|&n| n < last);
+
+    let reference = strategy.indicator_values(data).await?;
+    let mut deviations: BTreeMap<&str, Vec<StartupDeviation>> = BTreeMap::new();
+    for &n in &startup_candles {
+        let history = &data[last - n..];
+        let values = strategy.indicator_values(history).await?;
+        for (name, series) in &reference {
+            let value = values.get(name).and_then(|s| s.get(n)).copied().flatten();
+            deviations.entry(name).or_default().push(StartupDeviation {
+                startup_candles: n,
+                value,
+                deviation: relative_deviation(value, series.get(last).copied().flatten()),
+            });
+        }
+    }
+
+    let indicators = reference
+        .iter()
+        .map(|(name, series)| {
+            let deviations = deviations.remove(name.as_str()).unwrap_or_default();
+            let mut converged_at = None;
+            for d in deviations.iter().rev() {
+                match d.deviation {
+                    Some(deviation) if deviation <= config.tolerance => converged_at = Some(d.startup_candles),
+                    _ => break,
+                }
+            }
+            IndicatorConvergence {
+                name: name.clone(),
+                reference: series.get(last).copied().flatten(),
+                deviations,
+                converged_at,
+            }
+        })
+        .collect();
+
+    Ok(RecursiveReport {
+        strategy: strategy.name().to_string(),
+        pair: pair.to_string(),
+        candles: data.len(),
+        tolerance: config.tolerance,
+        declared_startup_candles: strategy.startup_candle_count(),
+        indicators,
+    })
+}
+
+/// Plain-text table of the deviation of every indicator per startup candle count
+pub fn render_text(report: &RecursiveReport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{} on {} ({} candles, tolerance {}%)",
+        report.strategy,
+        report.pair,
+        report.candles,
+        report.tolerance * 100.0
+    );
+    let Some(first) = report.indicators.first() else {
+        let _ = writeln!(out, "  no indicators reported by the strategy");
+        return out;
+    };
+
+    let _ = write!(out, "  {:<16}", "Indicator");
+    for d in &first.deviations {
+        let _ = write!(out, " {:>9}", d.startup_candles);
+    }
+    let _ = writeln!(out, " {:>10}", "Converged");
+    for indicator in &report.indicators {
+        let _ = write!(out, "  {:<16}", indicator.name);
+        for d in &indicator.deviations {
+            match d.deviation {
+                Some(deviation) => {
+                    let _ = write!(out, " {:>8.3}%", deviation * 100.0);
+                }
+                None => {
+                    let _ = write!(out, " {:>9}", "-");
+                }
+            }
+        }
+        let converged = indicator
+            .converged_at
+            .map_or_else(|| "never".to_string(), |n| n.to_string());
+        let _ = writeln!(out, " {:>10}", converged);
+    }
+
+    match report.required_startup_candles() {
+        Some(required) => {
+            let verdict = if report.is_sufficient() { "ok" } else { "too low" };
+            let _ = writeln!(
+                out,
+                "  startup_candle_count: {} declared, {} required ({})",
+                report.declared_startup_candles, required, verdict
+            );
+        }
+        None => {
+            let _ = writeln!(
+                out,
+                "  startup_candle_count: {} declared, not all indicators converge within the tried counts",
+                report.declared_startup_candles
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::indicators::{RSI, SMA};
+    use async_trait::async_trait;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn candles(len: usize) -> Vec<OHLCV> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        (0..len)
+            .map(|i| {
+                let close = Decimal::from(100 + (i * 7 % 13) as i64 - (i % 5) as i64 * 2);
+                OHLCV {
+                    timestamp: start + Duration::hours(i as i64),
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: Decimal::ONE,
+                }
+            })
+            .collect()
+    }
+
+    struct Indicators {
+        startup: usize,
+    }
+
+    #[async_trait]
+    impl Strategy for Indicators {
+        fn name(&self) -> &str {
+            "Indicators"
+        }
+
+        fn timeframes(&self) -> &[Timeframe] {
+            &[Timeframe::OneHour]
+        }
+
+        fn startup_candle_count(&self) -> usize {
+            self.startup
+        }
+
+        async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn populate_buy_trend(&self, _data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(vec![])
+        }
+
+        async fn populate_sell_trend(&self, _data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(vec![])
+        }
+
+        async fn indicator_values(&self, data: &[OHLCV]) -> Result<BTreeMap<String, Vec<Option<Decimal>>>> {
+            Ok(BTreeMap::from([
+                ("rsi_14".to_string(), RSI::new(14).calculate(data)?),
+                ("sma_20".to_string(), SMA::new(20).calculate(data)?),
+            ]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_window_indicator_converges_once_its_period_fits() {
+        let data = candles(600);
+        let report = analyze_startup_candles(
+            &Indicators { startup: 0 },
+            "BTC/USDT",
+            &data,
+            &RecursiveConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        // 1000 startup candles do not fit into 600 candles
+        let sma = report.indicators.iter().find(|i| i.name == "sma_20").unwrap();
+        let tried: Vec<usize> = sma.deviations.iter().map(|d| d.startup_candles).collect();
+        assert_eq!(tried, vec![10, 25, 50, 100, 200, 300, 500]);
+        // 11 candles are too few for a value, from 25 on the average is exact
+        assert_eq!(sma.deviations[0].value, None);
+        assert_eq!(sma.deviations[0].deviation, None);
+        assert!(sma.deviations[1..].iter().all(|d| d.deviation == Some(0.0)));
+        assert_eq!(sma.converged_at, Some(25));
+    }
+
+    #[tokio::test]
+    async fn test_recursive_indicator_needs_longer_history() {
+        let data = candles(600);
+        let report = analyze_startup_candles(
+            &Indicators { startup: 30 },
+            "BTC/USDT",
+            &data,
+            &RecursiveConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        let rsi = report.indicators.iter().find(|i| i.name == "rsi_14").unwrap();
+        // Seeded from different candles, the smoothed averages only agree after enough history
+        let early = rsi.deviations[1].deviation.unwrap();
+        assert!(early > report.tolerance, "deviation with 25 candles: {}", early);
+        let converged_at = rsi.converged_at.unwrap();
+        assert!(converged_at > 25);
+        for d in rsi.deviations.iter().filter(|d| d.startup_candles >= converged_at) {
+            assert!(d.deviation.unwrap() <= report.tolerance);
+        }
+
+        assert_eq!(report.declared_startup_candles, 30);
+        assert_eq!(report.required_startup_candles(), Some(converged_at));
+        assert!(!report.is_sufficient());
+        let text = render_text(&report);
+        assert!(text.contains("rsi_14"));
+        assert!(text.contains("too low"));
+
+        let enough = Indicators { startup: converged_at };
+        let report = analyze_startup_candles(&enough, "BTC/USDT", &data, &RecursiveConfig::default())
+            .await
+            .unwrap();
+        assert!(report.is_sufficient());
+    }
+
+    #[tokio::test]
+    async fn test_indicator_without_convergence() {
+        let data = candles(60);
+        let config = RecursiveConfig {
+            tolerance: 0.0,
+            startup_candles: vec![15, 30],
+        };
+        let report = analyze_startup_candles(&Indicators { startup: 100 }, "BTC/USDT", &data, &config)
+            .await
+            .unwrap();
+
+        let rsi = report.indicators.iter().find(|i| i.name == "rsi_14").unwrap();
+        assert_eq!(rsi.converged_at, None);
+        assert_eq!(report.required_startup_candles(), None);
+        assert!(!report.is_sufficient());
+        assert!(render_text(&report).contains("never"));
+    }
+}
@@ -0,0 +1,553 @@
+//! Detailed breakdown of a backtest result, in the spirit of freqtrade's backtesting summary.
+
+use crate::types::*;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Period granularity of the profit tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakdown {
+    Day,
+    Week,
+    Month,
+}
+
+impl std::str::FromStr for Breakdown {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(Breakdown::Day),
+            "week" => Ok(Breakdown::Week),
+            "month" => Ok(Breakdown::Month),
+            _ => Err(format!("unknown breakdown '{}', expected day, week or month", s)),
+        }
+    }
+}
+
+impl Breakdown {
+    /// First day of the period containing `date`
+    fn period_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Breakdown::Day => date,
+            Breakdown::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Breakdown::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    fn next(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Breakdown::Day => start + Duration::days(1),
+            Breakdown::Week => start + Duration::weeks(1),
+            Breakdown::Month => start
+                .checked_add_months(chrono::Months::new(1))
+                .unwrap_or(NaiveDate::MAX),
+        }
+    }
+
+    fn label(self, period: NaiveDate) -> String {
+        match self {
+            Breakdown::Month => period.format("%Y-%m").to_string(),
+            _ => period.to_string(),
+        }
+    }
+}
+
+/// Build the detailed report of a backtest from its closed trades
+pub fn build_report(result: &BacktestResult) -> BacktestReport {
+    let mut closed: Vec<&Trade> = result
+        .trades
+        .iter()
+        .filter(|t| !t.is_open && t.close_date.is_some())
+        .collect();
+    closed.sort_by_key(|t| t.close_date);
+    let starting_balance = Decimal::try_from(result.starting_balance).unwrap_or(Decimal::ZERO);
+
+    let per_pair = result
+        .per_pair
+        .iter()
+        .map(|p| p.pair.clone())
+        .chain(closed.iter().map(|t| t.pair.clone()))
+        .fold(Vec::<String>::new(), |mut pairs, pair| {
+            if !pairs.contains(&pair) {
+                pairs.push(pair);
+            }
+            pairs
+        })
+        .into_iter()
+        .map(|pair| {
+            let trades: Vec<&Trade> = closed.iter().copied().filter(|t| t.pair == pair).collect();
+            report_row(pair, &trades, starting_balance)
+        })
+        .collect();
+
+    let mut by_reason: BTreeMap<String, Vec<&Trade>> = BTreeMap::new();
+    for trade in &closed {
+        let reason = trade.exit_reason.map_or("unknown".to_string(), |r| r.to_string());
+        by_reason.entry(reason).or_default().push(trade);
+    }
+    let mut exit_reasons: Vec<ReportRow> = by_reason
+        .into_iter()
+        .map(|(reason, trades)| report_row(reason, &trades, starting_balance))
+        .collect();
+    exit_reasons.sort_by_key(|r| std::cmp::Reverse(r.trades));
+
+    let (max_consecutive_wins, max_consecutive_losses) = streaks(&closed);
+    let winners: Vec<&Trade> = closed.iter().copied().filter(|t| is_win(t)).collect();
+    let losers: Vec<&Trade> = closed.iter().copied().filter(|t| !is_win(t)).collect();
+    let total = report_row("TOTAL".to_string(), &closed, starting_balance);
+
+    BacktestReport {
+        strategy: result.strategy.clone(),
+        timeframe: result.timeframe,
+        start_date: result.start_date,
+        end_date: result.end_date,
+        starting_balance: result.starting_balance,
+        final_balance: (starting_balance + total.total_profit).to_f64().unwrap_or(0.0),
+        per_pair,
+        exit_reasons,
+        daily: periods(&closed, result, Breakdown::Day, starting_balance),
+        weekly: periods(&closed, result, Breakdown::Week, starting_balance),
+        monthly: periods(&closed, result, Breakdown::Month, starting_balance),
+        max_consecutive_wins,
+        max_consecutive_losses,
+        avg_winner_duration_secs: avg_duration_secs(&winners),
+        avg_loser_duration_secs: avg_duration_secs(&losers),
+        exposure: exposure(&closed, result.start_date, result.end_date),
+        market_change: result.market_change,
+        max_drawdown: result.max_drawdown,
+        sharpe_ratio: result.sharpe_ratio,
+        profit_factor: result.profit_factor,
+        total,
+    }
+}
+
+/// Trades that made money; break-even trades count as losses, as in the result summary
+fn is_win(trade: &Trade) -> bool {
+    trade.profit_abs.is_some_and(|p| p > Decimal::ZERO)
+}
+
+fn duration_secs(trade: &Trade) -> i64 {
+    trade
+        .close_date
+        .map_or(0, |close| (close - trade.open_date).num_seconds())
+}
+
+fn avg_duration_secs(trades: &[&Trade]) -> i64 {
+    if trades.is_empty() {
+        return 0;
+    }
+    trades.iter().map(|t| duration_secs(t)).sum::<i64>() / trades.len() as i64
+}
+
+fn ratio(profit: Decimal, starting_balance: Decimal) -> f64 {
+    if starting_balance.is_zero() {
+        return 0.0;
+    }
+    (profit / starting_balance).to_f64().unwrap_or(0.0)
+}
+
+fn report_row(key: String, trades: &[&Trade], starting_balance: Decimal) -> ReportRow {
+    let wins = trades.iter().filter(|t| is_win(t)).count();
+    let total_profit: Decimal = trades.iter().filter_map(|t| t.profit_abs).sum();
+    let ratio_sum: f64 = trades
+        .iter()
+        .filter_map(|t| t.profit_ratio.and_then(|r| r.to_f64()))
+        .sum();
+
+    ReportRow {
+        key,
+        trades: trades.len(),
+        wins,
+        losses: trades.len() - wins,
+        win_rate: if trades.is_empty() {
+            0.0
+        } else {
+            wins as f64 / trades.len() as f64
+        },
+        avg_profit_ratio: if trades.is_empty() {
+            0.0
+        } else {
+            ratio_sum / trades.len() as f64
+        },
+        total_profit,
+        total_profit_ratio: ratio(total_profit, starting_balance),
+        avg_duration_secs: avg_duration_secs(trades),
+    }
+}
+
+/// Longest runs of winning and losing trades, in close order
+fn streaks(closed: &[&Trade]) -> (usize, usize) {
+    let (mut wins, mut losses) = (0, 0);
+    let (mut max_wins, mut max_losses) = (0, 0);
+    for trade in closed {
+        if is_win(trade) {
+            wins += 1;
+            losses = 0;
+        } else {
+            losses += 1;
+            wins = 0;
+        }
+        max_wins = max_wins.max(wins);
+        max_losses = max_losses.max(losses);
+    }
+    (max_wins, max_losses)
+}
+
+/// Every period of the backtest, including those without exits, with the trades closed in it
+fn periods(
+    closed: &[&Trade],
+    result: &BacktestResult,
+    breakdown: Breakdown,
+    starting_balance: Decimal,
+) -> Vec<PeriodRow> {
+    let mut rows = BTreeMap::new();
+    let last = breakdown.period_start(result.end_date.date_naive());
+    let mut period = breakdown.period_start(result.start_date.date_naive());
+    while period <= last {
+        rows.insert(period, Vec::new());
+        period = breakdown.next(period);
+    }
+    for trade in closed {
+        if let Some(close_date) = trade.close_date {
+            rows.entry(breakdown.period_start(close_date.date_naive()))
+                .or_insert_with(Vec::new)
+                .push(*trade);
+        }
+    }
+
+    rows.into_iter()
+        .map(|(period, trades)| {
+            let wins = trades.iter().filter(|t| is_win(t)).count();
+            let profit: Decimal = trades.iter().filter_map(|t| t.profit_abs).sum();
+            PeriodRow {
+                period,
+                trades: trades.len(),
+                wins,
+                losses: trades.len() - wins,
+                profit,
+                profit_ratio: ratio(profit, starting_balance),
+            }
+        })
+        .collect()
+}
+
+/// Share of `[start, end]` covered by at least one trade, overlapping trades counted once
+fn exposure(closed: &[&Trade], start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    let period = (end - start).num_seconds();
+    if period <= 0 {
+        return 0.0;
+    }
+
+    let mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)> = closed
+        .iter()
+        .filter_map(|t| Some((t.open_date.max(start), t.close_date?.min(end))))
+        .filter(|(open, close)| close > open)
+        .collect();
+    intervals.sort();
+
+    let mut covered = 0;
+    let mut current: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+    for (open, close) in intervals {
+        current = match current {
+            Some((from, to)) if open <= to => Some((from, to.max(close))),
+            Some((from, to)) => {
+                covered += (to - from).num_seconds();
+                Some((open, close))
+            }
+            None => Some((open, close)),
+        };
+    }
+    if let Some((from, to)) = current {
+        covered += (to - from).num_seconds();
+    }
+    covered as f64 / period as f64
+}
+
+fn format_duration(secs: i64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (hours, minutes) = (rem / 3600, rem % 3600 / 60);
+    if days > 0 {
+        format!("{}d {:02}:{:02}", days, hours, minutes)
+    } else {
+        format!("{:02}:{:02}", hours, minutes)
+    }
+}
+
+fn write_rows(out: &mut String, title: &str, rows: &[ReportRow], total: &ReportRow, currency: &str) {
+    let _ = writeln!(out, "{}", title);
+    let _ = writeln!(
+        out,
+        "{:<18} {:>7} {:>9} {:>16} {:>9} {:>12} {:>14}",
+        "",
+        "Trades",
+        "Avg %",
+        format!("Tot {}", currency),
+        "Tot %",
+        "Avg hold",
+        "Win / Loss"
+    );
+    for row in rows.iter().chain(std::iter::once(total)) {
+        let _ = writeln!(
+            out,
+            "{:<18} {:>7} {:>9.2} {:>16} {:>9.2} {:>12} {:>14}",
+            row.key,
+            row.trades,
+            row.avg_profit_ratio * 100.0,
+            row.total_profit.round_dp(8),
+            row.total_profit_ratio * 100.0,
+            format_duration(row.avg_duration_secs),
+            format!("{} / {}", row.wins, row.losses)
+        );
+    }
+    let _ = writeln!(out);
+}
+
+fn write_periods(out: &mut String, breakdown: Breakdown, rows: &[PeriodRow], currency: &str) {
+    let title = match breakdown {
+        Breakdown::Day => "DAY BREAKDOWN",
+        Breakdown::Week => "WEEK BREAKDOWN",
+        Breakdown::Month => "MONTH BREAKDOWN",
+    };
+    let _ = writeln!(out, "{}", title);
+    let _ = writeln!(
+        out,
+        "{:<12} {:>7} {:>16} {:>9} {:>14}",
+        "Period",
+        "Trades",
+        format!("Tot {}", currency),
+        "Tot %",
+        "Win / Loss"
+    );
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "{:<12} {:>7} {:>16} {:>9.2} {:>14}",
+            breakdown.label(row.period),
+            row.trades,
+            row.profit.round_dp(8),
+            row.profit_ratio * 100.0,
+            format!("{} / {}", row.wins, row.losses)
+        );
+    }
+    let _ = writeln!(out);
+}
+
+/// Render the report as text tables; period tables are only included for the requested breakdowns
+pub fn render_text(report: &BacktestReport, currency: &str, breakdowns: &[Breakdown]) -> String {
+    let mut out = String::new();
+    write_rows(&mut out, "PAIRS", &report.per_pair, &report.total, currency);
+    write_rows(&mut out, "EXIT REASONS", &report.exit_reasons, &report.total, currency);
+    for breakdown in breakdowns {
+        let rows = match breakdown {
+            Breakdown::Day => &report.daily,
+            Breakdown::Week => &report.weekly,
+            Breakdown::Month => &report.monthly,
+        };
+        write_periods(&mut out, *breakdown, rows, currency);
+    }
+
+    let summary = [
+        ("Strategy", report.strategy.clone()),
+        ("Timeframe", report.timeframe.as_str().to_string()),
+        (
+            "Backtesting from",
+            report.start_date.format("%Y-%m-%d %H:%M").to_string(),
+        ),
+        ("Backtesting to", report.end_date.format("%Y-%m-%d %H:%M").to_string()),
+        (
+            "Starting balance",
+            format!("{:.2} {}", report.starting_balance, currency),
+        ),
+        ("Final balance", format!("{:.2} {}", report.final_balance, currency)),
+        ("Total trades", report.total.trades.to_string()),
+        (
+            "Total profit %",
+            format!("{:.2}%", report.total.total_profit_ratio * 100.0),
+        ),
+        ("Market change", format!("{:.2}%", report.market_change * 100.0)),
+        ("Exposure", format!("{:.2}%", report.exposure * 100.0)),
+        ("Avg holding", format_duration(report.total.avg_duration_secs)),
+        (
+            "Avg winner / loser",
+            format!(
+                "{} / {}",
+                format_duration(report.avg_winner_duration_secs),
+                format_duration(report.avg_loser_duration_secs)
+            ),
+        ),
+        (
+            "Max win / loss streak",
+            format!("{} / {}", report.max_consecutive_wins, report.max_consecutive_losses),
+        ),
+        ("Max drawdown", format!("{:.2}%", report.max_drawdown * 100.0)),
+        ("Sharpe ratio", format!("{:.2}", report.sharpe_ratio)),
+        ("Profit factor", format!("{:.2}", report.profit_factor)),
+    ];
+    let _ = writeln!(out, "SUMMARY");
+    for (name, value) in summary {
+        let _ = writeln!(out, "{:<24} {}", name, value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap()
+    }
+
+    fn trade(pair: &str, open: DateTime<Utc>, close: DateTime<Utc>, profit: i64, reason: ExitType) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            pair: pair.to_string(),
+            is_open: false,
+            exchange: "backtest".to_string(),
+            open_rate: Decimal::from(100),
+            open_date: open,
+            close_rate: Some(Decimal::from(100 + profit)),
+            close_date: Some(close),
+            amount: Decimal::ONE,
+            stake_amount: Decimal::from(100),
+            strategy: "Test".to_string(),
+            timeframe: Timeframe::OneHour,
+            stop_loss: None,
+            take_profit: None,
+            exit_reason: Some(reason),
+            profit_abs: Some(Decimal::from(profit)),
+            profit_ratio: Some(Decimal::new(profit, 2)),
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
+        }
+    }
+
+    fn result(trades: Vec<Trade>) -> BacktestResult {
+        BacktestResult {
+            strategy: "Test".to_string(),
+            pair: "BTC/USDT,ETH/USDT".to_string(),
+            timeframe: Timeframe::OneHour,
+            start_date: at(1, 0),
+            end_date: at(11, 0),
+            total_trades: trades.len(),
+            winning_trades: 0,
+            losing_trades: 0,
+            win_rate: 0.0,
+            total_profit: Decimal::ZERO,
+            max_drawdown: 0.0,
+            sharpe_ratio: 0.0,
+            profit_factor: 0.0,
+            avg_profit: Decimal::ZERO,
+            avg_loss: Decimal::ZERO,
+            trades,
+            per_pair: vec![],
+            starting_balance: 1000.0,
+            market_change: 0.05,
+        }
+    }
+
+    #[test]
+    fn test_groups_streaks_and_durations() {
+        let report = build_report(&result(vec![
+            trade("BTC/USDT", at(1, 0), at(1, 2), 10, ExitType::Signal),
+            trade("ETH/USDT", at(1, 1), at(1, 5), 20, ExitType::TakeProfit),
+            trade("BTC/USDT", at(2, 0), at(2, 6), -5, ExitType::StopLoss),
+            trade("BTC/USDT", at(3, 0), at(3, 6), -5, ExitType::StopLoss),
+            trade("ETH/USDT", at(4, 0), at(4, 6), 0, ExitType::StopLoss),
+            trade("ETH/USDT", at(9, 0), at(9, 2), 30, ExitType::Signal),
+        ]));
+
+        assert_eq!(report.total.trades, 6);
+        assert_eq!(report.total.total_profit, Decimal::from(50));
+        assert_eq!(report.total.total_profit_ratio, 0.05);
+        assert_eq!(report.final_balance, 1050.0);
+
+        let pairs: Vec<(&str, usize, Decimal)> = report
+            .per_pair
+            .iter()
+            .map(|r| (r.key.as_str(), r.trades, r.total_profit))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![("BTC/USDT", 3, Decimal::ZERO), ("ETH/USDT", 3, Decimal::from(50))]
+        );
+
+        let reasons: Vec<(&str, usize)> = report.exit_reasons.iter().map(|r| (r.key.as_str(), r.trades)).collect();
+        assert_eq!(reasons, vec![("stop_loss", 3), ("signal", 2), ("take_profit", 1)]);
+
+        // Win, win, loss, loss, break-even, win
+        assert_eq!(report.max_consecutive_wins, 2);
+        assert_eq!(report.max_consecutive_losses, 3);
+        assert_eq!(report.avg_winner_duration_secs, 8 * 3600 / 3);
+        assert_eq!(report.avg_loser_duration_secs, 6 * 3600);
+        assert_eq!(report.market_change, 0.05);
+    }
+
+    #[test]
+    fn test_period_tables_cover_the_whole_range() {
+        let report = build_report(&result(vec![
+            trade("BTC/USDT", at(1, 0), at(1, 2), 10, ExitType::Signal),
+            trade("BTC/USDT", at(8, 0), at(9, 2), -4, ExitType::StopLoss),
+        ]));
+
+        assert_eq!(report.daily.len(), 11);
+        assert_eq!(report.daily[0].profit, Decimal::from(10));
+        assert_eq!(report.daily[8].period, NaiveDate::from_ymd_opt(2024, 1, 9).unwrap());
+        assert_eq!(report.daily[8].losses, 1);
+        assert!(report.daily[1..8].iter().all(|d| d.trades == 0));
+
+        // 2024-01-01 is a Monday
+        let weeks: Vec<(NaiveDate, usize)> = report.weekly.iter().map(|w| (w.period, w.trades)).collect();
+        assert_eq!(
+            weeks,
+            vec![
+                (NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 1),
+                (NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(), 1),
+            ]
+        );
+        assert_eq!(report.monthly.len(), 1);
+        assert_eq!(report.monthly[0].profit, Decimal::from(6));
+        assert_eq!(report.monthly[0].profit_ratio, 0.006);
+    }
+
+    #[test]
+    fn test_exposure_counts_overlapping_trades_once() {
+        let report = build_report(&result(vec![
+            trade("BTC/USDT", at(1, 0), at(2, 0), 1, ExitType::Signal),
+            trade("ETH/USDT", at(1, 12), at(3, 0), 1, ExitType::Signal),
+            trade("ETH/USDT", at(6, 0), at(7, 0), 1, ExitType::Signal),
+        ]));
+
+        // 3 of the 10 backtested days
+        assert!((report.exposure - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_render_text_includes_requested_breakdowns() {
+        let report = build_report(&result(vec![trade(
+            "BTC/USDT",
+            at(1, 0),
+            at(1, 2),
+            10,
+            ExitType::Signal,
+        )]));
+
+        let text = render_text(&report, "USDT", &[Breakdown::Month]);
+        assert!(text.contains("PAIRS"));
+        assert!(text.contains("EXIT REASONS"));
+        assert!(text.contains("MONTH BREAKDOWN"));
+        assert!(text.contains("2024-01 "));
+        assert!(!text.contains("DAY BREAKDOWN"));
+        assert!(text.contains("Market change"));
+        assert!(text.contains("5.00%"));
+        assert_eq!("week".parse::<Breakdown>(), Ok(Breakdown::Week));
+        assert!("year".parse::<Breakdown>().is_err());
+    }
+}
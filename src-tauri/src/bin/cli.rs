@@ -0,0 +1,823 @@
+//! Headless command line interface, usable on servers without the desktop UI.
+//!
+//! ```text
+//! freqtrade-cli trade
+//! freqtrade-cli download-data --pairs BTC/USDT ETH/USDT --timeframes 1h 4h --timerange 20240101-20240301
+//! freqtrade-cli backtesting --pairs BTC/USDT ETH/USDT --timerange 20240101-20240301
+//! freqtrade-cli hyperopt --epochs 100 --spaces stoploss roi --timerange 20240101-20240301
+//! freqtrade-cli walk-forward --in-sample-days 90 --out-of-sample-days 30 --epochs 50
+//! freqtrade-cli monte-carlo --simulations 1000 --method bootstrap --seed 42
+//! freqtrade-cli lookahead-analysis --pairs BTC/USDT --timerange 20240101-20240301
+//! freqtrade-cli recursive-analysis --pairs BTC/USDT --startup-candles 50 100 200 500
+//! ```
+
+use clap::{Parser, Subcommand};
+use freqtrade_rs_lib::api::{self, AppState};
+use freqtrade_rs_lib::backtest::export::{ExportFormat, default_export_dir, export_result, import_results};
+use freqtrade_rs_lib::backtest::lookahead::{self, LookaheadConfig, analyze_lookahead};
+use freqtrade_rs_lib::backtest::monte_carlo::{MonteCarloConfig, SimulationMethod, run_monte_carlo};
+use freqtrade_rs_lib::backtest::recursive::{self, RecursiveConfig, analyze_startup_candles};
+use freqtrade_rs_lib::backtest::report::{Breakdown, build_report, render_text};
+use freqtrade_rs_lib::backtest::{BacktestConfig, BacktestEngine, DEFAULT_COMMISSION, load_candles};
+use freqtrade_rs_lib::config::{AppConfig, ConfigManager};
+use freqtrade_rs_lib::data::downloader::{DataDownloader, DownloadRequest, TimeRange};
+use freqtrade_rs_lib::exchange::market::find_market;
+use freqtrade_rs_lib::exchange::{ExchangeContext, ExchangeRegistry};
+use freqtrade_rs_lib::optimize::{Hyperopt, HyperoptConfig, WalkForward, WalkForwardConfig};
+use freqtrade_rs_lib::persistence::Repository;
+use freqtrade_rs_lib::strategy::load_strategy;
+use freqtrade_rs_lib::types::{BotStatus, Timeframe};
+use freqtrade_rs_lib::{AppError, Result};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(clap::Args)]
+struct BacktestingArgs {
+    /// Pairs to backtest together, defaults to bot.trading_pairs
+    #[arg(short, long, alias = "pair", num_args = 1..)]
+    pairs: Vec<String>,
+    /// Timeframe to backtest, defaults to bot.timeframe
+    #[arg(short, long)]
+    timeframe: Option<String>,
+    /// Lower timeframe, e.g. 1m, used to resolve stoploss and take-profit exits within a candle
+    #[arg(long)]
+    timeframe_detail: Option<String>,
+    /// Range as YYYYMMDD-YYYYMMDD, defaults to all stored candles
+    #[arg(long)]
+    timerange: Option<String>,
+    /// Commission per trade as a ratio
+    #[arg(long, default_value_t = DEFAULT_COMMISSION)]
+    commission: f64,
+    /// Expected slippage of market orders as a ratio
+    #[arg(long, default_value_t = 0.0)]
+    slippage: f64,
+    /// Stoploss as a negative ratio, e.g. -0.1
+    #[arg(long, allow_hyphen_values = true)]
+    stoploss: Option<f64>,
+    /// Take-profit as a ratio, e.g. 0.05
+    #[arg(long)]
+    take_profit: Option<f64>,
+    /// Profit tables to print in addition to the pair and exit reason tables: day, week, month
+    #[arg(long, num_args = 1..)]
+    breakdown: Vec<Breakdown>,
+    /// Export the result as json and/or csv
+    #[arg(long, num_args = 1..)]
+    export: Vec<ExportFormat>,
+    /// Export directory, defaults to backtest_results next to the database
+    #[arg(long)]
+    export_dir: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct HyperoptArgs {
+    /// Pairs to backtest together, defaults to bot.trading_pairs
+    #[arg(short, long, alias = "pair", num_args = 1..)]
+    pairs: Vec<String>,
+    /// Timeframe to backtest, defaults to bot.timeframe
+    #[arg(short, long)]
+    timeframe: Option<String>,
+    /// Range as YYYYMMDD-YYYYMMDD, defaults to all stored candles
+    #[arg(long)]
+    timerange: Option<String>,
+    #[arg(short, long, default_value_t = 100)]
+    epochs: usize,
+    /// Parameter spaces to optimize
+    #[arg(short, long, num_args = 1.., default_values_t = ["stoploss".to_string(), "roi".to_string()])]
+    spaces: Vec<String>,
+    /// Seed for reproducible parameter sampling
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Commission per trade as a ratio
+    #[arg(long, default_value_t = DEFAULT_COMMISSION)]
+    commission: f64,
+    /// Expected slippage of market orders as a ratio
+    #[arg(long, default_value_t = 0.0)]
+    slippage: f64,
+}
+
+#[derive(clap::Args)]
+struct WalkForwardArgs {
+    /// Pairs to backtest together, defaults to bot.trading_pairs
+    #[arg(short, long, alias = "pair", num_args = 1..)]
+    pairs: Vec<String>,
+    /// Timeframe to backtest, defaults to bot.timeframe
+    #[arg(short, long)]
+    timeframe: Option<String>,
+    /// Range as YYYYMMDD-YYYYMMDD, defaults to all stored candles
+    #[arg(long)]
+    timerange: Option<String>,
+    /// Days each optimization window spans
+    #[arg(long, default_value_t = 90)]
+    in_sample_days: i64,
+    /// Days the optimized parameters are validated on before the windows roll forward
+    #[arg(long, default_value_t = 30)]
+    out_of_sample_days: i64,
+    /// Hyperopt epochs per in-sample window
+    #[arg(short, long, default_value_t = 50)]
+    epochs: usize,
+    /// Parameter spaces to optimize
+    #[arg(short, long, num_args = 1.., default_values_t = ["stoploss".to_string(), "roi".to_string()])]
+    spaces: Vec<String>,
+    /// Seed for reproducible parameter sampling
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Commission per trade as a ratio
+    #[arg(long, default_value_t = DEFAULT_COMMISSION)]
+    commission: f64,
+    /// Expected slippage of market orders as a ratio
+    #[arg(long, default_value_t = 0.0)]
+    slippage: f64,
+}
+
+#[derive(clap::Args)]
+struct MonteCarloArgs {
+    /// Saved backtest result to simulate, defaults to the latest
+    #[arg(long)]
+    id: Option<i64>,
+    /// Number of simulated trade sequences
+    #[arg(short = 'n', long, default_value_t = 1000)]
+    simulations: usize,
+    /// shuffle reorders the trades, bootstrap resamples them with replacement
+    #[arg(short, long, default_value = "shuffle")]
+    method: SimulationMethod,
+    /// Maximum random change of the fee rate per trade
+    #[arg(long, default_value_t = 0.0)]
+    fee_noise: f64,
+    /// Maximum random adverse slippage per trade
+    #[arg(long, default_value_t = 0.0)]
+    slippage_noise: f64,
+    /// Loss of the starting balance, as a ratio, counted as ruin
+    #[arg(long, default_value_t = 0.5)]
+    ruin_threshold: f64,
+    /// Confidence levels to report
+    #[arg(long, num_args = 1.., default_values_t = [0.9, 0.95, 0.99])]
+    confidence: Vec<f64>,
+    /// Seed for reproducible simulations
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(clap::Args)]
+struct LookaheadArgs {
+    /// Pairs to analyze, defaults to bot.trading_pairs
+    #[arg(short, long, alias = "pair", num_args = 1..)]
+    pairs: Vec<String>,
+    /// Timeframe to analyze, defaults to bot.timeframe
+    #[arg(short, long)]
+    timeframe: Option<String>,
+    /// Range as YYYYMMDD-YYYYMMDD, defaults to all stored candles
+    #[arg(long)]
+    timerange: Option<String>,
+    /// Check every N-th candle besides the candles with signals
+    #[arg(long, default_value_t = 1)]
+    step: usize,
+    /// Changed signals to list per pair
+    #[arg(long, default_value_t = 20)]
+    max_rows: usize,
+}
+
+#[derive(clap::Args)]
+struct RecursiveArgs {
+    /// Pairs to analyze, defaults to bot.trading_pairs
+    #[arg(short, long, alias = "pair", num_args = 1..)]
+    pairs: Vec<String>,
+    /// Timeframe to analyze, defaults to bot.timeframe
+    #[arg(short, long)]
+    timeframe: Option<String>,
+    /// Range as YYYYMMDD-YYYYMMDD, defaults to all stored candles
+    #[arg(long)]
+    timerange: Option<String>,
+    /// Startup candle counts to compare against the full history
+    #[arg(long, num_args = 1.., default_values_t = RecursiveConfig::default().startup_candles)]
+    startup_candles: Vec<usize>,
+    /// Largest relative difference from the full-history value that counts as converged
+    #[arg(long, default_value_t = 0.001)]
+    tolerance: f64,
+}
+
+#[derive(Parser)]
+#[command(name = "freqtrade-cli", version, about = "freqtrade-rs without the desktop UI")]
+struct Cli {
+    /// Configuration file
+    #[arg(short, long, global = true, default_value = "config/default.toml")]
+    config: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the trading bot until interrupted with Ctrl-C, serving the REST API when enabled
+    Trade,
+    /// Backtest the configured strategy on candles stored in the database
+    Backtesting(BacktestingArgs),
+    /// Import backtest results from a JSON export into the database
+    BacktestingImport {
+        /// JSON file in freqtrade's backtest-results format
+        file: PathBuf,
+    },
+    /// Optimize the stoploss and take-profit of the configured strategy on stored candles
+    Hyperopt(HyperoptArgs),
+    /// Optimize on rolling in-sample windows and backtest each optimum on the window that follows
+    WalkForward(WalkForwardArgs),
+    /// Simulate reordered or resampled trades of a saved backtest result
+    MonteCarlo(MonteCarloArgs),
+    /// Check whether the strategy's signals or indicators change when future candles are removed
+    LookaheadAnalysis(LookaheadArgs),
+    /// Find how many startup candles the strategy's indicators need to converge
+    RecursiveAnalysis(RecursiveArgs),
+    /// Download OHLCV data into the database, fetching only missing candles
+    DownloadData {
+        /// Pairs to download, defaults to bot.trading_pairs
+        #[arg(short, long, num_args = 1..)]
+        pairs: Vec<String>,
+        /// Timeframes to download, defaults to bot.timeframe
+        #[arg(short, long, num_args = 1..)]
+        timeframes: Vec<String>,
+        /// Range as YYYYMMDD-YYYYMMDD; the end may be omitted
+        #[arg(long, conflicts_with = "days")]
+        timerange: Option<String>,
+        /// Download the last N days
+        #[arg(long, default_value_t = 30)]
+        days: i64,
+    },
+    /// List candles stored in the database
+    ListData,
+    /// Print the effective configuration with credentials masked
+    ShowConfig,
+    /// Resolve bot.trading_pairs against the exchange's markets
+    TestPairlist,
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).init();
+
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let config = load_config(&cli.config).await?;
+
+    match cli.command {
+        Command::Trade => trade(config).await,
+        Command::Backtesting(args) => backtesting(config, args).await,
+        Command::BacktestingImport { file } => backtesting_import(config, file).await,
+        Command::Hyperopt(args) => hyperopt(config, args).await,
+        Command::WalkForward(args) => walk_forward(config, args).await,
+        Command::MonteCarlo(args) => monte_carlo(config, args).await,
+        Command::LookaheadAnalysis(args) => lookahead_analysis(config, args).await,
+        Command::RecursiveAnalysis(args) => recursive_analysis(config, args).await,
+        Command::DownloadData {
+            pairs,
+            timeframes,
+            timerange,
+            days,
+        } => download_data(config, pairs, timeframes, timerange, days).await,
+        Command::ListData => list_data(config).await,
+        Command::ShowConfig => show_config(config),
+        Command::TestPairlist => test_pairlist(config).await,
+    }
+}
+
+async fn load_config(path: &PathBuf) -> Result<AppConfig> {
+    let mut manager = if path.exists() {
+        ConfigManager::load_from_file(path).await?
+    } else {
+        eprintln!("Config file {} not found, using defaults", path.display());
+        ConfigManager::new(AppConfig::default())
+    };
+    manager.load_from_env().await?;
+    Ok(manager.config().clone())
+}
+
+async fn open_repository(config: &AppConfig) -> Result<Arc<Repository>> {
+    Ok(Arc::new(Repository::new(&config.database.path).await?))
+}
+
+async fn trade(config: AppConfig) -> Result<()> {
+    let repository = open_repository(&config).await?;
+    println!(
+        "Trading {} on {} ({})",
+        config.bot.trading_pairs.join(", "),
+        config.exchange.name,
+        if config.bot.dry_run { "dry run" } else { "live" }
+    );
+
+    // The REST API controls the same bot, so it can be stopped and restarted remotely
+    let app = AppState::new(config, repository);
+    api::spawn_if_enabled(&app).await;
+    app.start_bot().await?;
+
+    tokio::signal::ctrl_c().await?;
+    eprintln!("Stopping bot...");
+    if app.bot_status().await == BotStatus::Running {
+        app.stop_bot().await?;
+    }
+    Ok(())
+}
+
+async fn backtesting(config: AppConfig, args: BacktestingArgs) -> Result<()> {
+    let pairs = if args.pairs.is_empty() {
+        config.bot.trading_pairs.clone()
+    } else {
+        args.pairs
+    };
+    let timeframe: Timeframe = args.timeframe.unwrap_or_else(|| config.bot.timeframe.clone()).parse()?;
+    let timerange = args.timerange.map(|t| TimeRange::parse(&t)).transpose()?;
+
+    let repository = open_repository(&config).await?;
+    let strategy = load_strategy(&config.strategy.name)?;
+    let data = load_candles(
+        &repository,
+        &pairs,
+        timeframe.as_str(),
+        timerange.as_ref(),
+        strategy.startup_candle_count(),
+    )
+    .await?;
+    let candle_count: usize = data.values().map(Vec::len).sum();
+
+    let backtest_config = BacktestConfig {
+        commission: args.commission,
+        slippage: args.slippage,
+        stoploss: args.stoploss,
+        take_profit: args.take_profit,
+        ..BacktestConfig::from_bot(&config.bot, timeframe, &data, timerange.as_ref())
+    };
+    let (start_date, end_date) = (backtest_config.start_date, backtest_config.end_date);
+    println!(
+        "Backtesting {} on {} pairs, {} {} candles from {} to {}",
+        strategy.name(),
+        data.len(),
+        candle_count,
+        timeframe.as_str(),
+        start_date,
+        end_date
+    );
+
+    let mut engine = BacktestEngine::new(backtest_config, strategy, data);
+    if let Some(detail_timeframe) = args.timeframe_detail {
+        let detail_timeframe: Timeframe = detail_timeframe.parse()?;
+        let detail = load_candles(&repository, &pairs, detail_timeframe.as_str(), timerange.as_ref(), 0).await?;
+        println!("Resolving exits on {} candles", detail_timeframe.as_str());
+        engine = engine.with_detail(detail_timeframe, detail);
+    }
+    let result = engine.run().await?;
+    let id = repository.save_backtest_result(&result).await?;
+    println!("Saved as backtest result #{}", id);
+    println!();
+    print!(
+        "{}",
+        render_text(&build_report(&result), &config.bot.stake_currency, &args.breakdown)
+    );
+
+    if !args.export.is_empty() {
+        let dir = args
+            .export_dir
+            .unwrap_or_else(|| default_export_dir(&config.database.path));
+        for file in export_result(&result, &dir, &args.export).await? {
+            println!("Exported {}", file.display());
+        }
+    }
+    Ok(())
+}
+
+async fn backtesting_import(config: AppConfig, file: PathBuf) -> Result<()> {
+    let repository = open_repository(&config).await?;
+    for result in import_results(&file).await? {
+        let id = repository.save_backtest_result(&result).await?;
+        println!(
+            "Imported {} on {} ({} trades) as backtest result #{}",
+            result.strategy, result.pair, result.total_trades, id
+        );
+    }
+    Ok(())
+}
+
+async fn hyperopt(config: AppConfig, args: HyperoptArgs) -> Result<()> {
+    let pairs = if args.pairs.is_empty() {
+        config.bot.trading_pairs.clone()
+    } else {
+        args.pairs
+    };
+    let timeframe: Timeframe = args.timeframe.unwrap_or_else(|| config.bot.timeframe.clone()).parse()?;
+    let timerange = args.timerange.map(|t| TimeRange::parse(&t)).transpose()?;
+
+    let repository = open_repository(&config).await?;
+    let strategy = load_strategy(&config.strategy.name)?;
+    let data = load_candles(
+        &repository,
+        &pairs,
+        timeframe.as_str(),
+        timerange.as_ref(),
+        strategy.startup_candle_count(),
+    )
+    .await?;
+    let backtest_config = BacktestConfig {
+        commission: args.commission,
+        slippage: args.slippage,
+        ..BacktestConfig::from_bot(&config.bot, timeframe, &data, timerange.as_ref())
+    };
+    println!(
+        "Optimizing {} of {} on {} pairs from {} to {}",
+        args.spaces.join(", "),
+        strategy.name(),
+        data.len(),
+        backtest_config.start_date,
+        backtest_config.end_date
+    );
+    let hyperopt = Hyperopt::new(
+        repository,
+        HyperoptConfig {
+            epochs: args.epochs,
+            spaces: args.spaces,
+            strategy: config.strategy.name.clone(),
+            seed: args.seed,
+        },
+    );
+
+    let result = hyperopt.optimize(&backtest_config, strategy, &data).await?;
+    println!("Best epoch {} with loss {:.5}", result.best_epoch, result.best_loss);
+    if let Some(best) = result
+        .epoch_results
+        .iter()
+        .find(|e| e.epoch == result.best_epoch)
+        .and_then(|e| e.result.as_ref())
+    {
+        println!(
+            "{} trades, profit {:.2} {}, max drawdown {:.2}%",
+            best.total_trades,
+            best.total_profit,
+            config.bot.stake_currency,
+            best.max_drawdown * 100.0
+        );
+    }
+    println!("{}", serde_json::to_string_pretty(&result.best_params)?);
+    Ok(())
+}
+
+async fn walk_forward(config: AppConfig, args: WalkForwardArgs) -> Result<()> {
+    let pairs = if args.pairs.is_empty() {
+        config.bot.trading_pairs.clone()
+    } else {
+        args.pairs
+    };
+    let timeframe: Timeframe = args.timeframe.unwrap_or_else(|| config.bot.timeframe.clone()).parse()?;
+    let timerange = args.timerange.map(|t| TimeRange::parse(&t)).transpose()?;
+
+    let repository = open_repository(&config).await?;
+    let strategy = load_strategy(&config.strategy.name)?;
+    let data = load_candles(
+        &repository,
+        &pairs,
+        timeframe.as_str(),
+        timerange.as_ref(),
+        strategy.startup_candle_count(),
+    )
+    .await?;
+    let backtest_config = BacktestConfig {
+        commission: args.commission,
+        slippage: args.slippage,
+        ..BacktestConfig::from_bot(&config.bot, timeframe, &data, timerange.as_ref())
+    };
+    let walk_forward = WalkForward::new(
+        repository,
+        WalkForwardConfig {
+            in_sample_days: args.in_sample_days,
+            out_of_sample_days: args.out_of_sample_days,
+            hyperopt: HyperoptConfig {
+                epochs: args.epochs,
+                spaces: args.spaces,
+                strategy: config.strategy.name.clone(),
+                seed: args.seed,
+            },
+        },
+    );
+
+    let result = walk_forward.run(&backtest_config, strategy, &data).await?;
+    println!(
+        "{:>3} {:<23} {:<23} {:>7} {:>9} {:>7} {:>9} {:>9}  Params",
+        "#", "In-sample", "Out-of-sample", "IS tr", "IS %", "OOS tr", "OOS %", "OOS DD %"
+    );
+    for window in &result.windows {
+        let mut params: Vec<String> = window
+            .best_params
+            .params
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, serde_json::to_string(value).unwrap_or_default()))
+            .collect();
+        params.sort();
+        println!(
+            "{:>3} {:<23} {:<23} {:>7} {:>9.2} {:>7} {:>9.2} {:>9.2}  {}",
+            window.index,
+            format!(
+                "{} - {}",
+                window.in_sample_start.format("%Y-%m-%d"),
+                window.in_sample_end.format("%Y-%m-%d")
+            ),
+            format!(
+                "{} - {}",
+                window.out_of_sample_start.format("%Y-%m-%d"),
+                window.out_of_sample_end.format("%Y-%m-%d")
+            ),
+            window.in_sample.total_trades,
+            window.in_sample.profit_ratio * 100.0,
+            window.out_of_sample.total_trades,
+            window.out_of_sample.profit_ratio * 100.0,
+            window.out_of_sample.max_drawdown * 100.0,
+            params.join(" ")
+        );
+    }
+
+    let oos = &result.out_of_sample;
+    let final_balance = result.equity_curve.last().map_or(oos.starting_balance, |p| p.value);
+    println!();
+    println!("Out-of-sample trades: {}", oos.total_trades);
+    println!("Win rate:             {:.2}%", oos.win_rate * 100.0);
+    println!(
+        "Total profit:         {} {}",
+        oos.total_profit.round_dp(8),
+        config.bot.stake_currency
+    );
+    println!(
+        "Final balance:        {:.2} {}",
+        final_balance, config.bot.stake_currency
+    );
+    println!("Max drawdown:         {:.2}%", oos.max_drawdown * 100.0);
+    println!("Sharpe ratio:         {:.2}", oos.sharpe_ratio);
+    println!("Market change:        {:.2}%", oos.market_change * 100.0);
+    Ok(())
+}
+
+async fn monte_carlo(config: AppConfig, args: MonteCarloArgs) -> Result<()> {
+    let repository = open_repository(&config).await?;
+    let id = match args.id {
+        Some(id) => id,
+        None => repository
+            .get_backtest_results()
+            .await?
+            .first()
+            .map(|r| r.id)
+            .ok_or_else(|| AppError::NotFound("No saved backtest results".to_string()))?,
+    };
+    let result = repository
+        .get_backtest_result(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Backtest result {} not found", id)))?;
+
+    let mc = run_monte_carlo(
+        &result,
+        &MonteCarloConfig {
+            simulations: args.simulations,
+            method: args.method,
+            fee_noise: args.fee_noise,
+            slippage_noise: args.slippage_noise,
+            ruin_threshold: args.ruin_threshold,
+            confidence_levels: args.confidence,
+            seed: args.seed,
+        },
+    )?;
+    let currency = &config.bot.stake_currency;
+    println!(
+        "{} simulations of the {} trades of backtest result #{} ({})",
+        mc.simulations, result.total_trades, id, result.strategy
+    );
+    println!(
+        "Original:      profit {:.2} {}, max drawdown {:.2}%",
+        mc.original_profit,
+        currency,
+        mc.original_max_drawdown * 100.0
+    );
+    println!();
+    println!(
+        "{:<16} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "", "Mean", "Std dev", "Min", "Median", "Max"
+    );
+    for (name, d, scale) in [
+        (format!("Profit {}", currency), &mc.final_profit, 1.0),
+        ("Max drawdown %".to_string(), &mc.max_drawdown, 100.0),
+    ] {
+        println!(
+            "{:<16} {:>12.2} {:>12.2} {:>12.2} {:>12.2} {:>12.2}",
+            name,
+            d.mean * scale,
+            d.std_dev * scale,
+            d.min * scale,
+            d.median * scale,
+            d.max * scale
+        );
+    }
+    println!();
+    println!(
+        "{:<12} {:>16} {:>16}",
+        "Confidence", "Profit at least", "Drawdown at most"
+    );
+    for c in &mc.confidence {
+        println!(
+            "{:<12} {:>16.2} {:>15.2}%",
+            format!("{:.0}%", c.level * 100.0),
+            c.final_profit,
+            c.max_drawdown * 100.0
+        );
+    }
+    println!();
+    println!("Risk of ruin:  {:.2}%", mc.ruin_probability * 100.0);
+    Ok(())
+}
+
+async fn lookahead_analysis(config: AppConfig, args: LookaheadArgs) -> Result<()> {
+    let pairs = if args.pairs.is_empty() {
+        config.bot.trading_pairs.clone()
+    } else {
+        args.pairs
+    };
+    let timeframe: Timeframe = args.timeframe.unwrap_or_else(|| config.bot.timeframe.clone()).parse()?;
+    let timerange = args.timerange.map(|t| TimeRange::parse(&t)).transpose()?;
+
+    let repository = open_repository(&config).await?;
+    let strategy = load_strategy(&config.strategy.name)?;
+    let data = load_candles(
+        &repository,
+        &pairs,
+        timeframe.as_str(),
+        timerange.as_ref(),
+        strategy.startup_candle_count(),
+    )
+    .await?;
+    let lookahead_config = LookaheadConfig { step: args.step };
+
+    let mut biased = Vec::new();
+    for (pair, candles) in &data {
+        let report = analyze_lookahead(strategy.as_ref(), pair, candles, &lookahead_config).await?;
+        print!("{}", lookahead::render_text(&report, args.max_rows));
+        if report.has_bias() {
+            biased.push(pair.as_str());
+        }
+    }
+    println!();
+    if biased.is_empty() {
+        println!("No lookahead bias found in {} on {} pairs", strategy.name(), data.len());
+    } else {
+        println!("{} uses future data on {}", strategy.name(), biased.join(", "));
+    }
+    Ok(())
+}
+
+async fn recursive_analysis(config: AppConfig, args: RecursiveArgs) -> Result<()> {
+    let pairs = if args.pairs.is_empty() {
+        config.bot.trading_pairs.clone()
+    } else {
+        args.pairs
+    };
+    let timeframe: Timeframe = args.timeframe.unwrap_or_else(|| config.bot.timeframe.clone()).parse()?;
+    let timerange = args.timerange.map(|t| TimeRange::parse(&t)).transpose()?;
+
+    let repository = open_repository(&config).await?;
+    let strategy = load_strategy(&config.strategy.name)?;
+    let data = load_candles(&repository, &pairs, timeframe.as_str(), timerange.as_ref(), 0).await?;
+    let recursive_config = RecursiveConfig {
+        tolerance: args.tolerance,
+        startup_candles: args.startup_candles,
+    };
+
+    let mut required = Some(0);
+    for (pair, candles) in &data {
+        let report = analyze_startup_candles(strategy.as_ref(), pair, candles, &recursive_config).await?;
+        print!("{}", recursive::render_text(&report));
+        required = required.zip(report.required_startup_candles()).map(|(a, b)| a.max(b));
+    }
+    println!();
+    match required {
+        Some(required) => println!(
+            "{} needs startup_candle_count >= {}, declares {}",
+            strategy.name(),
+            required,
+            strategy.startup_candle_count()
+        ),
+        None => println!(
+            "Some indicators of {} do not converge within the tried startup candle counts",
+            strategy.name()
+        ),
+    }
+    Ok(())
+}
+
+async fn download_data(
+    config: AppConfig,
+    pairs: Vec<String>,
+    timeframes: Vec<String>,
+    timerange: Option<String>,
+    days: i64,
+) -> Result<()> {
+    let pairs = if pairs.is_empty() {
+        config.bot.trading_pairs.clone()
+    } else {
+        pairs
+    };
+    if pairs.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No pairs given and bot.trading_pairs is empty".to_string(),
+        ));
+    }
+    let timeframes = if timeframes.is_empty() {
+        vec![config.bot.timeframe.clone()]
+    } else {
+        timeframes
+    };
+    let timerange = match timerange {
+        Some(timerange) => TimeRange::parse(&timerange)?,
+        None => TimeRange::last_days(days)?,
+    };
+
+    let repository = open_repository(&config).await?;
+    let exchange = ExchangeRegistry::default()
+        .create(&ExchangeContext {
+            exchange: config.exchange,
+            bot: config.bot,
+            repository: Some(repository.clone()),
+        })
+        .await?;
+
+    let request = DownloadRequest {
+        pairs,
+        timeframes,
+        timerange,
+    };
+    let runs = DataDownloader::new(exchange, repository).download(&request).await?;
+    for run in runs {
+        println!(
+            "{:<16} {:<6} {:>8} new candles",
+            run.pair, run.timeframe, run.candles_count
+        );
+    }
+    Ok(())
+}
+
+async fn list_data(config: AppConfig) -> Result<()> {
+    let data = open_repository(&config).await?.get_available_data().await?;
+    if data.is_empty() {
+        println!("No data stored in {}", config.database.path);
+        return Ok(());
+    }
+
+    println!(
+        "{:<16} {:<6} {:<20} {:<20} {:>8}",
+        "Pair", "TF", "From", "To", "Candles"
+    );
+    for d in data {
+        println!(
+            "{:<16} {:<6} {:<20} {:<20} {:>8}",
+            d.pair,
+            d.timeframe,
+            d.start_date.format("%Y-%m-%d %H:%M:%S"),
+            d.end_date.format("%Y-%m-%d %H:%M:%S"),
+            d.candles_count
+        );
+    }
+    Ok(())
+}
+
+fn show_config(config: AppConfig) -> Result<()> {
+    let toml = toml::to_string_pretty(&config.redacted())
+        .map_err(|e| AppError::Config(format!("Failed to serialize config: {}", e)))?;
+    println!("{}", toml);
+    Ok(())
+}
+
+async fn test_pairlist(config: AppConfig) -> Result<()> {
+    let exchange = ExchangeRegistry::default()
+        .create(&ExchangeContext {
+            exchange: config.exchange.clone(),
+            bot: config.bot.clone(),
+            repository: None,
+        })
+        .await?;
+    let markets = exchange.load_markets().await?;
+
+    let mut whitelist = Vec::new();
+    for pair in &config.bot.trading_pairs {
+        match find_market(&markets, pair) {
+            Some(market) if !market.active => eprintln!("Removed {}: market is not active", pair),
+            Some(market) if market.quote != config.bot.stake_currency => eprintln!(
+                "Removed {}: quoted in {} instead of {}",
+                pair, market.quote, config.bot.stake_currency
+            ),
+            Some(_) => whitelist.push(pair.as_str()),
+            None => eprintln!("Removed {}: not listed on {}", pair, config.exchange.name),
+        }
+    }
+
+    println!(
+        "{} pairs with {} as quote currency: {:?}",
+        whitelist.len(),
+        config.bot.stake_currency,
+        whitelist
+    );
+    Ok(())
+}
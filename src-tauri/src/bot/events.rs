@@ -0,0 +1,56 @@
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 事件通道容量，消费者落后超过该数量时会丢失最早的事件
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Tauri 前端事件名
+pub const BOT_EVENT_NAME: &str = "bot-event";
+
+/// 机器人运行过程中产生的事件，通过 broadcast 通道分发给前端和 WebSocket 客户端。
+///
+/// 序列化为 `{"type": "entry_fill", "data": {...}}`，与 freqtrade 的 WebSocket 消息格式一致
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum BotEvent {
+    /// 运行状态变化
+    Status { status: BotStatus, message: Option<String> },
+    /// 策略产生买入/卖出信号
+    Signal {
+        pair: String,
+        side: TradeSide,
+        count: usize,
+    },
+    /// 开仓订单成交（含部分成交后的更新）
+    EntryFill { trade: Trade },
+    /// 平仓订单成交
+    ExitFill { trade: Trade },
+    /// 保护机制触发；pair 为 None 表示全局停止
+    ProtectionTrigger {
+        pair: Option<String>,
+        protection: String,
+        reason: String,
+        until: DateTime<Utc>,
+    },
+    /// 处理过程中的错误
+    Error { pair: Option<String>, message: String },
+}
+
+impl BotEvent {
+    pub fn protection_trigger(pair: Option<&str>, stop: &crate::risk::manager::StopReason) -> Self {
+        BotEvent::ProtectionTrigger {
+            pair: pair.map(str::to_string),
+            protection: stop.protection.clone(),
+            reason: stop.reason.clone(),
+            until: stop.until,
+        }
+    }
+
+    pub fn error(pair: Option<&str>, message: impl Into<String>) -> Self {
+        BotEvent::Error {
+            pair: pair.map(str::to_string),
+            message: message.into(),
+        }
+    }
+}
@@ -1,11 +1,21 @@
+pub mod events;
+
 use crate::error::Result;
+use crate::exchange::binance::market_stream::{BinanceMarketStream, MarketEvent};
+use crate::exchange::binance::user_stream::{BinanceUserStream, UserDataEvent};
 use crate::types::*;
+use crate::wallets::Wallets;
 use chrono::Utc;
+use events::BotEvent;
 use futures::future::try_join_all;
 use rust_decimal::Decimal;
 use std::sync::Arc;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
+/// 每个交易对至少加载的K线数量，策略声明的启动K线更多时以策略为准
+const KLINE_HISTORY: usize = 500;
+
 #[derive(Clone)]
 pub struct TradingBot {
     status: Arc<tokio::sync::RwLock<BotStatus>>,
@@ -14,6 +24,10 @@ pub struct TradingBot {
     repository: Arc<crate::persistence::Repository>,
     risk_manager: Option<Arc<crate::risk::RiskManager>>,
     config: crate::config::BotConfig,
+    market_stream: Option<Arc<BinanceMarketStream>>,
+    user_stream: Option<Arc<BinanceUserStream>>,
+    wallets: Arc<Wallets>,
+    events: broadcast::Sender<BotEvent>,
 }
 
 impl TradingBot {
@@ -24,6 +38,8 @@ impl TradingBot {
         risk_manager: Option<Arc<crate::risk::RiskManager>>,
         config: crate::config::BotConfig,
     ) -> Self {
+        let wallets = Arc::new(Wallets::new(exchange.clone(), &config.stake_currency));
+        let (events, _) = broadcast::channel(events::EVENT_CHANNEL_CAPACITY);
         Self {
             status: Arc::new(tokio::sync::RwLock::new(BotStatus::Stopped)),
             exchange,
@@ -31,12 +47,269 @@ impl TradingBot {
             repository,
             risk_manager,
             config,
+            market_stream: None,
+            user_stream: None,
+            wallets,
+            events,
+        }
+    }
+
+    /// 按配置组装机器人：交易所、策略、默认保护机制以及 Binance 的 WebSocket 数据流
+    pub async fn from_config(
+        config: &crate::config::AppConfig,
+        repository: Arc<crate::persistence::Repository>,
+    ) -> Result<Self> {
+        // 根据 config.exchange.name 选择交易所；dry_run 模式下订单和余额在本地模拟
+        let exchange = crate::exchange::ExchangeRegistry::default()
+            .create(&crate::exchange::ExchangeContext {
+                exchange: config.exchange.clone(),
+                bot: config.bot.clone(),
+                repository: Some(repository.clone()),
+            })
+            .await?;
+
+        let strategy = crate::strategy::load_strategy(&config.strategy.name)?;
+
+        let risk_manager = Arc::new(crate::risk::RiskManager::new(repository.clone()));
+
+        // 添加默认的保护机制
+        // 冷却期保护：2次亏损后停止1小时
+        risk_manager
+            .add_protection(Box::new(crate::risk::CooldownPeriod::new(
+                crate::risk::CooldownPeriodConfig {
+                    stop_duration: 60,
+                    lookback_period: 1440,
+                    stop_after_losses: 2,
+                },
+            )))
+            .await?;
+
+        // 最大回撤保护：回撤超过20%时停止
+        risk_manager
+            .add_protection(Box::new(crate::risk::MaxDrawdownProtection::new(
+                crate::risk::MaxDrawdownProtectionConfig {
+                    max_allowed_drawdown: 20.0,
+                    lookback_period: 1440,
+                    stop_duration: 60,
+                },
+            )))
+            .await?;
+
+        let mut bot = Self::new(
+            exchange.clone(),
+            strategy,
+            repository,
+            Some(risk_manager),
+            config.bot.clone(),
+        );
+
+        // Binance 现货使用 WebSocket 推送K线，替代每轮 REST 轮询；自定义 base_url（如测试网）时仍走 REST
+        // 断线重连后通过 REST 补齐断线期间缺失的K线
+        let binance_spot =
+            matches!(config.exchange.name.as_str(), "binance" | "paper") && config.exchange.base_url.is_none();
+        if binance_spot && !config.bot.trading_pairs.is_empty() {
+            let market_stream =
+                BinanceMarketStream::new(&config.bot.trading_pairs, std::slice::from_ref(&config.bot.timeframe))
+                    .with_backfill(exchange);
+            bot = bot.with_market_stream(Arc::new(market_stream));
         }
+
+        // 实盘模式通过用户数据流同步订单成交和余额
+        if config.exchange.name == "binance"
+            && config.exchange.base_url.is_none()
+            && !config.bot.dry_run
+            && !config.exchange.key.is_empty()
+        {
+            let user_stream = BinanceUserStream::new(config.exchange.key.clone());
+            bot = bot.with_user_stream(Arc::new(user_stream));
+        }
+
+        Ok(bot)
+    }
+
+    pub fn risk_manager(&self) -> Option<Arc<crate::risk::RiskManager>> {
+        self.risk_manager.clone()
+    }
+
+    pub fn wallets(&self) -> Arc<Wallets> {
+        self.wallets.clone()
+    }
+
+    /// 使用 WebSocket 行情：K线从缓存读取，并在K线收盘时立即处理
+    pub fn with_market_stream(mut self, stream: Arc<BinanceMarketStream>) -> Self {
+        self.market_stream = Some(stream);
+        self
+    }
+
+    /// 使用用户数据流：订单成交/撤单和余额变动实时同步到交易记录
+    pub fn with_user_stream(mut self, stream: Arc<BinanceUserStream>) -> Self {
+        self.user_stream = Some(stream);
+        self
+    }
+
+    /// 使用外部事件通道，机器人重启后订阅者无需重新订阅
+    pub fn with_events(mut self, events: broadcast::Sender<BotEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// 订阅机器人事件
+    pub fn subscribe(&self) -> broadcast::Receiver<BotEvent> {
+        self.events.subscribe()
+    }
+
+    /// 发布事件；没有订阅者时直接丢弃
+    fn emit(&self, event: BotEvent) {
+        let _ = self.events.send(event);
+    }
+
+    async fn set_status(&self, status: BotStatus, message: Option<String>) {
+        *self.status.write().await = status;
+        self.emit(BotEvent::Status { status, message });
     }
 
     pub async fn start(&self) -> Result<()> {
-        *self.status.write().await = BotStatus::Running;
+        if let Err(e) = self.apply_futures_settings().await {
+            self.set_status(BotStatus::Error, Some(e.to_string())).await;
+            return Err(e);
+        }
+        self.set_status(BotStatus::Running, None).await;
+
+        let mut tasks = Vec::new();
+        if let Some(stream) = self.user_stream.clone() {
+            let events = stream.subscribe();
+            tasks.push(tokio::spawn(async move { stream.run().await }));
+            let bot = self.clone();
+            tasks.push(tokio::spawn(async move { bot.consume_user_events(events).await }));
+        }
+
+        let market_events = self.market_stream.clone().map(|stream| {
+            let events = stream.subscribe();
+            tasks.push(tokio::spawn(async move { stream.run().await }));
+            events
+        });
+
+        let result = self.run_loop(market_events).await;
+        for task in tasks {
+            task.abort();
+        }
+        result
+    }
+
+    /// 为每个交易对设置杠杆和保证金模式；现货交易所不支持时，仅在配置了杠杆或做空时报错
+    #[cfg_attr(test, visibility::make(pub))]
+    async fn apply_futures_settings(&self) -> Result<()> {
+        let needs_futures = self.config.leverage > 1 || self.config.can_short;
+        for pair in &self.config.trading_pairs {
+            let pair = pair.trim();
+            let result = match self.exchange.set_leverage(pair, self.config.leverage).await {
+                Ok(()) => self.exchange.set_margin_mode(pair, self.config.margin_mode).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => {}
+                Err(crate::error::AppError::NotImplemented(_)) if !needs_futures => return Ok(()),
+                Err(e) => {
+                    return Err(crate::error::AppError::Config(format!(
+                        "Failed to set {}x leverage and {:?} margin for {}: {}",
+                        self.config.leverage, self.config.margin_mode, pair, e
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn consume_user_events(&self, mut events: broadcast::Receiver<UserDataEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(UserDataEvent::OrderUpdate(order)) => {
+                    if let Err(e) = self.apply_order_update(&order).await {
+                        eprintln!("Failed to apply order update {}: {}", order.id, e);
+                        self.emit(BotEvent::error(
+                            Some(&order.symbol),
+                            format!("Failed to apply order update {}: {}", order.id, e),
+                        ));
+                    }
+                }
+                Ok(UserDataEvent::BalanceUpdate(balances)) => self.wallets.apply_balances(balances).await,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("User-data consumer lagged, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// 根据交易所推送的订单状态更新订单及其关联的交易
+    #[cfg_attr(test, visibility::make(pub))]
+    async fn apply_order_update(&self, order: &Order) -> Result<()> {
+        self.repository.save_order(order, None).await?;
+
+        let Some(trade_id) = self.repository.get_order_trade_id(&order.id).await? else {
+            return Ok(());
+        };
+        let Some(mut trade) = self.repository.get_trade(trade_id).await? else {
+            return Ok(());
+        };
+        if !trade.is_open {
+            return Ok(());
+        }
+
+        // 开仓方向：多单买入，空单卖出
+        let is_entry = order.side
+            == if trade.is_short {
+                TradeSide::Sell
+            } else {
+                TradeSide::Buy
+            };
+        match (is_entry, order.status) {
+            // 开仓单未成交即被撤销/拒绝：交易不存在
+            (true, OrderStatus::Canceled | OrderStatus::Rejected | OrderStatus::Expired) if order.filled.is_zero() => {
+                self.repository.delete_trade(trade.id).await?;
+            }
+            // 开仓单（部分）成交：按实际成交量和均价更新持仓
+            (true, _) if order.filled > Decimal::ZERO => {
+                trade.amount = order.filled;
+                if let Some(price) = order.price {
+                    trade.open_rate = price;
+                }
+                // 占用资金按实际成交额计算，杠杆交易只占用保证金部分
+                trade.stake_amount = order.filled * trade.open_rate / trade.leverage;
+                // 持仓变化后强平价随之变化
+                if let Some(price) = self.fetch_liquidation_price(&trade.pair, &order.symbol).await {
+                    trade.liquidation_price = Some(price);
+                }
+                self.repository.update_trade(&trade).await?;
+                self.emit(BotEvent::EntryFill { trade });
+            }
+            (false, OrderStatus::Filled) => {
+                let close_price = order.price.unwrap_or(trade.open_rate);
+                let closed = close_trade(&trade, close_price, ExitType::Signal);
+                self.repository.update_trade(&closed).await?;
+                self.emit(BotEvent::ExitFill { trade: closed });
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// 查询交易对当前持仓的强平价；现货没有持仓，查询失败时仅记录日志
+    async fn fetch_liquidation_price(&self, pair: &str, symbol: &str) -> Option<Decimal> {
+        match self.exchange.fetch_positions().await {
+            Ok(positions) => positions
+                .into_iter()
+                .find(|p| p.symbol == symbol || p.symbol == pair)
+                .and_then(|p| p.liquidation_price),
+            Err(e) => {
+                eprintln!("Failed to fetch position of {}: {}", pair, e);
+                None
+            }
+        }
+    }
 
+    async fn run_loop(&self, mut market_events: Option<broadcast::Receiver<MarketEvent>>) -> Result<()> {
         loop {
             let status = *self.status.read().await;
 
@@ -48,13 +321,15 @@ impl TradingBot {
                     {
                         eprintln!("Global stop triggered: {}", stop_reason.reason);
                         eprintln!("Unlock at: {}", stop_reason.until);
-                        *self.status.write().await = BotStatus::Stopped;
+                        self.emit(BotEvent::protection_trigger(None, &stop_reason));
+                        self.set_status(BotStatus::Stopped, Some(stop_reason.reason)).await;
                         break;
                     }
 
                     if let Err(e) = self.process_all_pairs().await {
                         eprintln!("Error processing cycle: {}", e);
-                        *self.status.write().await = BotStatus::Error;
+                        self.emit(BotEvent::error(None, e.to_string()));
+                        self.set_status(BotStatus::Error, Some(e.to_string())).await;
                         break;
                     }
                 }
@@ -72,20 +347,28 @@ impl TradingBot {
                 tokio::time::Duration::from_secs(1)
             };
 
-            tokio::time::sleep(sleep_duration).await;
+            match market_events.as_mut() {
+                Some(events) if self.config.process_only_new_candles => {
+                    // 收到K线收盘事件立即处理，超时则照常轮询
+                    let _ =
+                        tokio::time::timeout(sleep_duration, wait_for_closed_candle(events, &self.config.timeframe))
+                            .await;
+                }
+                _ => tokio::time::sleep(sleep_duration).await,
+            }
         }
 
         Ok(())
     }
 
     pub async fn stop(&self) -> Result<()> {
-        *self.status.write().await = BotStatus::Stopped;
+        self.set_status(BotStatus::Stopped, None).await;
         Ok(())
     }
 
     #[allow(dead_code)]
     pub async fn pause(&self) -> Result<()> {
-        *self.status.write().await = BotStatus::Paused;
+        self.set_status(BotStatus::Paused, None).await;
         Ok(())
     }
 
@@ -104,6 +387,11 @@ impl TradingBot {
             ));
         }
 
+        // 每轮开始前同步账户余额，失败时沿用上一次的余额
+        if let Err(e) = self.wallets.update().await {
+            eprintln!("Failed to update wallets: {}", e);
+        }
+
         let futures = pairs.iter().map(|pair| {
             let pair = pair.trim();
             async move {
@@ -117,6 +405,7 @@ impl TradingBot {
                     Ok(_) => Ok(()),
                     Err(e) => {
                         eprintln!("Error processing pair {}: {}", pair, e);
+                        self.emit(BotEvent::error(Some(pair), e.to_string()));
                         // Continue processing other pairs even if one fails
                         Ok(())
                     }
@@ -129,212 +418,296 @@ impl TradingBot {
         Ok(())
     }
 
+    /// 每个交易对加载的K线数量：策略所需的启动K线加上当前K线，不少于 KLINE_HISTORY
+    fn kline_history(&self) -> usize {
+        (self.strategy.startup_candle_count() + 1).max(KLINE_HISTORY)
+    }
+
+    /// 优先使用 WebSocket 维护的K线缓存，缓存未初始化时通过 REST 拉取并写入缓存
+    async fn load_klines(&self, pair: &str, timeframe: &str) -> Result<Vec<OHLCV>> {
+        let Some(stream) = &self.market_stream else {
+            return self.exchange.fetch_ohlcv(pair, timeframe, self.kline_history()).await;
+        };
+
+        let cache = stream.cache();
+        if let Some(klines) = cache.get_seeded(pair, timeframe).await {
+            // 模拟盘的限价单依赖K线撮合，缓存的K线不经过 fetch_ohlcv，需要单独通知交易所
+            self.exchange.observe_candles(pair, &klines).await?;
+            return Ok(klines);
+        }
+
+        let klines = self.exchange.fetch_ohlcv(pair, timeframe, self.kline_history()).await?;
+        cache.seed(pair, timeframe, &klines).await;
+        Ok(cache.get(pair, timeframe).await)
+    }
+
     #[cfg_attr(test, visibility::make(pub))]
     async fn process_cycle(&self, pair: &str, timeframe: &str) -> Result<()> {
         // 获取 K线数据
-        let klines = self.exchange.fetch_ohlcv(pair, timeframe, 500).await?;
+        let klines = self.load_klines(pair, timeframe).await?;
 
         // 获取当前开仓交易
         let open_trades: Vec<Trade> = self.repository.get_open_trades().await?;
+        let open_trade = open_trades.iter().find(|t| t.pair == pair);
 
-        // 处理卖信号
+        // 处理卖信号：平多单；允许做空时无持仓则开空单
         let sell_signals = self.strategy.populate_sell_trend(&klines).await?;
         if !sell_signals.is_empty() {
-            // Find trade for current pair
-            if let Some(trade) = open_trades.iter().find(|t| t.pair == pair) {
-                eprintln!("Got {} sell signals for {}", sell_signals.len(), pair);
-                // 执行卖出逻辑
-                if let Some(risk_mgr) = &self.risk_manager
-                    && let Some(stop_reason) = risk_mgr.check_pair_stop(pair).await?
-                {
-                    eprintln!("Pair stop triggered for {}: {}", pair, stop_reason.reason);
-                    return Ok(());
+            match open_trade {
+                Some(trade) if !trade.is_short => {
+                    eprintln!("Got {} sell signals for {}", sell_signals.len(), pair);
+                    self.emit(BotEvent::Signal {
+                        pair: pair.to_string(),
+                        side: TradeSide::Sell,
+                        count: sell_signals.len(),
+                    });
+                    self.exit_trade(trade, &klines).await?;
                 }
-                // 执行卖出（dry_run 模式下只记录）
-                if self.config.dry_run {
-                    eprintln!("[DRY RUN] Would sell {}", pair);
-                } else {
-                    eprintln!("Sell signal for {}", pair);
-                    let order_req = OrderRequest {
-                        symbol: pair.to_string(),
+                Some(_) => eprintln!("Already have open short for {}", pair),
+                None if self.config.can_short && open_trades.len() < self.config.max_open_trades => {
+                    eprintln!("Got {} sell signals for {}", sell_signals.len(), pair);
+                    self.emit(BotEvent::Signal {
+                        pair: pair.to_string(),
                         side: TradeSide::Sell,
-                        order_type: OrderType::Market,
-                        amount: trade.amount,
-                        price: None,
-                    };
-
-                    match self.exchange.create_order(order_req).await {
-                        Ok(order) => {
-                            eprintln!("Sell order executed: {:?}", order);
-                            // Use order price if available, otherwise fallback to current candle close
-                            let close_price = match order.price {
-                                Some(p) => p,
-                                None => {
-                                    klines
-                                        .last()
-                                        .ok_or_else(|| {
-                                            crate::error::AppError::InvalidInput("No klines data available".to_string())
-                                        })?
-                                        .close
-                                }
-                            };
-
-                            let mut updated_trade = trade.clone();
-                            updated_trade.is_open = false;
-                            updated_trade.close_rate = Some(close_price);
-                            updated_trade.close_date = Some(Utc::now());
-                            updated_trade.exit_reason = Some(ExitType::Signal);
-
-                            // Calculate profit
-                            let profit_abs = (close_price - trade.open_rate) * trade.amount;
-                            let profit_ratio = (close_price - trade.open_rate) / trade.open_rate;
-
-                            updated_trade.profit_abs = Some(profit_abs);
-                            updated_trade.profit_ratio = Some(profit_ratio);
-
-                            if let Err(e) = self.repository.update_trade(&updated_trade).await {
-                                eprintln!("Failed to update trade in DB: {}", e);
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to execute sell order: {}", e);
-                        }
-                    }
+                        count: sell_signals.len(),
+                    });
+                    // 本轮已开空单，不再处理买信号
+                    return self.enter_trade(pair, true, &klines, &open_trades).await;
                 }
-            } else {
-                eprintln!("Sell signals found for {} but no open trade exists", pair);
+                None => eprintln!("Sell signals found for {} but no open trade exists", pair),
             }
         }
 
-        // 处理买信号
-        if open_trades.len() < self.config.max_open_trades {
+        // 处理买信号：平空单；有空余仓位时开多单
+        let has_short = open_trade.is_some_and(|t| t.is_short);
+        if has_short || open_trades.len() < self.config.max_open_trades {
             let buy_signals = self.strategy.populate_buy_trend(&klines).await?;
             if !buy_signals.is_empty() {
                 eprintln!("Got {} buy signals for {}", buy_signals.len(), pair);
-
-                // 检查交易对是否已有持仓
-                if open_trades.iter().any(|t| t.pair == pair) {
-                    eprintln!("Already have open position for {}", pair);
-                    return Ok(());
-                }
-
-                // 检查风控
-                if let Some(risk_mgr) = &self.risk_manager
-                    && let Some(stop_reason) = risk_mgr.check_pair_stop(pair).await?
-                {
-                    eprintln!("Pair stop triggered for {}: {}", pair, stop_reason.reason);
-                    return Ok(());
+                self.emit(BotEvent::Signal {
+                    pair: pair.to_string(),
+                    side: TradeSide::Buy,
+                    count: buy_signals.len(),
+                });
+
+                match open_trade {
+                    Some(trade) if trade.is_short => self.exit_trade(trade, &klines).await?,
+                    // 检查交易对是否已有持仓
+                    Some(_) => eprintln!("Already have open position for {}", pair),
+                    None => self.enter_trade(pair, false, &klines, &open_trades).await?,
                 }
+            }
+        }
 
-                // 计算买入金额和数量
-                let current_price = klines
-                    .last()
-                    .ok_or_else(|| crate::error::AppError::InvalidInput("No klines data available".to_string()))?
-                    .close;
-                let stake_amount_f64 = self.config.stake_amount;
-                let stake_amount_decimal = Decimal::try_from(stake_amount_f64).unwrap_or(Decimal::from(0));
-
-                let amount = if stake_amount_decimal > Decimal::ZERO {
-                    stake_amount_decimal / current_price
-                } else {
-                    return Err(crate::error::AppError::InvalidInput("Invalid stake amount".to_string()));
-                };
+        Ok(())
+    }
 
-                // dry_run 模式
-                if self.config.dry_run {
-                    eprintln!(
-                        "[DRY RUN] Would buy {} - Amount: {} @ Price: {}",
-                        pair, amount, current_price
-                    );
+    /// 按信号平仓：多单卖出、空单买回（dry_run 模式下由 PaperExchange 模拟成交）
+    async fn exit_trade(&self, trade: &Trade, klines: &[OHLCV]) -> Result<()> {
+        let pair = trade.pair.as_str();
+        if let Some(risk_mgr) = &self.risk_manager
+            && let Some(stop_reason) = risk_mgr.check_pair_stop(pair).await?
+        {
+            eprintln!("Pair stop triggered for {}: {}", pair, stop_reason.reason);
+            self.emit(BotEvent::protection_trigger(Some(pair), &stop_reason));
+            return Ok(());
+        }
 
-                    // 创建模拟交易记录
-                    let trade = Trade {
-                        id: Uuid::new_v4(),
-                        pair: pair.to_string(),
-                        is_open: true,
-                        exchange: self.exchange.get_name().to_string(),
-                        open_rate: current_price,
-                        open_date: Utc::now(),
-                        close_rate: None,
-                        close_date: None,
-                        amount,
-                        stake_amount: stake_amount_decimal,
-                        strategy: self.strategy.name().to_string(),
-                        timeframe: Timeframe::OneHour,
-                        stop_loss: None,
-                        take_profit: None,
-                        exit_reason: None,
-                        profit_abs: None,
-                        profit_ratio: None,
-                    };
-
-                    if let Err(e) = self.repository.create_trade(&trade).await {
-                        eprintln!("Failed to create trade in DB: {}", e);
+        let (side, label) = if trade.is_short {
+            (TradeSide::Buy, "Buy")
+        } else {
+            (TradeSide::Sell, "Sell")
+        };
+        eprintln!("Exit signal for {}", pair);
+        let order_req = OrderRequest {
+            symbol: pair.to_string(),
+            side,
+            order_type: OrderType::Market,
+            amount: trade.amount,
+            price: None,
+        };
+
+        match self.exchange.create_order(order_req).await {
+            Ok(order) => {
+                eprintln!("Exit order executed: {:?}", order);
+                // Use order price if available, otherwise fallback to current candle close
+                let close_price = match order.price {
+                    Some(p) => p,
+                    None => {
+                        klines
+                            .last()
+                            .ok_or_else(|| {
+                                crate::error::AppError::InvalidInput("No klines data available".to_string())
+                            })?
+                            .close
                     }
+                };
 
-                    return Ok(());
+                let updated_trade = close_trade(trade, close_price, ExitType::Signal);
+                if let Err(e) = self.repository.update_trade(&updated_trade).await {
+                    eprintln!("Failed to update trade in DB: {}", e);
+                }
+                self.emit(BotEvent::ExitFill { trade: updated_trade });
+                if let Err(e) = self.repository.save_order(&order, Some(trade.id)).await {
+                    eprintln!("Failed to save order in DB: {}", e);
                 }
+            }
+            Err(e) => {
+                eprintln!("Failed to execute exit order: {}", e);
+                self.emit(BotEvent::error(Some(pair), format!("{} order failed: {}", label, e)));
+            }
+        }
 
-                // 实盘模式 - 执行买入
-                eprintln!(
-                    "Executing buy for {} - Amount: {} @ Price: {}",
-                    pair, amount, current_price
-                );
-                let order_req = OrderRequest {
-                    symbol: pair.to_string(),
-                    side: TradeSide::Buy,
-                    order_type: OrderType::Market,
-                    amount,
-                    price: None,
-                };
+        Ok(())
+    }
 
-                match self.exchange.create_order(order_req).await {
-                    Ok(order) => {
-                        eprintln!("Buy order executed: {:?}", order);
-
-                        // 计算实际成交价格 (避免除零)
-                        let avg_price = order.price.unwrap_or_else(|| {
-                            if order.amount > Decimal::ZERO {
-                                order.filled * current_price / order.amount
-                            } else {
-                                current_price
-                            }
-                        });
-
-                        // 创建交易记录
-                        let trade = Trade {
-                            id: Uuid::new_v4(),
-                            pair: pair.to_string(),
-                            is_open: true,
-                            exchange: self.exchange.get_name().to_string(),
-                            open_rate: avg_price,
-                            open_date: Utc::now(),
-                            close_rate: None,
-                            close_date: None,
-                            amount: order.filled,
-                            stake_amount: stake_amount_decimal,
-                            strategy: self.strategy.name().to_string(),
-                            timeframe: Timeframe::OneHour,
-                            stop_loss: None,
-                            take_profit: None,
-                            exit_reason: None,
-                            profit_abs: None,
-                            profit_ratio: None,
-                        };
-
-                        if let Err(e) = self.repository.create_trade(&trade).await {
-                            eprintln!("Failed to create trade in DB: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to execute buy order: {}", e);
-                        return Err(crate::error::AppError::Exchange(format!("Buy order failed: {}", e)));
+    /// 开仓：多单买入、空单卖出；下单数量为保证金乘以杠杆
+    #[cfg_attr(test, visibility::make(pub))]
+    async fn enter_trade(&self, pair: &str, is_short: bool, klines: &[OHLCV], open_trades: &[Trade]) -> Result<()> {
+        // 检查风控
+        if let Some(risk_mgr) = &self.risk_manager
+            && let Some(stop_reason) = risk_mgr.check_pair_stop(pair).await?
+        {
+            eprintln!("Pair stop triggered for {}: {}", pair, stop_reason.reason);
+            self.emit(BotEvent::protection_trigger(Some(pair), &stop_reason));
+            return Ok(());
+        }
+
+        // 计算下单金额和数量
+        let current_price = klines
+            .last()
+            .ok_or_else(|| crate::error::AppError::InvalidInput("No klines data available".to_string()))?
+            .close;
+        // 下单金额受可用余额限制；stake_amount 为 0 时按剩余仓位平分可用余额；低于最小下单额时跳过
+        let leverage = Decimal::from(self.config.leverage);
+        let stake_amount_decimal = self
+            .wallets
+            .get_trade_stake_amount(
+                pair,
+                Decimal::try_from(self.config.stake_amount).unwrap_or(Decimal::ZERO),
+                self.config.max_open_trades,
+                open_trades,
+                leverage,
+            )
+            .await?;
+        if stake_amount_decimal <= Decimal::ZERO {
+            eprintln!(
+                "Insufficient {} balance or stake below minimum notional to open trade for {}",
+                self.wallets.stake_currency(),
+                pair
+            );
+            return Ok(());
+        }
+        let amount = stake_amount_decimal * leverage / current_price;
+
+        let (side, label) = if is_short {
+            (TradeSide::Sell, "Sell")
+        } else {
+            (TradeSide::Buy, "Buy")
+        };
+        eprintln!(
+            "Executing {} for {} - Amount: {} @ Price: {}",
+            side, pair, amount, current_price
+        );
+        let order_req = OrderRequest {
+            symbol: pair.to_string(),
+            side,
+            order_type: OrderType::Market,
+            amount,
+            price: None,
+        };
+
+        match self.exchange.create_order(order_req).await {
+            Ok(order) => {
+                eprintln!("Entry order executed: {:?}", order);
+
+                // 计算实际成交价格 (避免除零)
+                let avg_price = order.price.unwrap_or_else(|| {
+                    if order.amount > Decimal::ZERO {
+                        order.filled * current_price / order.amount
+                    } else {
+                        current_price
                     }
+                });
+
+                // 创建交易记录
+                let trade = Trade {
+                    id: Uuid::new_v4(),
+                    pair: pair.to_string(),
+                    is_open: true,
+                    exchange: self.exchange.get_name().to_string(),
+                    open_rate: avg_price,
+                    open_date: Utc::now(),
+                    close_rate: None,
+                    close_date: None,
+                    amount: order.filled,
+                    stake_amount: stake_amount_decimal,
+                    strategy: self.strategy.name().to_string(),
+                    timeframe: Timeframe::OneHour,
+                    stop_loss: None,
+                    take_profit: None,
+                    exit_reason: None,
+                    profit_abs: None,
+                    profit_ratio: None,
+                    is_short,
+                    leverage,
+                    liquidation_price: self.fetch_liquidation_price(pair, &order.symbol).await,
+                };
+
+                if let Err(e) = self.repository.create_trade(&trade).await {
+                    eprintln!("Failed to create trade in DB: {}", e);
+                } else if let Err(e) = self.repository.save_order(&order, Some(trade.id)).await {
+                    eprintln!("Failed to save order in DB: {}", e);
                 }
+                self.emit(BotEvent::EntryFill { trade });
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("Failed to execute entry order: {}", e);
+                self.emit(BotEvent::error(Some(pair), format!("{} order failed: {}", label, e)));
+                Err(crate::error::AppError::Exchange(format!(
+                    "{} order failed: {}",
+                    label, e
+                )))
             }
         }
+    }
+}
 
-        Ok(())
+/// 以指定价格平仓并计算收益
+fn close_trade(trade: &Trade, close_price: Decimal, exit_reason: ExitType) -> Trade {
+    let mut closed = trade.clone();
+    closed.is_open = false;
+    closed.close_rate = Some(close_price);
+    closed.close_date = Some(Utc::now());
+    closed.exit_reason = Some(exit_reason);
+    // 空单价格下跌为盈利；收益率按保证金（杠杆）计算
+    let price_diff = if trade.is_short {
+        trade.open_rate - close_price
+    } else {
+        close_price - trade.open_rate
+    };
+    closed.profit_abs = Some(price_diff * trade.amount);
+    closed.profit_ratio = Some(price_diff / trade.open_rate * trade.leverage);
+    closed
+}
+
+/// 等待指定周期的K线收盘事件
+async fn wait_for_closed_candle(events: &mut broadcast::Receiver<MarketEvent>, timeframe: &str) {
+    // 丢弃上一轮处理期间积压的事件，避免同一根K线触发多次处理；积压溢出时跳过丢失的事件继续丢弃
+    while let Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) = events.try_recv() {}
+
+    loop {
+        match events.recv().await {
+            Ok(MarketEvent::Kline {
+                timeframe: tf,
+                is_closed: true,
+                ..
+            }) if tf == timeframe => return,
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(_)) => return,
+            Err(broadcast::error::RecvError::Closed) => std::future::pending::<()>().await,
+        }
     }
 }
 
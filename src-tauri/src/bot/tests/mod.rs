@@ -12,6 +12,7 @@ mod bot_tests {
     use async_trait::async_trait;
     use rust_decimal::Decimal;
     use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     use tokio::sync::RwLock;
 
     // --- Mock Exchange ---
@@ -19,6 +20,16 @@ mod bot_tests {
         name: String,
         ohlcv_data: RwLock<Vec<OHLCV>>,
         orders: RwLock<Vec<Order>>,
+        /// `limit` of the last fetch_ohlcv call
+        ohlcv_limit: AtomicUsize,
+        /// Candles passed to observe_candles
+        observed_candles: AtomicUsize,
+        /// Accept leverage and margin mode settings like a futures exchange
+        futures: AtomicBool,
+        /// Leverage and margin mode settings, in call order
+        futures_settings: RwLock<Vec<String>>,
+        /// Open positions reported by fetch_positions
+        positions: RwLock<Vec<Position>>,
     }
 
     impl MockExchange {
@@ -27,6 +38,11 @@ mod bot_tests {
                 name: "mock_exchange".to_string(),
                 ohlcv_data: RwLock::new(vec![]),
                 orders: RwLock::new(vec![]),
+                ohlcv_limit: AtomicUsize::new(0),
+                observed_candles: AtomicUsize::new(0),
+                futures: AtomicBool::new(false),
+                futures_settings: RwLock::new(vec![]),
+                positions: RwLock::new(vec![]),
             }
         }
     }
@@ -37,6 +53,10 @@ mod bot_tests {
             &self.name
         }
 
+        async fn fetch_markets(&self) -> crate::error::Result<Vec<crate::exchange::market::Market>> {
+            Ok(vec![])
+        }
+
         async fn fetch_ticker(&self, symbol: &str) -> crate::error::Result<Ticker> {
             Ok(Ticker {
                 symbol: symbol.to_string(),
@@ -46,12 +66,8 @@ mod bot_tests {
             })
         }
 
-        async fn fetch_ohlcv(
-            &self,
-            _symbol: &str,
-            _timeframe: &str,
-            _limit: usize,
-        ) -> crate::error::Result<Vec<OHLCV>> {
+        async fn fetch_ohlcv(&self, _symbol: &str, _timeframe: &str, limit: usize) -> crate::error::Result<Vec<OHLCV>> {
+            self.ohlcv_limit.store(limit, Ordering::SeqCst);
             let data = self.ohlcv_data.read().await;
             if data.is_empty() {
                 // Return some dummy data if empty to prevent errors in tests
@@ -68,6 +84,11 @@ mod bot_tests {
             }
         }
 
+        async fn observe_candles(&self, _symbol: &str, candles: &[OHLCV]) -> crate::error::Result<()> {
+            self.observed_candles.fetch_add(candles.len(), Ordering::SeqCst);
+            Ok(())
+        }
+
         async fn fetch_balance(&self) -> crate::error::Result<Balance> {
             Ok(Balance {
                 currency: "USDT".to_string(),
@@ -77,8 +98,12 @@ mod bot_tests {
             })
         }
 
+        async fn fetch_balances(&self) -> crate::error::Result<Vec<Balance>> {
+            Ok(vec![self.fetch_balance().await?])
+        }
+
         async fn fetch_positions(&self) -> crate::error::Result<Vec<Position>> {
-            Ok(vec![])
+            Ok(self.positions.read().await.clone())
         }
 
         async fn create_order(&self, req: OrderRequest) -> crate::error::Result<Order> {
@@ -125,12 +150,35 @@ mod bot_tests {
         async fn fetch_orders(&self, _symbol: &str) -> crate::error::Result<Vec<Order>> {
             Ok(self.orders.read().await.clone())
         }
+
+        async fn set_leverage(&self, symbol: &str, leverage: u32) -> crate::error::Result<()> {
+            if !self.futures.load(Ordering::SeqCst) {
+                return Err(crate::error::AppError::NotImplemented("spot".to_string()));
+            }
+            self.futures_settings
+                .write()
+                .await
+                .push(format!("{} {}x", symbol, leverage));
+            Ok(())
+        }
+
+        async fn set_margin_mode(&self, symbol: &str, mode: MarginMode) -> crate::error::Result<()> {
+            if !self.futures.load(Ordering::SeqCst) {
+                return Err(crate::error::AppError::NotImplemented("spot".to_string()));
+            }
+            self.futures_settings
+                .write()
+                .await
+                .push(format!("{} {:?}", symbol, mode));
+            Ok(())
+        }
     }
 
     // --- Mock Strategy ---
     struct MockStrategy {
         buy_signals: RwLock<Vec<Signal>>,
         sell_signals: RwLock<Vec<Signal>>,
+        startup_candles: AtomicUsize,
     }
 
     impl MockStrategy {
@@ -138,6 +186,7 @@ mod bot_tests {
             Self {
                 buy_signals: RwLock::new(vec![]),
                 sell_signals: RwLock::new(vec![]),
+                startup_candles: AtomicUsize::new(0),
             }
         }
 
@@ -168,6 +217,9 @@ mod bot_tests {
         fn timeframes(&self) -> &[Timeframe] {
             &[Timeframe::OneHour]
         }
+        fn startup_candle_count(&self) -> usize {
+            self.startup_candles.load(Ordering::SeqCst)
+        }
         async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> crate::error::Result<()> {
             Ok(())
         }
@@ -291,6 +343,9 @@ mod bot_tests {
             exit_reason: None,
             profit_abs: None,
             profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
         ctx.repository.create_trade(&trade).await.unwrap();
 
@@ -328,4 +383,356 @@ mod bot_tests {
         let expected_pairs: HashSet<String> = ["BTC/USDT".to_string(), "ETH/USDT".to_string()].into();
         assert_eq!(pairs, expected_pairs);
     }
+
+    #[tokio::test]
+    async fn test_kline_history_covers_startup_candles() {
+        let ctx = setup_bot(true).await;
+
+        // Strategies without startup candles still get the default history
+        ctx.bot.process_cycle("BTC/USDT", "1h").await.unwrap();
+        assert_eq!(ctx.exchange.ohlcv_limit.load(Ordering::SeqCst), 500);
+
+        ctx.strategy.startup_candles.store(800, Ordering::SeqCst);
+        ctx.bot.process_cycle("BTC/USDT", "1h").await.unwrap();
+        assert_eq!(ctx.exchange.ohlcv_limit.load(Ordering::SeqCst), 801);
+    }
+
+    #[tokio::test]
+    async fn test_process_cycle_uses_market_stream_cache() {
+        use crate::exchange::binance::market_stream::BinanceMarketStream;
+
+        let mut ctx = setup_bot(false).await;
+        let stream = Arc::new(BinanceMarketStream::new(&["BTC/USDT".to_string()], &["1h".to_string()]));
+        stream
+            .cache()
+            .seed(
+                "BTC/USDT",
+                "1h",
+                &[OHLCV {
+                    timestamp: chrono::Utc::now(),
+                    open: Decimal::from(200),
+                    high: Decimal::from(200),
+                    low: Decimal::from(200),
+                    close: Decimal::from(200),
+                    volume: Decimal::from(1),
+                }],
+            )
+            .await;
+        ctx.bot = ctx.bot.with_market_stream(stream);
+
+        ctx.strategy.set_buy_signal().await;
+        ctx.bot.process_cycle("BTC/USDT", "1h").await.unwrap();
+
+        // Stake 100 at the cached close of 200, not the REST close of 105
+        let orders = ctx.exchange.orders.read().await;
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].amount, Decimal::new(5, 1));
+        // The exchange still sees the cached candles, so paper limit orders can fill
+        assert_eq!(ctx.exchange.ohlcv_limit.load(Ordering::SeqCst), 0);
+        assert_eq!(ctx.exchange.observed_candles.load(Ordering::SeqCst), 1);
+    }
+
+    fn futures_config() -> BotConfig {
+        BotConfig {
+            max_open_trades: 3,
+            stake_amount: 100.0,
+            dry_run: false,
+            process_only_new_candles: false,
+            trading_pairs: vec!["BTC/USDT".to_string(), "ETH/USDT".to_string()],
+            leverage: 3,
+            margin_mode: MarginMode::Cross,
+            can_short: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_futures_settings_per_pair() {
+        let ctx = setup_bot(false).await;
+        let bot = |config: BotConfig| {
+            TradingBot::new(
+                ctx.exchange.clone(),
+                ctx.strategy.clone(),
+                ctx.repository.clone(),
+                None,
+                config,
+            )
+        };
+
+        // A spot exchange is fine without leverage or shorts, but not with them
+        let spot = BotConfig {
+            leverage: 1,
+            can_short: false,
+            ..futures_config()
+        };
+        bot(spot).apply_futures_settings().await.unwrap();
+        assert!(matches!(
+            bot(futures_config()).apply_futures_settings().await,
+            Err(crate::error::AppError::Config(_))
+        ));
+
+        ctx.exchange.futures.store(true, Ordering::SeqCst);
+        bot(futures_config()).apply_futures_settings().await.unwrap();
+        assert_eq!(
+            *ctx.exchange.futures_settings.read().await,
+            vec!["BTC/USDT 3x", "BTC/USDT Cross", "ETH/USDT 3x", "ETH/USDT Cross"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_entry_and_exit() {
+        let mut ctx = setup_bot(false).await;
+        ctx.bot = TradingBot::new(
+            ctx.exchange.clone(),
+            ctx.strategy.clone(),
+            ctx.repository.clone(),
+            None,
+            futures_config(),
+        );
+
+        // A sell signal without an open trade opens a short sized at stake times leverage
+        ctx.strategy.set_sell_signal().await;
+        ctx.bot.process_cycle("BTC/USDT", "1h").await.unwrap();
+        let trade = ctx.repository.get_open_trades().await.unwrap().remove(0);
+        assert!(trade.is_short);
+        assert_eq!(trade.leverage, Decimal::from(3));
+        assert_eq!(trade.amount, Decimal::from(300) / Decimal::from(105));
+        assert_eq!(ctx.exchange.orders.read().await[0].side, TradeSide::Sell);
+
+        // The repeated sell signal does not add to it, the buy signal buys it back
+        ctx.strategy.set_buy_signal().await;
+        ctx.bot.process_cycle("BTC/USDT", "1h").await.unwrap();
+        let orders = ctx.exchange.orders.read().await;
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[1].side, TradeSide::Buy);
+        assert_eq!(orders[1].amount, trade.amount);
+        let closed = ctx.repository.get_trade(trade.id).await.unwrap().unwrap();
+        assert!(!closed.is_open);
+        assert!(ctx.repository.get_open_trades().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_closed_candle_skips_lagged_backlog() {
+        use crate::bot::{MarketEvent, wait_for_closed_candle};
+        use std::time::Duration;
+        use tokio::sync::broadcast;
+
+        let closed = || MarketEvent::Kline {
+            pair: "BTC/USDT".to_string(),
+            timeframe: "1h".to_string(),
+            candle: OHLCV {
+                timestamp: chrono::Utc::now(),
+                open: Decimal::ONE,
+                high: Decimal::ONE,
+                low: Decimal::ONE,
+                close: Decimal::ONE,
+                volume: Decimal::ONE,
+            },
+            is_closed: true,
+        };
+        let (sender, mut events) = broadcast::channel(2);
+        // More closes than the channel holds, all from the previous cycle
+        for _ in 0..5 {
+            sender.send(closed()).unwrap();
+        }
+
+        let waited = tokio::time::timeout(Duration::from_millis(50), wait_for_closed_candle(&mut events, "1h")).await;
+        assert!(waited.is_err(), "backlog must not wake the loop");
+
+        let wait = tokio::time::timeout(Duration::from_secs(1), wait_for_closed_candle(&mut events, "1h"));
+        let send = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            sender.send(closed()).unwrap();
+        };
+        let (waited, _) = tokio::join!(wait, send);
+        assert!(waited.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_apply_order_update_partial_fill_and_cancel() {
+        let ctx = setup_bot(false).await;
+        ctx.strategy.set_buy_signal().await;
+        ctx.bot.process_cycle("BTC/USDT", "1h").await.unwrap();
+
+        let trade = ctx.repository.get_open_trades().await.unwrap().remove(0);
+        let mut order = ctx.exchange.orders.read().await[0].clone();
+
+        // Exchange reports only 0.4 filled at 99
+        order.status = OrderStatus::PartiallyFilled;
+        order.filled = Decimal::new(4, 1);
+        order.remaining = order.amount - order.filled;
+        order.price = Some(Decimal::from(99));
+        ctx.bot.apply_order_update(&order).await.unwrap();
+
+        let updated = ctx.repository.get_trade(trade.id).await.unwrap().unwrap();
+        assert_eq!(updated.amount, Decimal::new(4, 1));
+        assert_eq!(updated.open_rate, Decimal::from(99));
+        // Only the filled part ties up stake, not the requested 100
+        assert_eq!(trade.stake_amount, Decimal::from(100));
+        assert_eq!(updated.stake_amount, Decimal::new(396, 1));
+
+        // An unfilled buy that gets canceled removes its trade
+        order.id = "unknown".to_string();
+        order.status = OrderStatus::Canceled;
+        order.filled = Decimal::ZERO;
+        ctx.bot.apply_order_update(&order).await.unwrap();
+        assert!(ctx.repository.get_trade(trade.id).await.unwrap().is_some());
+
+        let mut linked = ctx.exchange.orders.read().await[0].clone();
+        linked.status = OrderStatus::Canceled;
+        linked.filled = Decimal::ZERO;
+        ctx.bot.apply_order_update(&linked).await.unwrap();
+        assert!(ctx.repository.get_trade(trade.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_liquidation_price_from_position() {
+        let mut ctx = setup_bot(false).await;
+        ctx.bot = TradingBot::new(
+            ctx.exchange.clone(),
+            ctx.strategy.clone(),
+            ctx.repository.clone(),
+            None,
+            futures_config(),
+        );
+        let position = |liquidation_price: i64| Position {
+            symbol: "BTC/USDT".to_string(),
+            side: TradeSide::Sell,
+            size: Decimal::ONE,
+            entry_price: Decimal::from(100),
+            mark_price: Decimal::from(100),
+            unrealized_pnl: Decimal::ZERO,
+            percentage: Decimal::ZERO,
+            leverage: Decimal::from(3),
+            liquidation_price: Some(Decimal::from(liquidation_price)),
+            margin_mode: Some(MarginMode::Cross),
+        };
+
+        // The short's liquidation price is taken from the position once the entry fills
+        *ctx.exchange.positions.write().await = vec![position(132)];
+        ctx.strategy.set_sell_signal().await;
+        ctx.bot.process_cycle("BTC/USDT", "1h").await.unwrap();
+        let trade = ctx.repository.get_open_trades().await.unwrap().remove(0);
+        assert_eq!(trade.liquidation_price, Some(Decimal::from(132)));
+
+        // A partial fill changes the position and with it the liquidation price
+        *ctx.exchange.positions.write().await = vec![position(140)];
+        let mut order = ctx.exchange.orders.read().await[0].clone();
+        order.status = OrderStatus::PartiallyFilled;
+        order.filled = Decimal::ONE;
+        ctx.bot.apply_order_update(&order).await.unwrap();
+        let updated = ctx.repository.get_trade(trade.id).await.unwrap().unwrap();
+        assert_eq!(updated.liquidation_price, Some(Decimal::from(140)));
+    }
+
+    #[tokio::test]
+    async fn test_process_cycle_emits_signal_and_entry_fill() {
+        use crate::bot::events::BotEvent;
+
+        let ctx = setup_bot(true).await;
+        let mut events = ctx.bot.subscribe();
+        ctx.strategy.set_buy_signal().await;
+        ctx.bot.process_cycle("BTC/USDT", "1h").await.unwrap();
+
+        match events.try_recv().unwrap() {
+            BotEvent::Signal { pair, side, count } => {
+                assert_eq!(pair, "BTC/USDT");
+                assert_eq!(side, TradeSide::Buy);
+                assert_eq!(count, 1);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match events.try_recv().unwrap() {
+            BotEvent::EntryFill { trade } => assert_eq!(trade.pair, "BTC/USDT"),
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        ctx.bot.stop().await.unwrap();
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            BotEvent::Status {
+                status: BotStatus::Stopped,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_enter_trade_records_okx_market_fill() {
+        use crate::exchange::okx::OkxExchange;
+        use mockito::{Matcher, Server};
+        use std::str::FromStr;
+
+        let mut server = Server::new_async().await;
+        let _instruments = server
+            .mock("GET", "/api/v5/public/instruments")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [{
+                    "instType": "SPOT", "instId": "BTC-USDT", "baseCcy": "BTC", "quoteCcy": "USDT", "state": "live",
+                    "lotSz": "0.00000001", "tickSz": "0.1", "minSz": "0.00001", "maxLmtSz": "9999999999"
+                }]}"#,
+            )
+            .create_async()
+            .await;
+        let _balance = server
+            .mock("GET", "/api/v5/account/balance")
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [{"totalEq": "1000", "details": [
+                    {"ccy": "USDT", "cashBal": "1000", "availBal": "1000", "frozenBal": "0"}
+                ]}]}"#,
+            )
+            .create_async()
+            .await;
+        let _create = server
+            .mock("POST", "/api/v5/trade/order")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "instId": "BTC-USDT",
+                "side": "buy",
+                "ordType": "market",
+            })))
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [{"ordId": "42", "clOrdId": "", "sCode": "0", "sMsg": ""}]}"#,
+            )
+            .create_async()
+            .await;
+        // The acknowledgement carries no fill; the order is read back for it
+        let fetch = server
+            .mock("GET", "/api/v5/trade/order")
+            .match_query(Matcher::UrlEncoded("ordId".into(), "42".into()))
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [{
+                    "instId": "BTC-USDT", "ordId": "42", "side": "buy", "ordType": "market",
+                    "px": "", "sz": "0.95238095", "accFillSz": "0.95238095", "avgPx": "104.9", "fee": "-0.00095238",
+                    "state": "filled", "cTime": "1700000000000", "uTime": "1700000000100"
+                }]}"#,
+            )
+            .create_async()
+            .await;
+
+        let ctx = setup_bot(false).await;
+        let exchange = Arc::new(
+            OkxExchange::new("key".to_string(), "secret".to_string(), "passphrase".to_string())
+                .with_base_url(server.url()),
+        );
+        let bot = TradingBot::new(
+            exchange,
+            ctx.strategy.clone(),
+            ctx.repository.clone(),
+            None,
+            BotConfig {
+                max_open_trades: 3,
+                stake_amount: 100.0,
+                ..Default::default()
+            },
+        );
+        let klines = ctx.exchange.fetch_ohlcv("BTC/USDT", "1h", 1).await.unwrap();
+        bot.enter_trade("BTC/USDT", false, &klines, &[]).await.unwrap();
+
+        fetch.assert_async().await;
+        let trade = ctx.repository.get_open_trades().await.unwrap().remove(0);
+        assert_eq!(trade.exchange, "okx");
+        assert_eq!(trade.amount, Decimal::from_str("0.95238095").unwrap());
+        assert_eq!(trade.open_rate, Decimal::from_str("104.9").unwrap());
+    }
 }
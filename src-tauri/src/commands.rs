@@ -1,102 +1,26 @@
-use crate::bot::TradingBot;
+use crate::backtest::BacktestRequest;
+use crate::backtest::export::{self, ExportFormat};
+use crate::backtest::monte_carlo::{self, MonteCarloConfig, MonteCarloResult};
 use crate::error::Result;
 use crate::types::*;
-use crate::{exchange, persistence, risk, strategy};
-use freqtrade_rs_lib::backtest::BacktestConfig;
-use std::sync::Arc;
+use crate::{data, exchange};
 use tauri::State;
 
-pub struct AppState {
-    pub config: Arc<tokio::sync::RwLock<crate::config::AppConfig>>,
-    pub repository: Arc<persistence::Repository>,
-    pub bot: Arc<tokio::sync::Mutex<Option<TradingBot>>>,
-    pub risk_manager: Arc<tokio::sync::RwLock<Option<Arc<risk::RiskManager>>>>,
-}
+pub use crate::api::AppState;
 
 #[tauri::command]
 pub async fn get_bot_status(state: State<'_, AppState>) -> Result<BotStatus> {
-    let bot = state.bot.lock().await;
-    if let Some(bot) = bot.as_ref() {
-        Ok(bot.get_status().await)
-    } else {
-        Ok(BotStatus::Stopped)
-    }
+    Ok(state.bot_status().await)
 }
 
 #[tauri::command]
 pub async fn start_bot(state: State<'_, AppState>) -> Result<String> {
-    let mut bot_guard = state.bot.lock().await;
-
-    if bot_guard.is_some() {
-        return Err(crate::error::AppError::Bot("Bot is already running".to_string()));
-    }
-
-    let config = state.config.read().await;
-    let exchange = Arc::new(exchange::binance::BinanceExchange::new(
-        config.exchange.key.clone(),
-        config.exchange.secret.clone(),
-    ));
-
-    let strategy = Arc::new(SimpleStrategy::new());
-
-    // 创建风险管理器（可选）
-    let risk_manager = Arc::new(risk::RiskManager::new(state.repository.clone()));
-
-    // 添加默认的保护机制
-    // 冷却期保护：2次亏损后停止1小时
-    risk_manager
-        .add_protection(Box::new(risk::CooldownPeriod::new(risk::CooldownPeriodConfig {
-            stop_duration: 60,
-            lookback_period: 1440,
-            stop_after_losses: 2,
-        })))
-        .await?;
-
-    // 最大回撤保护：回撤超过20%时停止
-    risk_manager
-        .add_protection(Box::new(risk::MaxDrawdownProtection::new(
-            risk::MaxDrawdownProtectionConfig {
-                max_allowed_drawdown: 20.0,
-                lookback_period: 1440,
-                stop_duration: 60,
-            },
-        )))
-        .await?;
-
-    let bot = TradingBot::new(
-        exchange,
-        strategy,
-        state.repository.clone(),
-        Some(risk_manager.clone()),
-        config.bot.clone(),
-    );
-
-    let bot_arc = Arc::new(bot.clone());
-    *bot_guard = Some(bot);
-
-    // 保存 risk_manager 到 state
-    let mut risk_mgr_guard = state.risk_manager.write().await;
-    *risk_mgr_guard = Some(risk_manager);
-    drop(risk_mgr_guard);
-
-    drop(bot_guard);
-
-    tokio::spawn(async move {
-        let _ = bot_arc.start().await;
-    });
-
-    Ok("Bot started with risk management".to_string())
+    state.start_bot().await
 }
 
 #[tauri::command]
 pub async fn stop_bot(state: State<'_, AppState>) -> Result<String> {
-    let bot_guard = state.bot.lock().await;
-    if let Some(bot) = bot_guard.as_ref() {
-        bot.stop().await?;
-        Ok("Bot stopped".to_string())
-    } else {
-        Err(crate::error::AppError::Bot("Bot is not running".to_string()))
-    }
+    state.stop_bot().await
 }
 
 #[tauri::command]
@@ -110,100 +34,69 @@ pub async fn get_all_trades(state: State<'_, AppState>) -> Result<Vec<Trade>> {
 }
 
 #[tauri::command]
-pub async fn run_backtest(_config: BacktestConfig) -> Result<BacktestResult> {
-    Ok(BacktestResult {
-        strategy: "TestStrategy".to_string(),
-        pair: "BTCUSDT".to_string(),
-        timeframe: Timeframe::OneHour,
-        start_date: chrono::Utc::now(),
-        end_date: chrono::Utc::now(),
-        total_trades: 0,
-        winning_trades: 0,
-        losing_trades: 0,
-        win_rate: 0.0,
-        total_profit: rust_decimal::Decimal::ZERO,
-        max_drawdown: 0.0,
-        sharpe_ratio: 0.0,
-        profit_factor: 0.0,
-        avg_profit: rust_decimal::Decimal::ZERO,
-        avg_loss: rust_decimal::Decimal::ZERO,
-        trades: vec![],
-    })
-}
-
-struct SimpleStrategy;
-
-impl SimpleStrategy {
-    fn new() -> Self {
-        Self
-    }
+pub async fn run_backtest(state: State<'_, AppState>, request: BacktestRequest) -> Result<BacktestResult> {
+    state.run_backtest(request).await
 }
 
-#[async_trait::async_trait]
-impl strategy::Strategy for SimpleStrategy {
-    fn name(&self) -> &str {
-        "SimpleStrategy"
-    }
-
-    fn timeframes(&self) -> &[Timeframe] {
-        &[Timeframe::OneHour]
-    }
-
-    async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
-        Ok(())
-    }
-
-    async fn populate_buy_trend(&self, _data: &[OHLCV]) -> Result<Vec<Signal>> {
-        Ok(vec![])
-    }
-
-    async fn populate_sell_trend(&self, _data: &[OHLCV]) -> Result<Vec<Signal>> {
-        Ok(vec![])
-    }
+#[tauri::command]
+pub async fn get_backtest_results(state: State<'_, AppState>) -> Result<Vec<BacktestSummary>> {
+    state.repository.get_backtest_results().await
 }
 
 #[tauri::command]
-pub async fn get_dashboard_stats(state: State<'_, AppState>) -> Result<DashboardStats> {
+pub async fn get_backtest_result(state: State<'_, AppState>, id: i64) -> Result<BacktestResult> {
     state
         .repository
-        .get_dashboard_stats()
-        .await
-        .map_err(|e| crate::error::AppError::Database(format!("Failed to get dashboard stats: {}", e)))
+        .get_backtest_result(id)
+        .await?
+        .ok_or_else(|| crate::error::AppError::NotFound(format!("Backtest result {} not found", id)))
 }
 
 #[tauri::command]
-pub async fn get_equity_curve(state: State<'_, AppState>, timeframe: String) -> Result<Vec<EquityPoint>> {
-    let _trades = state
-        .repository
-        .get_all_trades()
-        .await
-        .map_err(|e| format!("Failed to get trades: {}", e))?;
-
-    // Simplified: Generate mock curve data based on time
-    let days_to_show = match timeframe.as_str() {
-        "1d" => 1,
-        "1w" => 7,
-        _ => 1,
-    };
-
-    let mut equity = 10000.0_f64;
-    let mut points = Vec::new();
-
-    let start_date = chrono::Utc::now() - chrono::Duration::days(days_to_show as i64);
+pub async fn get_backtest_report(state: State<'_, AppState>, id: i64) -> Result<BacktestReport> {
+    let result = get_backtest_result(state, id).await?;
+    Ok(crate::backtest::report::build_report(&result))
+}
 
-    for i in 0..days_to_show {
-        let time = (start_date + chrono::Duration::days(i as i64))
-            .format("%H:%M")
-            .to_string();
+#[tauri::command]
+pub async fn run_monte_carlo(
+    state: State<'_, AppState>,
+    id: i64,
+    config: MonteCarloConfig,
+) -> Result<MonteCarloResult> {
+    let result = get_backtest_result(state, id).await?;
+    monte_carlo::run_monte_carlo(&result, &config)
+}
 
-        // Simulate fluctuation (can be replaced with real data from DB later)
-        let change = (i as f64 * 0.5 - 1.75) * 50.0;
-        equity += change;
+#[tauri::command]
+pub async fn export_backtest_result(
+    state: State<'_, AppState>,
+    id: i64,
+    formats: Vec<ExportFormat>,
+) -> Result<Vec<String>> {
+    let dir = export::default_export_dir(&state.config.read().await.database.path);
+    let result = get_backtest_result(state, id).await?;
+    let files = export::export_result(&result, &dir, &formats).await?;
+    Ok(files.into_iter().map(|f| f.display().to_string()).collect())
+}
 
-        points.push(EquityPoint { time, value: equity });
+#[tauri::command]
+pub async fn import_backtest_results(state: State<'_, AppState>, path: String) -> Result<Vec<i64>> {
+    let mut ids = Vec::new();
+    for result in export::import_results(std::path::Path::new(&path)).await? {
+        ids.push(state.repository.save_backtest_result(&result).await?);
     }
+    Ok(ids)
+}
 
-    Ok(points)
+#[tauri::command]
+pub async fn get_dashboard_stats(state: State<'_, AppState>) -> Result<DashboardStats> {
+    state.dashboard_stats().await
+}
+
+#[tauri::command]
+pub async fn get_equity_curve(state: State<'_, AppState>, timeframe: String) -> Result<Vec<EquityPoint>> {
+    state.equity_curve(&timeframe).await
 }
 
 #[tauri::command]
@@ -214,16 +107,37 @@ pub async fn get_config(state: State<'_, AppState>) -> Result<crate::config::App
 
 #[tauri::command]
 pub async fn update_config(state: State<'_, AppState>, config: crate::config::AppConfig) -> Result<()> {
-    let mut state_config = state.config.write().await;
-    *state_config = config.clone();
+    state.update_config(config).await
+}
 
-    // Save to file
-    let config_path = "config/default.toml";
-    let toml_str = toml::to_string_pretty(&config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+#[tauri::command]
+pub async fn download_data(
+    state: State<'_, AppState>,
+    pairs: Vec<String>,
+    timeframes: Vec<String>,
+    timerange: String,
+) -> Result<Vec<DataDownload>> {
+    let config = state.config.read().await.clone();
+    let exchange = exchange::ExchangeRegistry::default()
+        .create(&exchange::ExchangeContext {
+            exchange: config.exchange,
+            bot: config.bot,
+            repository: Some(state.repository.clone()),
+        })
+        .await?;
 
-    tokio::fs::write(config_path, toml_str)
+    // 只下载数据库中缺失的区间
+    let request = data::downloader::DownloadRequest {
+        pairs,
+        timeframes,
+        timerange: data::downloader::TimeRange::parse(&timerange)?,
+    };
+    data::downloader::DataDownloader::new(exchange, state.repository.clone())
+        .download(&request)
         .await
-        .map_err(|e| format!("Failed to save config: {}", e))?;
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn get_data_downloads(state: State<'_, AppState>) -> Result<Vec<DataDownload>> {
+    state.repository.get_data_downloads().await
 }
@@ -1,5 +1,5 @@
 use crate::error::{AppError, Result};
-use crate::types::Timeframe;
+use crate::types::{MarginMode, Timeframe};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use tokio::fs;
@@ -16,12 +16,24 @@ pub struct BotConfig {
     pub trading_pairs: Vec<String>,
     #[serde(default = "default_timeframe")]
     pub timeframe: String,
+    /// Leverage set on every pair at startup; above 1 needs a futures exchange
+    #[serde(default = "default_leverage")]
+    pub leverage: u32,
+    #[serde(default)]
+    pub margin_mode: MarginMode,
+    /// Open a short on a sell signal when the pair has no open trade; needs a futures exchange
+    #[serde(default)]
+    pub can_short: bool,
 }
 
 fn default_timeframe() -> String {
     "1h".to_string()
 }
 
+fn default_leverage() -> u32 {
+    1
+}
+
 impl Default for BotConfig {
     fn default() -> Self {
         Self {
@@ -33,6 +45,9 @@ impl Default for BotConfig {
             process_only_new_candles: true,
             trading_pairs: vec![],
             timeframe: default_timeframe(),
+            leverage: default_leverage(),
+            margin_mode: MarginMode::default(),
+            can_short: false,
         }
     }
 }
@@ -42,7 +57,13 @@ pub struct ExchangeConfig {
     pub name: String,
     pub key: String,
     pub secret: String,
+    /// API passphrase, required by exchanges such as OKX
+    #[serde(default)]
+    pub password: Option<String>,
     pub enable_rate_limit: bool,
+    /// Override the REST endpoint, e.g. "https://testnet.binance.vision" for the spot testnet
+    #[serde(default)]
+    pub base_url: Option<String>,
 }
 
 impl Default for ExchangeConfig {
@@ -51,7 +72,9 @@ impl Default for ExchangeConfig {
             name: "binance".to_string(),
             key: String::new(),
             secret: String::new(),
+            password: None,
             enable_rate_limit: true,
+            base_url: None,
         }
     }
 }
@@ -60,15 +83,20 @@ impl Default for ExchangeConfig {
 pub struct StrategyConfig {
     pub name: String,
     pub timeframe: Timeframe,
+    #[serde(default = "default_strategy_params")]
     pub params: serde_json::Value,
 }
 
+fn default_strategy_params() -> serde_json::Value {
+    serde_json::json!({})
+}
+
 impl Default for StrategyConfig {
     fn default() -> Self {
         Self {
             name: "SimpleStrategy".to_string(),
             timeframe: Timeframe::OneHour,
-            params: serde_json::json!({}),
+            params: default_strategy_params(),
         }
     }
 }
@@ -91,6 +119,14 @@ pub struct ApiServerConfig {
     pub enabled: bool,
     pub listen_ip: String,
     pub listen_port: u16,
+    /// HTTP basic auth credentials; the server refuses to start without a password
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// Key for signing access tokens; a random key is used when empty, so tokens do not survive restarts
+    #[serde(default)]
+    pub jwt_secret_key: String,
 }
 
 impl Default for ApiServerConfig {
@@ -99,6 +135,9 @@ impl Default for ApiServerConfig {
             enabled: true,
             listen_ip: "127.0.0.1".to_string(),
             listen_port: 8080,
+            username: String::new(),
+            password: String::new(),
+            jwt_secret_key: String::new(),
         }
     }
 }
@@ -132,6 +171,31 @@ pub struct AppConfig {
     pub log: LogConfig,
 }
 
+/// Placeholder shown instead of credentials
+pub const REDACTED: &str = "REDACTED";
+
+impl AppConfig {
+    /// Copy with the exchange credentials and API server secrets replaced by `REDACTED`, for
+    /// display; unset values stay empty
+    pub fn redacted(&self) -> AppConfig {
+        let mut config = self.clone();
+        for secret in [
+            &mut config.exchange.key,
+            &mut config.exchange.secret,
+            &mut config.api_server.password,
+            &mut config.api_server.jwt_secret_key,
+        ] {
+            if !secret.is_empty() {
+                *secret = REDACTED.to_string();
+            }
+        }
+        if config.exchange.password.is_some() {
+            config.exchange.password = Some(REDACTED.to_string());
+        }
+        config
+    }
+}
+
 pub struct ConfigManager {
     config: AppConfig,
 }
@@ -155,6 +219,9 @@ impl ConfigManager {
         if let Ok(secret) = std::env::var("EXCHANGE_API_SECRET") {
             self.config.exchange.secret = secret;
         }
+        if let Ok(password) = std::env::var("EXCHANGE_API_PASSWORD") {
+            self.config.exchange.password = Some(password);
+        }
         if let Ok(db_path) = std::env::var("DATABASE_PATH") {
             self.config.database.path = db_path;
         }
@@ -194,6 +261,24 @@ impl ConfigManager {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_redacted_masks_all_credentials() {
+        let mut config = AppConfig::default();
+        config.exchange.key = "key".to_string();
+        config.exchange.secret = "secret".to_string();
+        config.exchange.password = Some("passphrase".to_string());
+        config.api_server.password = "api".to_string();
+        config.api_server.jwt_secret_key = "jwt".to_string();
+
+        let redacted = config.redacted();
+        assert_eq!(redacted.exchange.key, REDACTED);
+        assert_eq!(redacted.exchange.secret, REDACTED);
+        assert_eq!(redacted.exchange.password.as_deref(), Some(REDACTED));
+        assert_eq!(redacted.api_server.password, REDACTED);
+        assert_eq!(redacted.api_server.jwt_secret_key, REDACTED);
+        assert_eq!(redacted.api_server.username, config.api_server.username);
+    }
+
     #[tokio::test]
     async fn test_default_config() {
         let config = AppConfig::default();
@@ -217,6 +302,7 @@ mod tests {
         name = "binance"
         key = "test_key"
         secret = "test_secret"
+        password = "test_passphrase"
         enable_rate_limit = true
         "#;
 
@@ -226,6 +312,14 @@ mod tests {
         assert_eq!(config.bot.stake_amount, 200.0);
         assert_eq!(config.exchange.key, "test_key");
         assert_eq!(config.exchange.secret, "test_secret");
+        assert_eq!(config.exchange.password.as_deref(), Some("test_passphrase"));
+    }
+
+    #[tokio::test]
+    async fn test_load_shipped_default_config() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../config/default.toml");
+        let manager = ConfigManager::load_from_file(path).await.unwrap();
+        assert_eq!(manager.config().strategy.timeframe, Timeframe::OneHour);
     }
 
     #[tokio::test]
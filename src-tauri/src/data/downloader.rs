@@ -0,0 +1,292 @@
+use crate::data::history::download_ohlcv;
+use crate::error::{AppError, Result};
+use crate::exchange::Exchange;
+use crate::persistence::Repository;
+use crate::persistence::repository::parse_timeframe_to_duration;
+use crate::types::DataDownload;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Time window in freqtrade's `--timerange` notation, e.g. "20240101-20240301" or "20240101-"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: DateTime<Utc>,
+    /// Exclusive end; open-ended ranges run until now
+    pub end: DateTime<Utc>,
+}
+
+impl TimeRange {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Self> {
+        if start >= end {
+            return Err(AppError::InvalidInput(format!(
+                "Timerange start {} is not before end {}",
+                start, end
+            )));
+        }
+        Ok(Self { start, end })
+    }
+
+    /// Parse "YYYYMMDD-YYYYMMDD"; the end may be omitted and then defaults to now
+    pub fn parse(timerange: &str) -> Result<Self> {
+        let (start, end) = timerange
+            .split_once('-')
+            .ok_or_else(|| AppError::InvalidInput(format!("Invalid timerange: {}", timerange)))?;
+        if start.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Timerange {} needs a start date",
+                timerange
+            )));
+        }
+
+        let start = parse_date(start)?;
+        let end = if end.is_empty() { Utc::now() } else { parse_date(end)? };
+        Self::new(start, end)
+    }
+
+    /// The last `days` days up to now
+    pub fn last_days(days: i64) -> Result<Self> {
+        let end = Utc::now();
+        Self::new(end - Duration::days(days), end)
+    }
+}
+
+fn parse_date(date: &str) -> Result<DateTime<Utc>> {
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+        .map_err(|e| AppError::InvalidInput(format!("Invalid date {} in timerange: {}", date, e)))
+}
+
+/// What to download: every pair in every timeframe over one timerange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadRequest {
+    pub pairs: Vec<String>,
+    pub timeframes: Vec<String>,
+    pub timerange: TimeRange,
+}
+
+/// Fills the `klines` table from an exchange.
+///
+/// Only the parts of the requested range that are not stored yet are fetched: candles before
+/// the oldest stored one and from the newest stored one onwards (which refreshes a candle that
+/// was still open when it was saved). Gaps in the middle of stored data are not detected.
+/// Every pair/timeframe run is recorded in `data_downloads`.
+pub struct DataDownloader {
+    exchange: Arc<dyn Exchange>,
+    repository: Arc<Repository>,
+}
+
+impl DataDownloader {
+    pub fn new(exchange: Arc<dyn Exchange>, repository: Arc<Repository>) -> Self {
+        Self { exchange, repository }
+    }
+
+    pub async fn download(&self, request: &DownloadRequest) -> Result<Vec<DataDownload>> {
+        let mut runs = Vec::new();
+        for pair in &request.pairs {
+            for timeframe in &request.timeframes {
+                runs.push(self.download_pair(pair, timeframe, request.timerange).await?);
+            }
+        }
+        Ok(runs)
+    }
+
+    async fn download_pair(&self, pair: &str, timeframe: &str, timerange: TimeRange) -> Result<DataDownload> {
+        let candle = parse_timeframe_to_duration(timeframe)?;
+        let stored = self.repository.get_kline_range(pair, timeframe).await?;
+
+        let mut candles_count = 0;
+        for (start, end) in missing_ranges(timerange, stored, candle) {
+            eprintln!("Downloading {} {} from {} to {}", pair, timeframe, start, end);
+            candles_count +=
+                download_ohlcv(self.exchange.as_ref(), &self.repository, pair, timeframe, start, end).await?;
+        }
+
+        let run = DataDownload {
+            pair: pair.to_string(),
+            timeframe: timeframe.to_string(),
+            start_date: timerange.start,
+            end_date: timerange.end,
+            candles_count,
+            download_time: Utc::now(),
+        };
+        self.repository.save_data_download(&run).await?;
+        Ok(run)
+    }
+}
+
+/// Parts of `timerange` not covered by the stored `(first, last)` candle open times
+fn missing_ranges(
+    timerange: TimeRange,
+    stored: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    candle: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let Some((first, last)) = stored else {
+        return vec![(timerange.start, timerange.end)];
+    };
+
+    let mut ranges = Vec::new();
+    if timerange.start < first {
+        ranges.push((timerange.start, first.min(timerange.end)));
+    }
+    if last + candle < timerange.end {
+        ranges.push((last.max(timerange.start), timerange.end));
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::market::Market;
+    use crate::types::*;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+    use std::sync::Mutex;
+
+    /// Serves one hourly candle per hour of any requested range and records the requests
+    #[derive(Default)]
+    struct HourlyFeed {
+        requests: Mutex<Vec<(DateTime<Utc>, DateTime<Utc>)>>,
+    }
+
+    #[async_trait]
+    impl Exchange for HourlyFeed {
+        async fn fetch_ticker(&self, _symbol: &str) -> Result<Ticker> {
+            Err(AppError::NotImplemented("fetch_ticker".to_string()))
+        }
+
+        async fn fetch_ohlcv(&self, _symbol: &str, _timeframe: &str, _limit: usize) -> Result<Vec<OHLCV>> {
+            Ok(vec![])
+        }
+
+        async fn fetch_ohlcv_range(
+            &self,
+            _symbol: &str,
+            _timeframe: &str,
+            since: DateTime<Utc>,
+            until: DateTime<Utc>,
+        ) -> Result<Vec<OHLCV>> {
+            self.requests.lock().unwrap().push((since, until));
+            let mut klines = Vec::new();
+            let mut time = since;
+            while time < until {
+                klines.push(OHLCV {
+                    timestamp: time,
+                    open: Decimal::ONE,
+                    high: Decimal::ONE,
+                    low: Decimal::ONE,
+                    close: Decimal::ONE,
+                    volume: Decimal::ONE,
+                });
+                time += Duration::hours(1);
+            }
+            Ok(klines)
+        }
+
+        async fn fetch_balance(&self) -> Result<Balance> {
+            Err(AppError::NotImplemented("fetch_balance".to_string()))
+        }
+
+        async fn fetch_balances(&self) -> Result<Vec<Balance>> {
+            Ok(vec![])
+        }
+
+        async fn fetch_positions(&self) -> Result<Vec<Position>> {
+            Ok(vec![])
+        }
+
+        async fn create_order(&self, _order: OrderRequest) -> Result<Order> {
+            Err(AppError::NotImplemented("create_order".to_string()))
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn fetch_order(&self, _order_id: &str) -> Result<Order> {
+            Err(AppError::NotImplemented("fetch_order".to_string()))
+        }
+
+        async fn fetch_orders(&self, _symbol: &str) -> Result<Vec<Order>> {
+            Ok(vec![])
+        }
+
+        fn get_name(&self) -> &str {
+            "hourly"
+        }
+
+        async fn fetch_markets(&self) -> Result<Vec<Market>> {
+            Ok(vec![])
+        }
+    }
+
+    fn hour(h: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(h)
+    }
+
+    #[test]
+    fn test_parse_timerange() {
+        let range = TimeRange::parse("20240101-20240301").unwrap();
+        assert_eq!(range.start, hour(0));
+        assert_eq!(range.end, Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+
+        let open_ended = TimeRange::parse("20240101-").unwrap();
+        assert!(open_ended.end > open_ended.start);
+
+        assert!(TimeRange::parse("-20240301").is_err());
+        assert!(TimeRange::parse("20240301-20240101").is_err());
+        assert!(TimeRange::parse("2024-01-01").is_err());
+    }
+
+    #[test]
+    fn test_missing_ranges() {
+        let range = TimeRange::new(hour(0), hour(100)).unwrap();
+        let candle = Duration::hours(1);
+
+        assert_eq!(missing_ranges(range, None, candle), vec![(hour(0), hour(100))]);
+        assert_eq!(
+            missing_ranges(range, Some((hour(10), hour(49))), candle),
+            vec![(hour(0), hour(10)), (hour(49), hour(100))]
+        );
+        assert!(missing_ranges(range, Some((hour(0), hour(99))), candle).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_download_fetches_only_missing_candles() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Arc::new(Repository::new(dir.path().join("download.db")).await.unwrap());
+        let feed = Arc::new(HourlyFeed::default());
+        let downloader = DataDownloader::new(feed.clone(), repository.clone());
+
+        let first = DownloadRequest {
+            pairs: vec!["BTC/USDT".to_string()],
+            timeframes: vec!["1h".to_string()],
+            timerange: TimeRange::new(hour(10), hour(20)).unwrap(),
+        };
+        let runs = downloader.download(&first).await.unwrap();
+        assert_eq!(runs[0].candles_count, 10);
+
+        // 扩大范围后只补齐头尾，并重新获取最后一根可能未收盘的K线
+        let second = DownloadRequest {
+            timerange: TimeRange::new(hour(0), hour(30)).unwrap(),
+            ..first
+        };
+        let runs = downloader.download(&second).await.unwrap();
+        assert_eq!(runs[0].candles_count, 10 + 11);
+        assert_eq!(
+            feed.requests.lock().unwrap()[1..],
+            [(hour(0), hour(10)), (hour(19), hour(30))]
+        );
+
+        assert_eq!(
+            repository.get_kline_range("BTC/USDT", "1h").await.unwrap(),
+            Some((hour(0), hour(29)))
+        );
+        let history = repository.get_data_downloads().await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].start_date, hour(0));
+        assert_eq!(history[0].candles_count, 21);
+    }
+}
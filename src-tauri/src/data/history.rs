@@ -0,0 +1,110 @@
+use crate::error::Result;
+use crate::exchange::Exchange;
+use crate::persistence::Repository;
+use crate::persistence::repository::parse_timeframe_to_duration;
+use chrono::{DateTime, Utc};
+
+/// Number of candles requested from the exchange per saved chunk
+pub const DOWNLOAD_CHUNK_CANDLES: i32 = 1000;
+
+/// Download candles with open times in `[since, until)` and store them.
+///
+/// The range is fetched in chunks of [`DOWNLOAD_CHUNK_CANDLES`] and every chunk is written
+/// with `Repository::save_klines` as soon as it arrives, so long ranges never sit in memory
+/// and an interrupted download keeps what it already fetched. Returns the number of
+/// candles saved.
+pub async fn download_ohlcv(
+    exchange: &dyn Exchange,
+    repository: &Repository,
+    symbol: &str,
+    timeframe: &str,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<usize> {
+    let chunk = parse_timeframe_to_duration(timeframe)? * DOWNLOAD_CHUNK_CANDLES;
+    let mut start = since;
+    let mut saved = 0;
+
+    while start < until {
+        let end = (start + chunk).min(until);
+        let klines = exchange.fetch_ohlcv_range(symbol, timeframe, start, end).await?;
+        repository.save_klines(symbol, timeframe, &klines).await?;
+        saved += klines.len();
+        start = end;
+    }
+
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::binance::BinanceExchange;
+    use chrono::{Duration, TimeZone};
+    use mockito::{Matcher, Server};
+
+    fn kline_page(start: DateTime<Utc>, count: i64) -> String {
+        let rows: Vec<String> = (0..count)
+            .map(|i| {
+                let open_time = (start + Duration::hours(i)).timestamp_millis();
+                format!(
+                    r#"[{}, "100.0", "101.0", "99.0", "100.5", "10.0", {}, "1005.0", 10, "5.0", "502.5", "0"]"#,
+                    open_time,
+                    open_time + 3_599_999
+                )
+            })
+            .collect();
+        format!("[{}]", rows.join(","))
+    }
+
+    #[tokio::test]
+    async fn test_download_ohlcv_saves_every_chunk() {
+        let mut server = Server::new_async().await;
+        let since = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let middle = since + Duration::hours(1000);
+        let until = since + Duration::hours(1500);
+
+        let first = server
+            .mock("GET", "/api/v3/klines")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("symbol".into(), "BTCUSDT".into()),
+                Matcher::UrlEncoded("startTime".into(), since.timestamp_millis().to_string()),
+                Matcher::UrlEncoded("endTime".into(), (middle.timestamp_millis() - 1).to_string()),
+                Matcher::UrlEncoded("limit".into(), "1000".into()),
+            ]))
+            .with_status(200)
+            .with_body(kline_page(since, 1000))
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/api/v3/klines")
+            .match_query(Matcher::UrlEncoded(
+                "startTime".into(),
+                middle.timestamp_millis().to_string(),
+            ))
+            .with_status(200)
+            .with_body(kline_page(middle, 500))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Repository::new(dir.path().join("history.db")).await.unwrap();
+        let exchange = BinanceExchange::new("key".to_string(), "secret".to_string())
+            .with_base_url(server.url())
+            .with_rate_limit(std::time::Duration::ZERO);
+
+        // Pairs are given as on the command line and stored under that name
+        let saved = download_ohlcv(&exchange, &repository, "BTC/USDT", "1h", since, until)
+            .await
+            .unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+        assert_eq!(saved, 1500);
+        let stored = repository.get_klines("BTC/USDT", "1h", 2000).await.unwrap();
+        assert_eq!(stored.len(), 1500);
+        assert_eq!(stored.first().unwrap().timestamp, until - Duration::hours(1));
+    }
+}
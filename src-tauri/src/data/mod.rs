@@ -0,0 +1,2 @@
+pub mod downloader;
+pub mod history;
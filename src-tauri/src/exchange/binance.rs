@@ -1,13 +1,29 @@
+pub mod futures;
+pub mod market_stream;
+pub mod user_stream;
+
 use crate::error::AppError;
 use crate::error::Result;
 use crate::exchange::Exchange;
+use crate::exchange::market::{Market, find_market};
+use crate::persistence::repository::parse_timeframe_to_duration;
 use crate::types::*;
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use hmac::{Hmac, Mac};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+/// Maximum number of candles Binance returns per klines request
+const KLINES_PAGE_LIMIT: usize = 1000;
+/// Default spacing between paged history requests
+const DEFAULT_REQUEST_INTERVAL: Duration = Duration::from_millis(200);
+/// How often a request rejected with 429/418 is retried
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
 
 #[derive(Deserialize)]
 struct BinanceOrderResponse {
@@ -75,11 +91,65 @@ struct BalanceInfo {
     locked: String,
 }
 
+#[derive(Deserialize)]
+struct ExchangeInfo {
+    symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Deserialize)]
+struct SymbolInfo {
+    symbol: String,
+    status: String,
+    #[serde(rename = "baseAsset")]
+    base_asset: String,
+    #[serde(rename = "quoteAsset")]
+    quote_asset: String,
+    filters: Vec<serde_json::Value>,
+}
+
+impl SymbolInfo {
+    fn filter_value(&self, filter_type: &str, field: &str) -> Option<Decimal> {
+        self.filters
+            .iter()
+            .find(|f| f["filterType"] == filter_type)
+            .and_then(|f| f[field].as_str())
+            .and_then(|v| v.parse().ok())
+    }
+
+    fn into_market(self) -> Market {
+        let amount_step = self.filter_value("LOT_SIZE", "stepSize");
+        let price_tick = self.filter_value("PRICE_FILTER", "tickSize");
+        let min_amount = self.filter_value("LOT_SIZE", "minQty");
+        let max_amount = self.filter_value("LOT_SIZE", "maxQty");
+        let min_notional = self
+            .filter_value("MIN_NOTIONAL", "minNotional")
+            .or_else(|| self.filter_value("NOTIONAL", "minNotional"))
+            // USDⓈ-M futures name the field `notional`
+            .or_else(|| self.filter_value("MIN_NOTIONAL", "notional"));
+
+        Market {
+            symbol: format!("{}/{}", self.base_asset, self.quote_asset),
+            id: self.symbol,
+            base: self.base_asset,
+            quote: self.quote_asset,
+            active: self.status == "TRADING",
+            amount_step,
+            price_tick,
+            min_amount,
+            max_amount,
+            min_notional,
+        }
+    }
+}
+
 pub struct BinanceExchange {
     _api_key: String,
     _api_secret: String,
     base_url: String,
     client: reqwest::Client,
+    markets: RwLock<Option<HashMap<String, Market>>>,
+    request_interval: Duration,
+    last_request: Mutex<Option<Instant>>,
 }
 
 impl BinanceExchange {
@@ -89,10 +159,19 @@ impl BinanceExchange {
             _api_secret: api_secret,
             base_url: "https://api.binance.com".to_string(),
             client: reqwest::Client::new(),
+            markets: RwLock::new(None),
+            request_interval: DEFAULT_REQUEST_INTERVAL,
+            last_request: Mutex::new(None),
         }
     }
 
-    #[cfg(test)]
+    /// Minimum spacing between paged history requests; zero disables throttling
+    pub fn with_rate_limit(mut self, interval: Duration) -> Self {
+        self.request_interval = interval;
+        self
+    }
+
+    /// Point the client at another REST endpoint, e.g. the testnet
     pub fn with_base_url(mut self, url: String) -> Self {
         self.base_url = url;
         self
@@ -103,21 +182,117 @@ impl BinanceExchange {
     }
 
     fn sign_query(&self, query: String) -> String {
-        let timestamp = Utc::now().timestamp_millis();
-        let query_with_timestamp = if query.is_empty() {
-            format!("timestamp={}", timestamp)
-        } else {
-            format!("{}&timestamp={}", query, timestamp)
+        sign_query(&self._api_secret, query)
+    }
+
+    /// Wait until `request_interval` has passed since the previous throttled request
+    async fn throttle(&self) {
+        let mut last_request = self.last_request.lock().await;
+        if let Some(last) = *last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.request_interval {
+                tokio::time::sleep(self.request_interval - elapsed).await;
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    /// Throttled GET that backs off when Binance answers 429 (rate limit) or 418 (IP ban),
+    /// honouring the `Retry-After` header
+    async fn get_rate_limited(&self, url: &str) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            self.throttle().await;
+            let response = self.client.get(url).send().await?;
+            let status = response.status().as_u16();
+            if status != 429 && status != 418 {
+                return Ok(response);
+            }
+
+            attempt += 1;
+            if attempt > MAX_RATE_LIMIT_RETRIES {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(AppError::Exchange(format!(
+                    "Binance rate limit exceeded: {}",
+                    error_text
+                )));
+            }
+            let retry_after = response
+                .headers()
+                .get("Retry-After")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(attempt as u64);
+            eprintln!("Binance rate limit hit ({}), retrying in {}s", status, retry_after);
+            tokio::time::sleep(Duration::from_secs(retry_after)).await;
+        }
+    }
+
+    /// Round the order to the market's LOT_SIZE/PRICE_FILTER and reject it locally
+    /// if it would fail the notional filter. Unknown symbols are passed through.
+    async fn apply_market_filters(&self, order: OrderRequest) -> Result<OrderRequest> {
+        let markets = self.load_markets().await?;
+        let Some(market) = find_market(&markets, &order.symbol) else {
+            return Ok(order);
+        };
+
+        let reference_price = match order.price {
+            Some(price) => price,
+            None if market.min_notional.is_some() => self.fetch_ticker(&market.id).await?.price,
+            None => Decimal::ZERO,
         };
+        market.prepare_order(order, reference_price)
+    }
+}
+
+/// Append a timestamp and HMAC-SHA256 signature to a query string
+fn sign_query(api_secret: &str, query: String) -> String {
+    let timestamp = Utc::now().timestamp_millis();
+    let query_with_timestamp = if query.is_empty() {
+        format!("timestamp={}", timestamp)
+    } else {
+        format!("{}&timestamp={}", query, timestamp)
+    };
+
+    type HmacSha256 = Hmac<Sha256>;
+    let mut mac = HmacSha256::new_from_slice(api_secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(query_with_timestamp.as_bytes());
+    let result = mac.finalize();
+    let signature = hex::encode(result.into_bytes());
+
+    format!("{}&signature={}", query_with_timestamp, signature)
+}
 
-        type HmacSha256 = Hmac<Sha256>;
-        let mut mac = HmacSha256::new_from_slice(self._api_secret.as_bytes()).expect("HMAC can take key of any size");
-        mac.update(query_with_timestamp.as_bytes());
-        let result = mac.finalize();
-        let signature = hex::encode(result.into_bytes());
+/// Binance uses concatenated symbols ("BTCUSDT"); accept unified ones ("BTC/USDT") as well
+fn binance_symbol(symbol: &str) -> String {
+    symbol.replace('/', "")
+}
 
-        format!("{}&signature={}", query_with_timestamp, signature)
+/// Parse Binance kline arrays (spot and futures share the layout)
+fn parse_klines(data: Vec<serde_json::Value>) -> Vec<OHLCV> {
+    let mut klines = Vec::new();
+    for item in data {
+        if let (Some(open_time), Some(open), Some(high), Some(low), Some(close), Some(volume)) = (
+            item[0].as_i64(),
+            item[1].as_str(),
+            item[2].as_str(),
+            item[3].as_str(),
+            item[4].as_str(),
+            item[5].as_str(),
+        ) {
+            let timestamp = chrono::DateTime::from_timestamp(open_time / 1000, 0).unwrap_or_else(Utc::now);
+
+            klines.push(OHLCV {
+                timestamp,
+                open: open.parse().unwrap_or(Decimal::ZERO),
+                high: high.parse().unwrap_or(Decimal::ZERO),
+                low: low.parse().unwrap_or(Decimal::ZERO),
+                close: close.parse().unwrap_or(Decimal::ZERO),
+                volume: volume.parse().unwrap_or(Decimal::ZERO),
+            });
+        }
     }
+    klines
 }
 
 /// Helper function to parse Binance order status string to OrderStatus enum
@@ -147,8 +322,8 @@ fn parse_order_type(order_type: &str) -> OrderType {
     match order_type {
         "MARKET" => OrderType::Market,
         "LIMIT" => OrderType::Limit,
-        "STOP_LOSS_LIMIT" => OrderType::StopLimit,
-        "STOP_LOSS" => OrderType::StopMarket,
+        "STOP_LOSS_LIMIT" | "STOP" => OrderType::StopLimit,
+        "STOP_LOSS" | "STOP_MARKET" => OrderType::StopMarket,
         _ => OrderType::Market,
     }
 }
@@ -188,26 +363,47 @@ impl Exchange for BinanceExchange {
         let response = self.client.get(&url).send().await?;
         let data: Vec<serde_json::Value> = response.json().await?;
 
+        Ok(parse_klines(data))
+    }
+
+    async fn fetch_ohlcv_range(
+        &self,
+        symbol: &str,
+        timeframe: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<OHLCV>> {
+        let candle = parse_timeframe_to_duration(timeframe)?;
+        let symbol = binance_symbol(symbol);
+        let mut start = since;
         let mut klines = Vec::new();
-        for item in data {
-            if let (Some(open_time), Some(open), Some(high), Some(low), Some(close), Some(volume)) = (
-                item[0].as_i64(),
-                item[1].as_str(),
-                item[2].as_str(),
-                item[3].as_str(),
-                item[4].as_str(),
-                item[5].as_str(),
-            ) {
-                let timestamp = chrono::DateTime::from_timestamp(open_time / 1000, 0).unwrap_or_else(Utc::now);
-
-                klines.push(OHLCV {
-                    timestamp,
-                    open: open.parse().unwrap_or(Decimal::ZERO),
-                    high: high.parse().unwrap_or(Decimal::ZERO),
-                    low: low.parse().unwrap_or(Decimal::ZERO),
-                    close: close.parse().unwrap_or(Decimal::ZERO),
-                    volume: volume.parse().unwrap_or(Decimal::ZERO),
-                });
+
+        while start < until {
+            // endTime is inclusive, the requested range is not
+            let url = format!(
+                "{}/api/v3/klines?symbol={}&interval={}&startTime={}&endTime={}&limit={}",
+                self.get_base_url(),
+                symbol,
+                timeframe,
+                start.timestamp_millis(),
+                until.timestamp_millis() - 1,
+                KLINES_PAGE_LIMIT
+            );
+            let response = self.get_rate_limited(&url).await?;
+            if !response.status().is_success() {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(AppError::Exchange(format!("Failed to fetch klines: {}", error_text)));
+            }
+
+            let page = parse_klines(response.json().await?);
+            let Some(last) = page.last() else {
+                break;
+            };
+            start = last.timestamp + candle;
+            let exhausted = page.len() < KLINES_PAGE_LIMIT;
+            klines.extend(page);
+            if exhausted {
+                break;
             }
         }
 
@@ -215,6 +411,20 @@ impl Exchange for BinanceExchange {
     }
 
     async fn fetch_balance(&self) -> Result<Balance> {
+        // Find USDT balance
+        let balances = self.fetch_balances().await?;
+        Ok(balances
+            .into_iter()
+            .find(|b| b.currency == "USDT")
+            .unwrap_or_else(|| Balance {
+                currency: "USDT".to_string(),
+                total: Decimal::ZERO,
+                free: Decimal::ZERO,
+                used: Decimal::ZERO,
+            }))
+    }
+
+    async fn fetch_balances(&self) -> Result<Vec<Balance>> {
         let query = String::new();
         let signed_query = self.sign_query(query);
         let url = format!("{}/api/v3/account?{}", self.get_base_url(), signed_query);
@@ -233,96 +443,32 @@ impl Exchange for BinanceExchange {
 
         let data: BinanceAccountInfo = response.json().await?;
 
-        // Find USDT balance
-        for balance in &data.balances {
-            if balance.asset == "USDT" {
+        // The account lists every asset; keep only the ones actually held
+        Ok(data
+            .balances
+            .iter()
+            .map(|balance| {
                 let free = balance.free.parse().unwrap_or(Decimal::ZERO);
                 let locked = balance.locked.parse().unwrap_or(Decimal::ZERO);
-                let total = free + locked;
-                return Ok(Balance {
-                    currency: "USDT".to_string(),
-                    total,
+                Balance {
+                    currency: balance.asset.clone(),
+                    total: free + locked,
                     free,
                     used: locked,
-                });
-            }
-        }
-
-        Ok(Balance {
-            currency: "USDT".to_string(),
-            total: Decimal::ZERO,
-            free: Decimal::ZERO,
-            used: Decimal::ZERO,
-        })
+                }
+            })
+            .filter(|b| b.total > Decimal::ZERO)
+            .collect())
     }
 
     async fn fetch_positions(&self) -> Result<Vec<Position>> {
-        let query = String::new();
-        let signed_query = self.sign_query(query);
-        let url = format!("{}/api/v3/account?{}", self.get_base_url(), signed_query);
-
-        let response = self
-            .client
-            .get(&url)
-            .header("X-MBX-APIKEY", &self._api_key)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AppError::Exchange(format!("Binance error: {}", error_text)));
-        }
-
-        let data: serde_json::Value = response.json().await?;
-        let positions_json = &data["positions"];
-
-        let mut positions = Vec::new();
-        if let Some(positions_arr) = positions_json.as_array() {
-            for pos in positions_arr {
-                let symbol = pos["symbol"].as_str().unwrap_or("").to_string();
-                let notional = pos["notional"].as_str().unwrap_or("0").parse().unwrap_or(Decimal::ZERO);
-                let entry_price = pos["entryPrice"]
-                    .as_str()
-                    .unwrap_or("0")
-                    .parse()
-                    .unwrap_or(Decimal::ZERO);
-                let mark_price = pos["markPrice"]
-                    .as_str()
-                    .unwrap_or("0")
-                    .parse()
-                    .unwrap_or(Decimal::ZERO);
-
-                // Only include non-zero positions
-                if notional != Decimal::ZERO {
-                    let side = if notional > Decimal::ZERO {
-                        TradeSide::Buy
-                    } else {
-                        TradeSide::Sell
-                    };
-                    let size = notional.abs();
-                    let unrealized_pnl = pos["unrealizedProfit"]
-                        .as_str()
-                        .unwrap_or("0")
-                        .parse()
-                        .unwrap_or(Decimal::ZERO);
-
-                    positions.push(Position {
-                        symbol,
-                        side,
-                        size,
-                        entry_price,
-                        mark_price,
-                        unrealized_pnl,
-                        percentage: Decimal::ZERO,
-                    });
-                }
-            }
-        }
-
-        Ok(positions)
+        // Spot accounts hold balances, not positions; see `futures::BinanceFuturesExchange`
+        Ok(vec![])
     }
 
     async fn create_order(&self, order: OrderRequest) -> Result<Order> {
+        let order = self.apply_market_filters(order).await?;
+
         let side = match order.side {
             TradeSide::Buy => "BUY",
             TradeSide::Sell => "SELL",
@@ -516,6 +662,37 @@ impl Exchange for BinanceExchange {
     fn get_name(&self) -> &str {
         "binance"
     }
+
+    async fn fetch_markets(&self) -> Result<Vec<Market>> {
+        let url = format!("{}/api/v3/exchangeInfo", self.get_base_url());
+        let response = self.client.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Exchange(format!(
+                "Failed to fetch exchange info: {}",
+                error_text
+            )));
+        }
+
+        let data: ExchangeInfo = response.json().await?;
+        Ok(data.symbols.into_iter().map(SymbolInfo::into_market).collect())
+    }
+
+    async fn load_markets(&self) -> Result<HashMap<String, Market>> {
+        if let Some(markets) = self.markets.read().await.as_ref() {
+            return Ok(markets.clone());
+        }
+
+        let markets: HashMap<String, Market> = self
+            .fetch_markets()
+            .await?
+            .into_iter()
+            .map(|m| (m.symbol.clone(), m))
+            .collect();
+        *self.markets.write().await = Some(markets.clone());
+        Ok(markets)
+    }
 }
 
 #[cfg(test)]
@@ -524,10 +701,111 @@ mod tests {
     use mockito::{Matcher, Server};
     use std::str::FromStr;
 
+    const EXCHANGE_INFO: &str = r#"{
+        "timezone": "UTC",
+        "serverTime": 1565246363776,
+        "symbols": [{
+            "symbol": "BTCUSDT",
+            "status": "TRADING",
+            "baseAsset": "BTC",
+            "quoteAsset": "USDT",
+            "filters": [
+                {"filterType": "PRICE_FILTER", "minPrice": "0.01", "maxPrice": "1000000.00", "tickSize": "0.01"},
+                {"filterType": "LOT_SIZE", "minQty": "0.00001", "maxQty": "9000.00000000", "stepSize": "0.00001"},
+                {"filterType": "NOTIONAL", "minNotional": "5.00000000", "applyMinToMarket": true}
+            ]
+        }]
+    }"#;
+
+    async fn mock_exchange_info(server: &mut mockito::ServerGuard) -> mockito::Mock {
+        server
+            .mock("GET", "/api/v3/exchangeInfo")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(EXCHANGE_INFO)
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_load_markets_parses_filters_and_caches() {
+        let mut server = Server::new_async().await;
+        let url = server.url();
+        let mock = mock_exchange_info(&mut server).await.expect(1);
+
+        let exchange = BinanceExchange::new("test_key".to_string(), "test_secret".to_string()).with_base_url(url);
+
+        let markets = exchange.load_markets().await.unwrap();
+        exchange.load_markets().await.unwrap();
+
+        mock.assert_async().await;
+        let market = &markets["BTC/USDT"];
+        assert_eq!(market.id, "BTCUSDT");
+        assert!(market.active);
+        assert_eq!(market.amount_step, Some(Decimal::from_str("0.00001").unwrap()));
+        assert_eq!(market.price_tick, Some(Decimal::from_str("0.01").unwrap()));
+        assert_eq!(market.min_notional, Some(Decimal::from(5)));
+    }
+
+    #[tokio::test]
+    async fn test_create_order_rounds_and_rejects_min_notional() {
+        let mut server = Server::new_async().await;
+        let url = server.url();
+        let _info = mock_exchange_info(&mut server).await;
+
+        let order_mock = server
+            .mock("POST", "/api/v3/order")
+            .match_query(Matcher::Regex("quantity=0.00123&price=43210.13&".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 29, "transactTime": 1507725176595, "price": "43210.13",
+                "origQty": "0.00123", "executedQty": "0.00000000", "cummulativeQuoteQty": "0.00000000",
+                "status": "NEW", "type": "LIMIT", "side": "BUY"}"#,
+            )
+            .create_async()
+            .await;
+
+        let exchange = BinanceExchange::new("test_key".to_string(), "test_secret".to_string()).with_base_url(url);
+
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Limit,
+                amount: Decimal::from_str("0.0012345678").unwrap(),
+                price: Some(Decimal::from_str("43210.126").unwrap()),
+            })
+            .await
+            .unwrap();
+        order_mock.assert_async().await;
+        assert_eq!(order.amount, Decimal::from_str("0.00123").unwrap());
+
+        let rejected = exchange
+            .create_order(OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Limit,
+                amount: Decimal::from_str("0.0001").unwrap(),
+                price: Some(Decimal::from(40000)),
+            })
+            .await;
+        assert!(matches!(rejected, Err(AppError::InvalidInput(_))));
+    }
+
     #[tokio::test]
     async fn test_create_order_success() {
         let mut server = Server::new_async().await;
         let url = server.url();
+        let _info = mock_exchange_info(&mut server).await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/24hr")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"symbol": "BTCUSDT", "lastPrice": "10.00", "volume": "1", "priceChangePercent": "0"}"#)
+            .create_async()
+            .await;
 
         let mock = server
             .mock("POST", "/api/v3/order")
@@ -627,6 +905,41 @@ mod tests {
         assert_eq!(balance.total, Decimal::from_str("150.0").unwrap());
     }
 
+    #[tokio::test]
+    async fn test_fetch_balances_skips_empty_assets() {
+        let mut server = Server::new_async().await;
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/v3/account")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                "makerCommission": 15,
+                "takerCommission": 15,
+                "balances": [
+                    {"asset": "BTC", "free": "0.50000000", "locked": "0.25000000"},
+                    {"asset": "LTC", "free": "0.00000000", "locked": "0.00000000"},
+                    {"asset": "USDT", "free": "100.00000000", "locked": "0.00000000"}
+                ]
+            }"#,
+            )
+            .create_async()
+            .await;
+
+        let exchange = BinanceExchange::new("test_key".to_string(), "test_secret".to_string()).with_base_url(url);
+
+        let balances = exchange.fetch_balances().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[0].currency, "BTC");
+        assert_eq!(balances[0].total, Decimal::from_str("0.75").unwrap());
+        assert_eq!(balances[1].currency, "USDT");
+    }
+
     #[tokio::test]
     async fn test_fetch_ohlcv_success() {
         let mut server = Server::new_async().await;
@@ -673,4 +986,78 @@ mod tests {
         assert_eq!(kline.close, Decimal::from_str("0.01577100").unwrap());
         assert_eq!(kline.volume, Decimal::from_str("148976.11427815").unwrap());
     }
+
+    fn hourly_klines(start_ms: i64, count: i64) -> String {
+        let rows: Vec<String> = (0..count)
+            .map(|i| {
+                let open_time = start_ms + i * 3_600_000;
+                format!(
+                    r#"[{}, "1.0", "2.0", "0.5", "1.5", "100.0", {}, "150.0", 10, "50.0", "75.0", "0"]"#,
+                    open_time,
+                    open_time + 3_599_999
+                )
+            })
+            .collect();
+        format!("[{}]", rows.join(","))
+    }
+
+    #[tokio::test]
+    async fn test_fetch_ohlcv_range_pages_and_retries_rate_limit() {
+        let mut server = Server::new_async().await;
+        let since = 1_704_067_200_000_i64; // 2024-01-01T00:00:00Z
+        let second_page = since + 1000 * 3_600_000;
+        let until = since + 1003 * 3_600_000;
+
+        let first = server
+            .mock("GET", "/api/v3/klines")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("symbol".into(), "BTCUSDT".into()),
+                Matcher::UrlEncoded("startTime".into(), since.to_string()),
+                Matcher::UrlEncoded("endTime".into(), (until - 1).to_string()),
+                Matcher::UrlEncoded("limit".into(), "1000".into()),
+            ]))
+            .with_status(200)
+            .with_body(hourly_klines(since, 1000))
+            .expect(1)
+            .create_async()
+            .await;
+        let rate_limited = server
+            .mock("GET", "/api/v3/klines")
+            .match_query(Matcher::UrlEncoded("startTime".into(), second_page.to_string()))
+            .with_status(429)
+            .with_header("Retry-After", "0")
+            .with_body(r#"{"code": -1003, "msg": "Too many requests"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let page = server
+            .mock("GET", "/api/v3/klines")
+            .match_query(Matcher::UrlEncoded("startTime".into(), second_page.to_string()))
+            .with_status(200)
+            .with_body(hourly_klines(second_page, 3))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let exchange = BinanceExchange::new("test_key".to_string(), "test_secret".to_string())
+            .with_base_url(server.url())
+            .with_rate_limit(std::time::Duration::ZERO);
+        let klines = exchange
+            .fetch_ohlcv_range(
+                "BTC/USDT",
+                "1h",
+                DateTime::from_timestamp_millis(since).unwrap(),
+                DateTime::from_timestamp_millis(until).unwrap(),
+            )
+            .await;
+
+        first.assert_async().await;
+        rate_limited.assert_async().await;
+        page.assert_async().await;
+        let klines = klines.unwrap();
+        assert_eq!(klines.len(), 1003);
+        assert_eq!(klines[0].timestamp.timestamp_millis(), since);
+        assert_eq!(klines[1002].timestamp.timestamp_millis(), until - 3_600_000);
+    }
 }
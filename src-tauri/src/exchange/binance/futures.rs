@@ -0,0 +1,685 @@
+use super::{
+    ExchangeInfo, SymbolInfo, parse_klines, parse_order_status, parse_order_type, parse_trade_side, sign_query,
+};
+use crate::error::{AppError, Result};
+use crate::exchange::Exchange;
+use crate::exchange::market::{Market, find_market};
+use crate::persistence::Repository;
+use crate::types::*;
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::Method;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Error code returned by `/fapi/v1/marginType` when the symbol already uses the requested mode
+const NO_NEED_TO_CHANGE_MARGIN_TYPE: &str = "-4046";
+
+#[derive(Deserialize)]
+struct FuturesOrderResponse {
+    symbol: String,
+    #[serde(rename = "orderId")]
+    order_id: i64,
+    status: String,
+    side: String,
+    #[serde(rename = "type")]
+    order_type: String,
+    price: String,
+    #[serde(rename = "avgPrice", default)]
+    avg_price: String,
+    #[serde(rename = "origQty")]
+    orig_qty: String,
+    #[serde(rename = "executedQty")]
+    executed_qty: String,
+    #[serde(rename = "updateTime", default)]
+    update_time: i64,
+    #[serde(default)]
+    time: i64,
+}
+
+impl FuturesOrderResponse {
+    fn into_order(self) -> Order {
+        let amount = self.orig_qty.parse().unwrap_or(Decimal::ZERO);
+        let filled = self.executed_qty.parse().unwrap_or(Decimal::ZERO);
+        let avg_price: Decimal = self.avg_price.parse().unwrap_or(Decimal::ZERO);
+        let limit_price: Decimal = self.price.parse().unwrap_or(Decimal::ZERO);
+        // Prefer the average fill price; market orders report a zero limit price
+        let price = [avg_price, limit_price].into_iter().find(|p| *p > Decimal::ZERO);
+
+        let updated_at = chrono::DateTime::from_timestamp_millis(self.update_time).unwrap_or_else(Utc::now);
+        let created_at = if self.time > 0 {
+            chrono::DateTime::from_timestamp_millis(self.time).unwrap_or(updated_at)
+        } else {
+            updated_at
+        };
+
+        Order {
+            id: self.order_id.to_string(),
+            symbol: self.symbol,
+            side: parse_trade_side(&self.side),
+            order_type: parse_order_type(&self.order_type),
+            status: parse_order_status(&self.status),
+            price,
+            amount,
+            filled,
+            remaining: amount - filled,
+            fee: None,
+            created_at,
+            updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FuturesBalance {
+    asset: String,
+    balance: String,
+    #[serde(rename = "availableBalance")]
+    available_balance: String,
+}
+
+#[derive(Deserialize)]
+struct PositionRisk {
+    symbol: String,
+    #[serde(rename = "positionAmt")]
+    position_amt: String,
+    #[serde(rename = "entryPrice")]
+    entry_price: String,
+    #[serde(rename = "markPrice")]
+    mark_price: String,
+    #[serde(rename = "unRealizedProfit")]
+    unrealized_profit: String,
+    #[serde(rename = "liquidationPrice")]
+    liquidation_price: String,
+    leverage: String,
+    #[serde(rename = "marginType")]
+    margin_type: String,
+}
+
+#[derive(Deserialize)]
+struct PremiumIndex {
+    symbol: String,
+    #[serde(rename = "markPrice")]
+    mark_price: String,
+    #[serde(rename = "lastFundingRate")]
+    last_funding_rate: String,
+    #[serde(rename = "nextFundingTime")]
+    next_funding_time: i64,
+}
+
+/// Binance USDⓈ-M perpetual futures (`/fapi`).
+///
+/// Runs in one-way position mode: a sell order without an open long opens a short.
+/// Binance needs the symbol to look up or cancel an order, so ids of orders placed through
+/// this instance are remembered together with their symbol. Orders placed before a restart are
+/// looked up in the repository, if one is set.
+pub struct BinanceFuturesExchange {
+    api_key: String,
+    api_secret: String,
+    base_url: String,
+    client: reqwest::Client,
+    markets: RwLock<Option<HashMap<String, Market>>>,
+    order_symbols: RwLock<HashMap<String, String>>,
+    repository: Option<Arc<Repository>>,
+}
+
+impl BinanceFuturesExchange {
+    pub fn new(api_key: String, api_secret: String) -> Self {
+        Self {
+            api_key,
+            api_secret,
+            base_url: "https://fapi.binance.com".to_string(),
+            client: reqwest::Client::new(),
+            markets: RwLock::new(None),
+            order_symbols: RwLock::new(HashMap::new()),
+            repository: None,
+        }
+    }
+
+    /// Point the client at another REST endpoint, e.g. the testnet
+    pub fn with_base_url(mut self, url: String) -> Self {
+        self.base_url = url;
+        self
+    }
+
+    /// Resolve the symbol of orders this instance did not place from the stored orders
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Send a signed request and return the response body, mapping Binance errors to `AppError::Exchange`
+    async fn send_signed(&self, method: Method, path: &str, query: String) -> Result<String> {
+        let url = format!("{}{}?{}", self.base_url, path, sign_query(&self.api_secret, query));
+        let response = self
+            .client
+            .request(method, &url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(AppError::Exchange(format!("Binance futures error: {}", body)));
+        }
+        Ok(body)
+    }
+
+    async fn order_symbol(&self, order_id: &str) -> Result<String> {
+        if let Some(symbol) = self.order_symbols.read().await.get(order_id) {
+            return Ok(symbol.clone());
+        }
+        if let Some(repository) = &self.repository
+            && let Some(symbol) = repository.get_order_symbol(order_id).await?
+        {
+            return Ok(symbol);
+        }
+        Err(AppError::NotFound(format!("Unknown futures order {}", order_id)))
+    }
+
+    /// Exchange id ("BTCUSDT") of a symbol, or the symbol itself for unknown markets
+    async fn market_id(&self, symbol: &str) -> Result<String> {
+        let markets = self.load_markets().await?;
+        Ok(find_market(&markets, symbol).map_or_else(|| symbol.to_string(), |m| m.id.clone()))
+    }
+
+    async fn apply_market_filters(&self, order: OrderRequest) -> Result<OrderRequest> {
+        let markets = self.load_markets().await?;
+        let Some(market) = find_market(&markets, &order.symbol) else {
+            return Ok(order);
+        };
+
+        let reference_price = match order.price {
+            Some(price) => price,
+            None if market.min_notional.is_some() => self.fetch_ticker(&market.id).await?.price,
+            None => Decimal::ZERO,
+        };
+        market.prepare_order(order, reference_price)
+    }
+}
+
+#[async_trait]
+impl Exchange for BinanceFuturesExchange {
+    async fn fetch_ticker(&self, symbol: &str) -> Result<Ticker> {
+        let url = format!(
+            "{}/fapi/v1/ticker/24hr?symbol={}",
+            self.base_url,
+            self.market_id(symbol).await?
+        );
+        let response = self.client.get(&url).send().await?;
+        let data: serde_json::Value = response.json().await?;
+
+        let field = |name: &str| data[name].as_str().unwrap_or("0").parse().unwrap_or(Decimal::ZERO);
+        Ok(Ticker {
+            symbol: data["symbol"].as_str().unwrap_or("").to_string(),
+            price: field("lastPrice"),
+            volume_24h: field("volume"),
+            change_24h: field("priceChangePercent"),
+        })
+    }
+
+    async fn fetch_ohlcv(&self, symbol: &str, timeframe: &str, limit: usize) -> Result<Vec<OHLCV>> {
+        let url = format!(
+            "{}/fapi/v1/klines?symbol={}&interval={}&limit={}",
+            self.base_url,
+            self.market_id(symbol).await?,
+            timeframe,
+            limit
+        );
+        let response = self.client.get(&url).send().await?;
+        let data: Vec<serde_json::Value> = response.json().await?;
+        Ok(parse_klines(data))
+    }
+
+    async fn fetch_balance(&self) -> Result<Balance> {
+        let balances = self.fetch_balances().await?;
+        Ok(balances
+            .into_iter()
+            .find(|b| b.currency == "USDT")
+            .unwrap_or_else(|| Balance {
+                currency: "USDT".to_string(),
+                total: Decimal::ZERO,
+                free: Decimal::ZERO,
+                used: Decimal::ZERO,
+            }))
+    }
+
+    async fn fetch_balances(&self) -> Result<Vec<Balance>> {
+        let body = self.send_signed(Method::GET, "/fapi/v2/balance", String::new()).await?;
+        let data: Vec<FuturesBalance> = serde_json::from_str(&body)?;
+
+        Ok(data
+            .into_iter()
+            .map(|b| {
+                let total: Decimal = b.balance.parse().unwrap_or(Decimal::ZERO);
+                let free: Decimal = b.available_balance.parse().unwrap_or(Decimal::ZERO);
+                Balance {
+                    currency: b.asset,
+                    total,
+                    free,
+                    used: total - free,
+                }
+            })
+            .filter(|b| b.total > Decimal::ZERO)
+            .collect())
+    }
+
+    async fn fetch_positions(&self) -> Result<Vec<Position>> {
+        let body = self
+            .send_signed(Method::GET, "/fapi/v2/positionRisk", String::new())
+            .await?;
+        let data: Vec<PositionRisk> = serde_json::from_str(&body)?;
+
+        let mut positions = Vec::new();
+        for pos in data {
+            let amount: Decimal = pos.position_amt.parse().unwrap_or(Decimal::ZERO);
+            if amount.is_zero() {
+                continue;
+            }
+
+            let entry_price: Decimal = pos.entry_price.parse().unwrap_or(Decimal::ZERO);
+            let unrealized_pnl: Decimal = pos.unrealized_profit.parse().unwrap_or(Decimal::ZERO);
+            let leverage: Decimal = pos.leverage.parse().unwrap_or(Decimal::ONE);
+            let liquidation_price: Decimal = pos.liquidation_price.parse().unwrap_or(Decimal::ZERO);
+            let size = amount.abs();
+
+            // Return on the margin posted for the position
+            let margin = entry_price * size / leverage;
+            let percentage = if margin > Decimal::ZERO {
+                unrealized_pnl / margin * Decimal::from(100)
+            } else {
+                Decimal::ZERO
+            };
+
+            positions.push(Position {
+                symbol: pos.symbol,
+                side: if amount > Decimal::ZERO {
+                    TradeSide::Buy
+                } else {
+                    TradeSide::Sell
+                },
+                size,
+                entry_price,
+                mark_price: pos.mark_price.parse().unwrap_or(Decimal::ZERO),
+                unrealized_pnl,
+                percentage,
+                leverage,
+                liquidation_price: (liquidation_price > Decimal::ZERO).then_some(liquidation_price),
+                margin_mode: Some(if pos.margin_type == "isolated" {
+                    MarginMode::Isolated
+                } else {
+                    MarginMode::Cross
+                }),
+            });
+        }
+
+        Ok(positions)
+    }
+
+    async fn create_order(&self, order: OrderRequest) -> Result<Order> {
+        let order = self.apply_market_filters(order).await?;
+        let symbol = self.market_id(&order.symbol).await?;
+
+        let side = match order.side {
+            TradeSide::Buy => "BUY",
+            TradeSide::Sell => "SELL",
+        };
+        let order_type = match order.order_type {
+            OrderType::Market => "MARKET",
+            OrderType::Limit => "LIMIT",
+            OrderType::StopLimit => "STOP",
+            OrderType::StopMarket => "STOP_MARKET",
+        };
+
+        let mut query = format!(
+            "symbol={}&side={}&type={}&quantity={}&newOrderRespType=RESULT",
+            symbol, side, order_type, order.amount
+        );
+        match (order.order_type, order.price) {
+            (OrderType::Market, _) => {}
+            (OrderType::StopMarket, Some(price)) => query.push_str(&format!("&stopPrice={}", price)),
+            (OrderType::StopLimit, Some(price)) => {
+                query.push_str(&format!("&price={}&stopPrice={}&timeInForce=GTC", price, price))
+            }
+            (OrderType::Limit, Some(price)) => query.push_str(&format!("&price={}&timeInForce=GTC", price)),
+            (_, None) => {
+                return Err(AppError::Exchange(format!(
+                    "{:?} order requires price",
+                    order.order_type
+                )));
+            }
+        }
+
+        let body = self.send_signed(Method::POST, "/fapi/v1/order", query).await?;
+        let data: FuturesOrderResponse = serde_json::from_str(&body)?;
+        let placed = data.into_order();
+
+        self.order_symbols
+            .write()
+            .await
+            .insert(placed.id.clone(), placed.symbol.clone());
+        Ok(placed)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let symbol = self.order_symbol(order_id).await?;
+        let query = format!("symbol={}&orderId={}", symbol, order_id);
+        self.send_signed(Method::DELETE, "/fapi/v1/order", query).await?;
+        Ok(())
+    }
+
+    async fn fetch_order(&self, order_id: &str) -> Result<Order> {
+        let symbol = self.order_symbol(order_id).await?;
+        let query = format!("symbol={}&orderId={}", symbol, order_id);
+        let body = self.send_signed(Method::GET, "/fapi/v1/order", query).await?;
+        let data: FuturesOrderResponse = serde_json::from_str(&body)?;
+        Ok(data.into_order())
+    }
+
+    async fn fetch_orders(&self, symbol: &str) -> Result<Vec<Order>> {
+        let query = format!("symbol={}", self.market_id(symbol).await?);
+        let body = self.send_signed(Method::GET, "/fapi/v1/allOrders", query).await?;
+        let data: Vec<FuturesOrderResponse> = serde_json::from_str(&body)?;
+        Ok(data.into_iter().map(FuturesOrderResponse::into_order).collect())
+    }
+
+    fn get_name(&self) -> &str {
+        "binance_futures"
+    }
+
+    async fn fetch_markets(&self) -> Result<Vec<Market>> {
+        let url = format!("{}/fapi/v1/exchangeInfo", self.base_url);
+        let response = self.client.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Exchange(format!(
+                "Failed to fetch exchange info: {}",
+                error_text
+            )));
+        }
+
+        let data: ExchangeInfo = response.json().await?;
+        Ok(data.symbols.into_iter().map(SymbolInfo::into_market).collect())
+    }
+
+    async fn load_markets(&self) -> Result<HashMap<String, Market>> {
+        if let Some(markets) = self.markets.read().await.as_ref() {
+            return Ok(markets.clone());
+        }
+
+        let markets: HashMap<String, Market> = self
+            .fetch_markets()
+            .await?
+            .into_iter()
+            .map(|m| (m.symbol.clone(), m))
+            .collect();
+        *self.markets.write().await = Some(markets.clone());
+        Ok(markets)
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u32) -> Result<()> {
+        if leverage == 0 {
+            return Err(AppError::InvalidInput("Leverage must be at least 1".to_string()));
+        }
+        let query = format!("symbol={}&leverage={}", self.market_id(symbol).await?, leverage);
+        self.send_signed(Method::POST, "/fapi/v1/leverage", query).await?;
+        Ok(())
+    }
+
+    async fn set_margin_mode(&self, symbol: &str, mode: MarginMode) -> Result<()> {
+        let margin_type = match mode {
+            MarginMode::Isolated => "ISOLATED",
+            MarginMode::Cross => "CROSSED",
+        };
+        let query = format!("symbol={}&marginType={}", self.market_id(symbol).await?, margin_type);
+        match self.send_signed(Method::POST, "/fapi/v1/marginType", query).await {
+            Err(AppError::Exchange(msg)) if msg.contains(NO_NEED_TO_CHANGE_MARGIN_TYPE) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    async fn fetch_funding_rate(&self, symbol: &str) -> Result<FundingRate> {
+        let url = format!(
+            "{}/fapi/v1/premiumIndex?symbol={}",
+            self.base_url,
+            self.market_id(symbol).await?
+        );
+        let response = self.client.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Exchange(format!(
+                "Failed to fetch funding rate: {}",
+                error_text
+            )));
+        }
+
+        let data: PremiumIndex = response.json().await?;
+        Ok(FundingRate {
+            symbol: data.symbol,
+            funding_rate: data.last_funding_rate.parse().unwrap_or(Decimal::ZERO),
+            mark_price: data.mark_price.parse().unwrap_or(Decimal::ZERO),
+            next_funding_time: chrono::DateTime::from_timestamp_millis(data.next_funding_time).unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server, ServerGuard};
+    use std::str::FromStr;
+
+    fn exchange(url: String) -> BinanceFuturesExchange {
+        BinanceFuturesExchange::new("test_key".to_string(), "test_secret".to_string()).with_base_url(url)
+    }
+
+    async fn mock_exchange_info(server: &mut ServerGuard) -> mockito::Mock {
+        server
+            .mock("GET", "/fapi/v1/exchangeInfo")
+            .with_status(200)
+            .with_body(
+                r#"{"symbols": [{"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC",
+                "quoteAsset": "USDT", "filters": []}]}"#,
+            )
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_set_leverage_and_margin_mode() {
+        let mut server = Server::new_async().await;
+        let _info = mock_exchange_info(&mut server).await;
+        let leverage_mock = server
+            .mock("POST", "/fapi/v1/leverage")
+            .match_header("X-MBX-APIKEY", "test_key")
+            .match_query(Matcher::Regex("symbol=BTCUSDT&leverage=5&timestamp=".to_string()))
+            .with_status(200)
+            .with_body(r#"{"leverage": 5, "maxNotionalValue": "1000000", "symbol": "BTCUSDT"}"#)
+            .create_async()
+            .await;
+        let margin_mock = server
+            .mock("POST", "/fapi/v1/marginType")
+            .match_query(Matcher::Regex("marginType=ISOLATED".to_string()))
+            .with_status(400)
+            .with_body(r#"{"code": -4046, "msg": "No need to change margin type."}"#)
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        // The bot passes configured pairs, which map to the exchange symbol
+        exchange.set_leverage("BTC/USDT", 5).await.unwrap();
+        exchange.set_margin_mode("BTCUSDT", MarginMode::Isolated).await.unwrap();
+
+        leverage_mock.assert_async().await;
+        margin_mock.assert_async().await;
+        assert!(matches!(
+            exchange.set_leverage("BTCUSDT", 0).await,
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_ohlcv_maps_pair_to_symbol() {
+        let mut server = Server::new_async().await;
+        let _info = mock_exchange_info(&mut server).await;
+        let mock = server
+            .mock("GET", "/fapi/v1/klines")
+            .match_query(Matcher::Regex("symbol=BTCUSDT&interval=1h&limit=1".to_string()))
+            .with_status(200)
+            .with_body(r#"[[1700000000000, "100.0", "101.0", "99.0", "100.5", "10.0", 1700003599999, "1005.0", 10, "5.0", "502.5", "0"]]"#)
+            .create_async()
+            .await;
+
+        let klines = exchange(server.url()).fetch_ohlcv("BTC/USDT", "1h", 1).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(klines.len(), 1);
+        assert_eq!(klines[0].close, Decimal::from_str("100.5").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_positions_with_liquidation_price() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/fapi/v2/positionRisk")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"[
+                {"symbol": "BTCUSDT", "positionAmt": "-0.010", "entryPrice": "40000.0", "markPrice": "39000.0",
+                 "unRealizedProfit": "10.00000000", "liquidationPrice": "47500.5", "leverage": "10",
+                 "marginType": "isolated", "isolatedMargin": "40.0", "positionSide": "BOTH"},
+                {"symbol": "ETHUSDT", "positionAmt": "0.000", "entryPrice": "0.0", "markPrice": "2000.0",
+                 "unRealizedProfit": "0.00000000", "liquidationPrice": "0", "leverage": "20",
+                 "marginType": "cross", "isolatedMargin": "0.0", "positionSide": "BOTH"}
+            ]"#,
+            )
+            .create_async()
+            .await;
+
+        let positions = exchange(server.url()).fetch_positions().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(positions.len(), 1);
+        let short = &positions[0];
+        assert_eq!(short.side, TradeSide::Sell);
+        assert_eq!(short.size, Decimal::from_str("0.01").unwrap());
+        assert_eq!(short.leverage, Decimal::from(10));
+        assert_eq!(short.liquidation_price, Some(Decimal::from_str("47500.5").unwrap()));
+        assert_eq!(short.margin_mode, Some(MarginMode::Isolated));
+        // 10 profit on 40 margin
+        assert_eq!(short.percentage, Decimal::from(25));
+    }
+
+    #[tokio::test]
+    async fn test_open_short_and_cancel() {
+        let mut server = Server::new_async().await;
+        let _info = mock_exchange_info(&mut server).await;
+        let order_mock = server
+            .mock("POST", "/fapi/v1/order")
+            .match_query(Matcher::Regex(
+                "symbol=BTCUSDT&side=SELL&type=MARKET&quantity=0.01".to_string(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 3051, "status": "FILLED", "side": "SELL", "type": "MARKET",
+                "price": "0", "avgPrice": "40010.50", "origQty": "0.010", "executedQty": "0.010",
+                "cumQuote": "400.105", "positionSide": "BOTH", "reduceOnly": false, "updateTime": 1700000000000}"#,
+            )
+            .create_async()
+            .await;
+        let cancel_mock = server
+            .mock("DELETE", "/fapi/v1/order")
+            .match_query(Matcher::Regex("symbol=BTCUSDT&orderId=3051".to_string()))
+            .with_status(200)
+            .with_body("{}")
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Sell,
+                order_type: OrderType::Market,
+                amount: Decimal::from_str("0.01").unwrap(),
+                price: None,
+            })
+            .await
+            .unwrap();
+
+        order_mock.assert_async().await;
+        assert_eq!(order.side, TradeSide::Sell);
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.price, Some(Decimal::from_str("40010.50").unwrap()));
+
+        exchange.cancel_order("3051").await.unwrap();
+        cancel_mock.assert_async().await;
+        assert!(matches!(exchange.cancel_order("999").await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_placed_before_restart() {
+        let mut server = Server::new_async().await;
+        let cancel_mock = server
+            .mock("DELETE", "/fapi/v1/order")
+            .match_query(Matcher::Regex("symbol=ETHUSDT&orderId=4102".to_string()))
+            .with_status(200)
+            .with_body("{}")
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Arc::new(Repository::new(dir.path().join("test.db")).await.unwrap());
+        let order = Order {
+            id: "4102".to_string(),
+            symbol: "ETHUSDT".to_string(),
+            side: TradeSide::Buy,
+            order_type: OrderType::Limit,
+            status: OrderStatus::New,
+            price: Some(Decimal::from(2000)),
+            amount: Decimal::ONE,
+            filled: Decimal::ZERO,
+            remaining: Decimal::ONE,
+            fee: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        repository.save_order(&order, None).await.unwrap();
+
+        // A fresh instance has not placed the order itself
+        let exchange = exchange(server.url()).with_repository(repository);
+        exchange.cancel_order("4102").await.unwrap();
+        cancel_mock.assert_async().await;
+        assert!(matches!(exchange.cancel_order("999").await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_funding_rate() {
+        let mut server = Server::new_async().await;
+        let _info = mock_exchange_info(&mut server).await;
+        let mock = server
+            .mock("GET", "/fapi/v1/premiumIndex")
+            .match_query(Matcher::UrlEncoded("symbol".to_string(), "BTCUSDT".to_string()))
+            .with_status(200)
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "markPrice": "11793.63104562", "indexPrice": "11781.80495970",
+                "lastFundingRate": "0.00038246", "interestRate": "0.00010000",
+                "nextFundingTime": 1597392000000, "time": 1597370495002}"#,
+            )
+            .create_async()
+            .await;
+
+        let rate = exchange(server.url()).fetch_funding_rate("BTC/USDT").await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(rate.funding_rate, Decimal::from_str("0.00038246").unwrap());
+        assert_eq!(rate.next_funding_time.timestamp_millis(), 1597392000000);
+    }
+}
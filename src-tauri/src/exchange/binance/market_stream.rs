@@ -0,0 +1,617 @@
+use crate::error::{AppError, Result};
+use crate::exchange::Exchange;
+use crate::types::OHLCV;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{RwLock, broadcast};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+const DEFAULT_STREAM_URL: &str = "wss://stream.binance.com:9443";
+const DEFAULT_CACHE_SIZE: usize = 1000;
+const EVENT_CHANNEL_SIZE: usize = 1024;
+const TICKER_CHANNEL_SIZE: usize = 1024;
+
+/// Candle data pushed by the Binance kline streams
+#[derive(Debug, Clone)]
+pub enum MarketEvent {
+    Kline {
+        pair: String,
+        timeframe: String,
+        candle: OHLCV,
+        is_closed: bool,
+    },
+}
+
+/// Best bid/ask from the `@bookTicker` stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookTicker {
+    pub pair: String,
+    pub bid_price: Decimal,
+    pub bid_qty: Decimal,
+    pub ask_price: Decimal,
+    pub ask_qty: Decimal,
+}
+
+#[derive(Deserialize)]
+struct CombinedStreamMessage {
+    stream: String,
+    data: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct KlineEvent {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "k")]
+    kline: KlinePayload,
+}
+
+#[derive(Deserialize)]
+struct KlinePayload {
+    #[serde(rename = "t")]
+    open_time: i64,
+    #[serde(rename = "i")]
+    interval: String,
+    #[serde(rename = "o")]
+    open: String,
+    #[serde(rename = "h")]
+    high: String,
+    #[serde(rename = "l")]
+    low: String,
+    #[serde(rename = "c")]
+    close: String,
+    #[serde(rename = "v")]
+    volume: String,
+    #[serde(rename = "x")]
+    is_closed: bool,
+}
+
+#[derive(Deserialize)]
+struct BookTickerEvent {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "b")]
+    bid_price: String,
+    #[serde(rename = "B")]
+    bid_qty: String,
+    #[serde(rename = "a")]
+    ask_price: String,
+    #[serde(rename = "A")]
+    ask_qty: String,
+}
+
+#[derive(Default)]
+struct CandleSeries {
+    candles: VecDeque<OHLCV>,
+    seeded: bool,
+}
+
+/// In-memory candle cache keyed by (pair, timeframe)
+pub struct CandleCache {
+    series: RwLock<HashMap<(String, String), CandleSeries>>,
+    capacity: usize,
+}
+
+impl CandleCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            series: RwLock::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Seed the cache with REST history, keeping any streamed candles that are newer
+    pub async fn seed(&self, pair: &str, timeframe: &str, candles: &[OHLCV]) {
+        let mut series = self.series.write().await;
+        let entry = series.entry((pair.to_string(), timeframe.to_string())).or_default();
+
+        let streamed = std::mem::take(&mut entry.candles);
+        entry.candles = candles.iter().cloned().collect();
+        entry.seeded = true;
+        for candle in streamed {
+            Self::merge(entry, candle, self.capacity);
+        }
+        while entry.candles.len() > self.capacity {
+            entry.candles.pop_front();
+        }
+    }
+
+    /// Insert or replace a candle; the open candle is updated in place until it closes
+    pub async fn update(&self, pair: &str, timeframe: &str, candle: OHLCV) {
+        let mut series = self.series.write().await;
+        let entry = series.entry((pair.to_string(), timeframe.to_string())).or_default();
+        Self::merge(entry, candle, self.capacity);
+    }
+
+    /// Get all cached candles, oldest first
+    pub async fn get(&self, pair: &str, timeframe: &str) -> Vec<OHLCV> {
+        let series = self.series.read().await;
+        series
+            .get(&(pair.to_string(), timeframe.to_string()))
+            .map(|s| s.candles.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get cached candles only once the series has been seeded with history
+    pub async fn get_seeded(&self, pair: &str, timeframe: &str) -> Option<Vec<OHLCV>> {
+        let series = self.series.read().await;
+        series
+            .get(&(pair.to_string(), timeframe.to_string()))
+            .filter(|s| s.seeded)
+            .map(|s| s.candles.iter().cloned().collect())
+    }
+
+    /// Open time of the newest candle of every seeded series
+    pub async fn last_seeded(&self) -> Vec<(String, String, DateTime<Utc>)> {
+        let series = self.series.read().await;
+        series
+            .iter()
+            .filter(|(_, s)| s.seeded)
+            .filter_map(|((pair, timeframe), s)| Some((pair.clone(), timeframe.clone(), s.candles.back()?.timestamp)))
+            .collect()
+    }
+
+    fn merge(series: &mut CandleSeries, candle: OHLCV, capacity: usize) {
+        match series.candles.back_mut() {
+            Some(last) if last.timestamp == candle.timestamp => *last = candle,
+            Some(last) if last.timestamp > candle.timestamp => {}
+            _ => series.candles.push_back(candle),
+        }
+        while series.candles.len() > capacity {
+            series.candles.pop_front();
+        }
+    }
+}
+
+impl Default for CandleCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_SIZE)
+    }
+}
+
+/// Binance combined-stream client for `<symbol>@kline_<interval>` and `<symbol>@bookTicker`.
+///
+/// Keeps the candle cache and best bid/ask up to date and republishes candle and ticker updates
+/// on separate broadcast channels. The connection is re-established automatically when it drops,
+/// and with a backfill exchange the candles missed while disconnected are fetched over REST.
+pub struct BinanceMarketStream {
+    base_url: String,
+    /// Lowercase stream symbol ("btcusdt") to configured pair ("BTC/USDT")
+    pairs: HashMap<String, String>,
+    timeframes: Vec<String>,
+    cache: Arc<CandleCache>,
+    tickers: RwLock<HashMap<String, BookTicker>>,
+    events: broadcast::Sender<MarketEvent>,
+    ticker_events: broadcast::Sender<BookTicker>,
+    backfill: Option<Arc<dyn Exchange>>,
+    reconnect_delay: Duration,
+}
+
+impl BinanceMarketStream {
+    pub fn new(pairs: &[String], timeframes: &[String]) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_SIZE);
+        let (ticker_events, _) = broadcast::channel(TICKER_CHANNEL_SIZE);
+        Self {
+            base_url: DEFAULT_STREAM_URL.to_string(),
+            pairs: pairs
+                .iter()
+                .map(|p| (p.replace('/', "").to_lowercase(), p.clone()))
+                .collect(),
+            timeframes: timeframes.to_vec(),
+            cache: Arc::new(CandleCache::default()),
+            tickers: RwLock::new(HashMap::new()),
+            events,
+            ticker_events,
+            backfill: None,
+            reconnect_delay: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_base_url(mut self, url: String) -> Self {
+        self.base_url = url;
+        self
+    }
+
+    pub fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// Exchange used to fetch the candles of seeded series missed while disconnected
+    pub fn with_backfill(mut self, exchange: Arc<dyn Exchange>) -> Self {
+        self.backfill = Some(exchange);
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MarketEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn subscribe_book_tickers(&self) -> broadcast::Receiver<BookTicker> {
+        self.ticker_events.subscribe()
+    }
+
+    pub fn cache(&self) -> Arc<CandleCache> {
+        self.cache.clone()
+    }
+
+    pub async fn book_ticker(&self, pair: &str) -> Option<BookTicker> {
+        self.tickers.read().await.get(pair).cloned()
+    }
+
+    fn stream_url(&self) -> String {
+        let mut symbols: Vec<&String> = self.pairs.keys().collect();
+        symbols.sort();
+
+        let mut streams = Vec::new();
+        for symbol in symbols {
+            for timeframe in &self.timeframes {
+                streams.push(format!("{}@kline_{}", symbol, timeframe));
+            }
+            streams.push(format!("{}@bookTicker", symbol));
+        }
+
+        format!("{}/stream?streams={}", self.base_url, streams.join("/"))
+    }
+
+    /// Run the stream until the task is aborted, reconnecting after every disconnect
+    pub async fn run(&self) {
+        loop {
+            match self.connect_and_read().await {
+                Ok(()) => eprintln!("Binance market stream closed, reconnecting"),
+                Err(e) => eprintln!("Binance market stream error: {}, reconnecting", e),
+            }
+            tokio::time::sleep(self.reconnect_delay).await;
+        }
+    }
+
+    async fn connect_and_read(&self) -> Result<()> {
+        let (mut socket, _) = connect_async(self.stream_url())
+            .await
+            .map_err(|e| AppError::WebSocket(format!("Failed to connect: {}", e)))?;
+
+        // Messages received meanwhile wait in the socket, so the backfill leaves no gap
+        self.backfill_cache().await;
+
+        while let Some(message) = socket.next().await {
+            let message = message.map_err(|e| AppError::WebSocket(e.to_string()))?;
+            match message {
+                Message::Text(text) => {
+                    if let Err(e) = self.handle_message(text.as_str()).await {
+                        eprintln!("Failed to handle market stream message: {}", e);
+                    }
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fetch every seeded series from its newest cached candle on, replacing that candle
+    /// since it may have been cached before it closed
+    async fn backfill_cache(&self) {
+        let Some(exchange) = &self.backfill else {
+            return;
+        };
+
+        for (pair, timeframe, since) in self.cache.last_seeded().await {
+            // Request the exchange symbol ("BTCUSDT"); candles stay cached under the pair
+            let symbol = pair.replace('/', "");
+            match exchange.fetch_ohlcv_range(&symbol, &timeframe, since, Utc::now()).await {
+                Ok(candles) => {
+                    for candle in candles {
+                        self.cache.update(&pair, &timeframe, candle).await;
+                    }
+                }
+                Err(e) => eprintln!("Failed to backfill {} {} candles: {}", pair, timeframe, e),
+            }
+        }
+    }
+
+    async fn handle_message(&self, text: &str) -> Result<()> {
+        let message: CombinedStreamMessage = serde_json::from_str(text)?;
+
+        if message.stream.contains("@kline_") {
+            let event: KlineEvent = serde_json::from_value(message.data)?;
+            let pair = self.pair_for(&event.symbol)?;
+            let candle = OHLCV {
+                timestamp: chrono::DateTime::from_timestamp_millis(event.kline.open_time).unwrap_or_else(Utc::now),
+                open: parse_decimal(&event.kline.open)?,
+                high: parse_decimal(&event.kline.high)?,
+                low: parse_decimal(&event.kline.low)?,
+                close: parse_decimal(&event.kline.close)?,
+                volume: parse_decimal(&event.kline.volume)?,
+            };
+
+            self.cache.update(&pair, &event.kline.interval, candle.clone()).await;
+            let _ = self.events.send(MarketEvent::Kline {
+                pair,
+                timeframe: event.kline.interval,
+                candle,
+                is_closed: event.kline.is_closed,
+            });
+        } else if message.stream.ends_with("@bookTicker") {
+            let event: BookTickerEvent = serde_json::from_value(message.data)?;
+            let ticker = BookTicker {
+                pair: self.pair_for(&event.symbol)?,
+                bid_price: parse_decimal(&event.bid_price)?,
+                bid_qty: parse_decimal(&event.bid_qty)?,
+                ask_price: parse_decimal(&event.ask_price)?,
+                ask_qty: parse_decimal(&event.ask_qty)?,
+            };
+
+            self.tickers.write().await.insert(ticker.pair.clone(), ticker.clone());
+            let _ = self.ticker_events.send(ticker);
+        }
+
+        Ok(())
+    }
+
+    fn pair_for(&self, symbol: &str) -> Result<String> {
+        self.pairs
+            .get(&symbol.to_lowercase())
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Unsubscribed symbol in stream: {}", symbol)))
+    }
+}
+
+fn parse_decimal(value: &str) -> Result<Decimal> {
+    value
+        .parse()
+        .map_err(|e| AppError::Parse(format!("Invalid decimal {}: {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::SinkExt;
+    use std::str::FromStr;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    fn kline_message(open_time: i64, close: &str, is_closed: bool) -> String {
+        format!(
+            r#"{{
+                "stream": "btcusdt@kline_1m",
+                "data": {{
+                    "e": "kline", "E": {open_time}, "s": "BTCUSDT",
+                    "k": {{
+                        "t": {open_time}, "T": {close_time}, "s": "BTCUSDT", "i": "1m",
+                        "o": "100.0", "c": "{close}", "h": "110.0", "l": "90.0", "v": "12.5",
+                        "x": {is_closed}
+                    }}
+                }}
+            }}"#,
+            open_time = open_time,
+            close_time = open_time + 59_999,
+            close = close,
+            is_closed = is_closed,
+        )
+    }
+
+    const BOOK_TICKER_MESSAGE: &str = r#"{
+        "stream": "btcusdt@bookTicker",
+        "data": {"u": 400900217, "s": "BTCUSDT", "b": "99.5", "B": "3.0", "a": "100.5", "A": "4.0"}
+    }"#;
+
+    async fn next_kline(events: &mut broadcast::Receiver<MarketEvent>) -> (OHLCV, bool) {
+        let MarketEvent::Kline { candle, is_closed, .. } = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("timed out waiting for market event")
+            .unwrap();
+        (candle, is_closed)
+    }
+
+    #[test]
+    fn test_stream_url() {
+        let stream = BinanceMarketStream::new(&["BTC/USDT".to_string()], &["1h".to_string()])
+            .with_base_url("ws://localhost".to_string());
+
+        assert_eq!(
+            stream.stream_url(),
+            "ws://localhost/stream?streams=btcusdt@kline_1h/btcusdt@bookTicker"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_candle_cache_updates_open_candle_in_place() {
+        let cache = CandleCache::new(2);
+        let candle = |minute: i64, close: i64| OHLCV {
+            timestamp: chrono::DateTime::from_timestamp(minute * 60, 0).unwrap(),
+            open: Decimal::ONE,
+            high: Decimal::ONE,
+            low: Decimal::ONE,
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+        };
+
+        cache.update("BTC/USDT", "1m", candle(1, 10)).await;
+        assert!(cache.get_seeded("BTC/USDT", "1m").await.is_none());
+
+        cache.seed("BTC/USDT", "1m", &[candle(0, 5)]).await;
+        cache.update("BTC/USDT", "1m", candle(1, 11)).await;
+        cache.update("BTC/USDT", "1m", candle(2, 12)).await;
+
+        let candles = cache.get_seeded("BTC/USDT", "1m").await.unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].close, Decimal::from(11));
+        assert_eq!(candles[1].close, Decimal::from(12));
+    }
+
+    #[tokio::test]
+    async fn test_stream_updates_cache_and_tickers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            ws.send(Message::Text(BOOK_TICKER_MESSAGE.into())).await.unwrap();
+            ws.send(Message::Text(kline_message(60_000, "101.0", false).into()))
+                .await
+                .unwrap();
+            ws.send(Message::Text(kline_message(60_000, "105.0", true).into()))
+                .await
+                .unwrap();
+            // Keep the connection open until the client is done
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let stream = Arc::new(
+            BinanceMarketStream::new(&["BTC/USDT".to_string()], &["1m".to_string()])
+                .with_base_url(format!("ws://{}", addr)),
+        );
+        let mut events = stream.subscribe();
+        let mut tickers = stream.subscribe_book_tickers();
+        let runner = {
+            let stream = stream.clone();
+            tokio::spawn(async move { stream.run().await })
+        };
+
+        // The ticker sent first arrives on its own channel, not between the candles
+        let ticker = tokio::time::timeout(Duration::from_secs(5), tickers.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ticker.pair, "BTC/USDT");
+        let (_, is_closed) = next_kline(&mut events).await;
+        assert!(!is_closed);
+        let (candle, is_closed) = next_kline(&mut events).await;
+        assert!(is_closed);
+        assert_eq!(candle.close, Decimal::from_str("105.0").unwrap());
+
+        let cached = stream.cache().get("BTC/USDT", "1m").await;
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].close, Decimal::from_str("105.0").unwrap());
+
+        let ticker = stream.book_ticker("BTC/USDT").await.unwrap();
+        assert_eq!(ticker.bid_price, Decimal::from_str("99.5").unwrap());
+        assert_eq!(ticker.ask_price, Decimal::from_str("100.5").unwrap());
+
+        runner.abort();
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_stream_reconnects_after_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            for (open_time, close) in [(60_000, "101.0"), (120_000, "102.0")] {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(tcp).await.unwrap();
+                ws.send(Message::Text(kline_message(open_time, close, true).into()))
+                    .await
+                    .unwrap();
+                ws.close(None).await.unwrap();
+            }
+        });
+
+        let stream = Arc::new(
+            BinanceMarketStream::new(&["BTC/USDT".to_string()], &["1m".to_string()])
+                .with_base_url(format!("ws://{}", addr))
+                .with_reconnect_delay(Duration::from_millis(10)),
+        );
+        let mut events = stream.subscribe();
+        let runner = {
+            let stream = stream.clone();
+            tokio::spawn(async move { stream.run().await })
+        };
+
+        let (first, _) = next_kline(&mut events).await;
+        let (second, _) = next_kline(&mut events).await;
+        assert_eq!(first.close, Decimal::from_str("101.0").unwrap());
+        assert_eq!(second.close, Decimal::from_str("102.0").unwrap());
+        assert_eq!(stream.cache().get("BTC/USDT", "1m").await.len(), 2);
+
+        runner.abort();
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_connect_backfills_seeded_series_over_rest() {
+        let mut rest = mockito::Server::new_async().await;
+        let rows: Vec<String> = (1..=3)
+            .map(|minute| {
+                let open_time = minute * 60_000;
+                format!(
+                    r#"[{}, "100.0", "110.0", "90.0", "{}.0", "1.0", {}, "0", 1, "0", "0", "0"]"#,
+                    open_time,
+                    100 + minute,
+                    open_time + 59_999
+                )
+            })
+            .collect();
+        let klines = rest
+            .mock("GET", "/api/v3/klines")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("symbol".into(), "BTCUSDT".into()),
+                mockito::Matcher::UrlEncoded("startTime".into(), "60000".into()),
+            ]))
+            .with_status(200)
+            .with_body(format!("[{}]", rows.join(",")))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            ws.send(Message::Text(kline_message(240_000, "104.0", false).into()))
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let exchange = crate::exchange::binance::BinanceExchange::new(String::new(), String::new())
+            .with_base_url(rest.url())
+            .with_rate_limit(Duration::ZERO);
+        let stream = Arc::new(
+            BinanceMarketStream::new(&["BTC/USDT".to_string()], &["1m".to_string()])
+                .with_base_url(format!("ws://{}", addr))
+                .with_backfill(Arc::new(exchange)),
+        );
+        // Cached while still open, before the connection dropped
+        let open = OHLCV {
+            timestamp: chrono::DateTime::from_timestamp_millis(60_000).unwrap(),
+            open: Decimal::from(100),
+            high: Decimal::from(100),
+            low: Decimal::from(100),
+            close: Decimal::from(100),
+            volume: Decimal::ONE,
+        };
+        stream.cache().seed("BTC/USDT", "1m", &[open]).await;
+
+        let mut events = stream.subscribe();
+        let runner = {
+            let stream = stream.clone();
+            tokio::spawn(async move { stream.run().await })
+        };
+        next_kline(&mut events).await;
+
+        let closes: Vec<Decimal> = stream
+            .cache()
+            .get("BTC/USDT", "1m")
+            .await
+            .iter()
+            .map(|c| c.close)
+            .collect();
+        let expected: Vec<Decimal> = [101, 102, 103, 104].into_iter().map(Decimal::from).collect();
+        assert_eq!(closes, expected);
+        klines.assert_async().await;
+
+        runner.abort();
+        server.abort();
+    }
+}
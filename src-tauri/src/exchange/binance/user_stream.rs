@@ -0,0 +1,436 @@
+use super::{parse_order_status, parse_order_type, parse_trade_side};
+use crate::error::{AppError, Result};
+use crate::types::{Balance, Order};
+use chrono::Utc;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::{Instant, interval_at};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+const DEFAULT_REST_URL: &str = "https://api.binance.com";
+const DEFAULT_WS_URL: &str = "wss://stream.binance.com:9443";
+const EVENT_CHANNEL_SIZE: usize = 256;
+
+/// Account updates pushed by the Binance user-data stream
+#[derive(Debug, Clone)]
+pub enum UserDataEvent {
+    /// Order state after an `executionReport` (new, partial fill, fill, cancel, ...)
+    OrderUpdate(Order),
+    /// Changed balances from an `outboundAccountPosition`
+    BalanceUpdate(Vec<Balance>),
+}
+
+#[derive(Deserialize)]
+struct ListenKeyResponse {
+    #[serde(rename = "listenKey")]
+    listen_key: String,
+}
+
+#[derive(Deserialize)]
+struct ExecutionReport {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "S")]
+    side: String,
+    #[serde(rename = "o")]
+    order_type: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "X")]
+    status: String,
+    #[serde(rename = "i")]
+    order_id: i64,
+    #[serde(rename = "z")]
+    cumulative_filled: String,
+    #[serde(rename = "Z")]
+    cumulative_quote: String,
+    #[serde(rename = "O")]
+    created_time: i64,
+}
+
+#[derive(Deserialize)]
+struct AccountPosition {
+    #[serde(rename = "B")]
+    balances: Vec<AccountBalance>,
+}
+
+#[derive(Deserialize)]
+struct AccountBalance {
+    #[serde(rename = "a")]
+    asset: String,
+    #[serde(rename = "f")]
+    free: String,
+    #[serde(rename = "l")]
+    locked: String,
+}
+
+/// Binance spot user-data stream client.
+///
+/// Obtains a listen key over REST, keeps it alive while connected and republishes
+/// order and balance updates on a broadcast channel. Reconnects with a fresh listen key
+/// when the socket drops.
+pub struct BinanceUserStream {
+    api_key: String,
+    rest_url: String,
+    ws_url: String,
+    client: reqwest::Client,
+    events: broadcast::Sender<UserDataEvent>,
+    keepalive_interval: Duration,
+    reconnect_delay: Duration,
+}
+
+impl BinanceUserStream {
+    pub fn new(api_key: String) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_SIZE);
+        Self {
+            api_key,
+            rest_url: DEFAULT_REST_URL.to_string(),
+            ws_url: DEFAULT_WS_URL.to_string(),
+            client: reqwest::Client::new(),
+            events,
+            // Listen keys expire after 60 minutes without a keepalive
+            keepalive_interval: Duration::from_secs(30 * 60),
+            reconnect_delay: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_rest_url(mut self, url: String) -> Self {
+        self.rest_url = url;
+        self
+    }
+
+    pub fn with_ws_url(mut self, url: String) -> Self {
+        self.ws_url = url;
+        self
+    }
+
+    pub fn with_keepalive_interval(mut self, interval: Duration) -> Self {
+        self.keepalive_interval = interval;
+        self
+    }
+
+    pub fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<UserDataEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn create_listen_key(&self) -> Result<String> {
+        let url = format!("{}/api/v3/userDataStream", self.rest_url);
+        let response = self
+            .client
+            .post(&url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Exchange(format!(
+                "Failed to create listen key: {}",
+                error_text
+            )));
+        }
+
+        let data: ListenKeyResponse = response.json().await?;
+        Ok(data.listen_key)
+    }
+
+    pub async fn keepalive_listen_key(&self, listen_key: &str) -> Result<()> {
+        let url = format!("{}/api/v3/userDataStream?listenKey={}", self.rest_url, listen_key);
+        let response = self
+            .client
+            .put(&url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Exchange(format!(
+                "Failed to keep listen key alive: {}",
+                error_text
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub async fn close_listen_key(&self, listen_key: &str) -> Result<()> {
+        let url = format!("{}/api/v3/userDataStream?listenKey={}", self.rest_url, listen_key);
+        let response = self
+            .client
+            .delete(&url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Exchange(format!(
+                "Failed to close listen key: {}",
+                error_text
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Run the stream until the task is aborted, reconnecting after every disconnect
+    pub async fn run(&self) {
+        loop {
+            match self.connect_and_read().await {
+                Ok(()) => eprintln!("Binance user-data stream closed, reconnecting"),
+                Err(e) => eprintln!("Binance user-data stream error: {}, reconnecting", e),
+            }
+            tokio::time::sleep(self.reconnect_delay).await;
+        }
+    }
+
+    async fn connect_and_read(&self) -> Result<()> {
+        let listen_key = self.create_listen_key().await?;
+        let (mut socket, _) = connect_async(format!("{}/ws/{}", self.ws_url, listen_key))
+            .await
+            .map_err(|e| AppError::WebSocket(format!("Failed to connect: {}", e)))?;
+
+        let mut keepalive = interval_at(Instant::now() + self.keepalive_interval, self.keepalive_interval);
+
+        loop {
+            tokio::select! {
+                message = socket.next() => {
+                    let Some(message) = message else {
+                        return Ok(());
+                    };
+                    match message.map_err(|e| AppError::WebSocket(e.to_string()))? {
+                        Message::Text(text) => match parse_user_data_event(text.as_str()) {
+                            Ok(Some(event)) => {
+                                let _ = self.events.send(event);
+                            }
+                            Ok(None) => {}
+                            Err(e) => eprintln!("Failed to handle user-data message: {}", e),
+                        },
+                        Message::Close(_) => return Ok(()),
+                        _ => {}
+                    }
+                }
+                _ = keepalive.tick() => {
+                    self.keepalive_listen_key(&listen_key).await?;
+                }
+            }
+        }
+    }
+}
+
+/// Parse a user-data payload; events other than orders and balances are ignored
+fn parse_user_data_event(text: &str) -> Result<Option<UserDataEvent>> {
+    let data: serde_json::Value = serde_json::from_str(text)?;
+
+    match data["e"].as_str() {
+        Some("executionReport") => {
+            let report: ExecutionReport = serde_json::from_value(data)?;
+            let amount = parse_decimal(&report.quantity)?;
+            let filled = parse_decimal(&report.cumulative_filled)?;
+            let quote = parse_decimal(&report.cumulative_quote)?;
+            let limit_price = parse_decimal(&report.price)?;
+
+            // Prefer the average fill price; market orders report a zero limit price
+            let price = if filled > Decimal::ZERO && quote > Decimal::ZERO {
+                Some(quote / filled)
+            } else if limit_price > Decimal::ZERO {
+                Some(limit_price)
+            } else {
+                None
+            };
+
+            Ok(Some(UserDataEvent::OrderUpdate(Order {
+                id: report.order_id.to_string(),
+                symbol: report.symbol,
+                side: parse_trade_side(&report.side),
+                order_type: parse_order_type(&report.order_type),
+                status: parse_order_status(&report.status),
+                price,
+                amount,
+                filled,
+                remaining: amount - filled,
+                fee: None,
+                created_at: chrono::DateTime::from_timestamp_millis(report.created_time).unwrap_or_else(Utc::now),
+                updated_at: chrono::DateTime::from_timestamp_millis(report.event_time).unwrap_or_else(Utc::now),
+            })))
+        }
+        Some("outboundAccountPosition") => {
+            let position: AccountPosition = serde_json::from_value(data)?;
+            let balances = position
+                .balances
+                .into_iter()
+                .map(|b| {
+                    let free = parse_decimal(&b.free)?;
+                    let used = parse_decimal(&b.locked)?;
+                    Ok(Balance {
+                        currency: b.asset,
+                        total: free + used,
+                        free,
+                        used,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Some(UserDataEvent::BalanceUpdate(balances)))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn parse_decimal(value: &str) -> Result<Decimal> {
+    value
+        .parse()
+        .map_err(|e| AppError::Parse(format!("Invalid decimal {}: {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderStatus, OrderType, TradeSide};
+    use futures::SinkExt;
+    use mockito::{Matcher, Server};
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_hdr_async;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    const PARTIAL_FILL_REPORT: &str = r#"{
+        "e": "executionReport", "E": 1499405658658, "s": "BTCUSDT", "c": "mUvoqJxFIILMdfAW5iGSOW",
+        "S": "BUY", "o": "LIMIT", "f": "GTC", "q": "2.00000000", "p": "100.00000000",
+        "P": "0.00000000", "F": "0.00000000", "g": -1, "C": "", "x": "TRADE", "X": "PARTIALLY_FILLED",
+        "r": "NONE", "i": 4293153, "l": "0.50000000", "z": "0.50000000", "L": "99.00000000",
+        "n": "0.00050000", "N": "BTC", "T": 1499405658657, "t": 12, "I": 8641984, "w": false,
+        "m": true, "M": true, "O": 1499405658000, "Z": "49.50000000", "Y": "49.50000000", "Q": "0.00000000"
+    }"#;
+
+    const ACCOUNT_POSITION: &str = r#"{
+        "e": "outboundAccountPosition", "E": 1564034571105, "u": 1564034571073,
+        "B": [{"a": "USDT", "f": "150.50000000", "l": "50.00000000"}]
+    }"#;
+
+    #[test]
+    fn test_parse_execution_report() {
+        let event = parse_user_data_event(PARTIAL_FILL_REPORT).unwrap().unwrap();
+        let UserDataEvent::OrderUpdate(order) = event else {
+            panic!("expected order update");
+        };
+
+        assert_eq!(order.id, "4293153");
+        assert_eq!(order.side, TradeSide::Buy);
+        assert_eq!(order.order_type, OrderType::Limit);
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.filled, Decimal::from_str("0.5").unwrap());
+        assert_eq!(order.remaining, Decimal::from_str("1.5").unwrap());
+        assert_eq!(order.price, Some(Decimal::from(99)));
+    }
+
+    #[test]
+    fn test_parse_account_position() {
+        let event = parse_user_data_event(ACCOUNT_POSITION).unwrap().unwrap();
+        let UserDataEvent::BalanceUpdate(balances) = event else {
+            panic!("expected balance update");
+        };
+
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].currency, "USDT");
+        assert_eq!(balances[0].used, Decimal::from(50));
+        assert_eq!(balances[0].total, Decimal::from_str("200.5").unwrap());
+    }
+
+    #[test]
+    fn test_parse_ignores_other_events() {
+        let event = parse_user_data_event(r#"{"e": "listStatus", "E": 1}"#).unwrap();
+        assert!(event.is_none());
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)]
+    async fn test_user_stream_publishes_updates_and_keeps_alive() {
+        let mut server = Server::new_async().await;
+        let listen_key_mock = server
+            .mock("POST", "/api/v3/userDataStream")
+            .match_header("X-MBX-APIKEY", "test_key")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"listenKey": "pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1"}"#)
+            .create_async()
+            .await;
+        let keepalive_mock = server
+            .mock("PUT", "/api/v3/userDataStream")
+            .match_header("X-MBX-APIKEY", "test_key")
+            .match_query(Matcher::Regex("listenKey=pqia91ma".to_string()))
+            .with_status(200)
+            .with_body("{}")
+            .expect_at_least(1)
+            .create_async()
+            .await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requested_path = Arc::new(Mutex::new(String::new()));
+        let ws_server = {
+            let requested_path = requested_path.clone();
+            tokio::spawn(async move {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = accept_hdr_async(tcp, |req: &Request, resp: Response| {
+                    *requested_path.lock().unwrap() = req.uri().path().to_string();
+                    Ok(resp)
+                })
+                .await
+                .unwrap();
+                ws.send(Message::Text(PARTIAL_FILL_REPORT.into())).await.unwrap();
+                ws.send(Message::Text(ACCOUNT_POSITION.into())).await.unwrap();
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            })
+        };
+
+        let stream = Arc::new(
+            BinanceUserStream::new("test_key".to_string())
+                .with_rest_url(server.url())
+                .with_ws_url(format!("ws://{}", addr))
+                .with_keepalive_interval(Duration::from_millis(50)),
+        );
+        let mut events = stream.subscribe();
+        let runner = {
+            let stream = stream.clone();
+            tokio::spawn(async move { stream.run().await })
+        };
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, UserDataEvent::OrderUpdate(ref o) if o.id == "4293153"));
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, UserDataEvent::BalanceUpdate(ref b) if b[0].currency == "USDT"));
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        listen_key_mock.assert_async().await;
+        keepalive_mock.assert_async().await;
+        assert_eq!(
+            requested_path.lock().unwrap().as_str(),
+            "/ws/pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1"
+        );
+
+        runner.abort();
+        ws_server.abort();
+    }
+}
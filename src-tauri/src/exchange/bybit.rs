@@ -0,0 +1,884 @@
+use crate::error::{AppError, Result};
+use crate::exchange::Exchange;
+use crate::exchange::market::{Market, find_market};
+use crate::persistence::Repository;
+use crate::types::*;
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::Method;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// How long (ms) a signed request stays valid on Bybit's side
+const RECV_WINDOW: &str = "5000";
+
+/// Common envelope of every v5 response; `result` is only decoded once `retCode` is 0
+#[derive(Deserialize)]
+struct BybitResponse {
+    #[serde(rename = "retCode")]
+    ret_code: i64,
+    #[serde(rename = "retMsg")]
+    ret_msg: String,
+    #[serde(default)]
+    result: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct BybitList<T> {
+    list: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct BybitTicker {
+    symbol: String,
+    #[serde(rename = "lastPrice")]
+    last_price: String,
+    #[serde(rename = "volume24h", default)]
+    volume_24h: String,
+    /// 24h change as a fraction, e.g. "0.0123"
+    #[serde(rename = "price24hPcnt", default)]
+    price_24h_pcnt: String,
+}
+
+#[derive(Deserialize)]
+struct BybitWallet {
+    coin: Vec<BybitCoin>,
+}
+
+#[derive(Deserialize)]
+struct BybitCoin {
+    coin: String,
+    #[serde(rename = "walletBalance")]
+    wallet_balance: String,
+    #[serde(default)]
+    locked: String,
+}
+
+#[derive(Deserialize)]
+struct BybitOrderId {
+    #[serde(rename = "orderId")]
+    order_id: String,
+}
+
+#[derive(Deserialize)]
+struct BybitOrder {
+    #[serde(rename = "orderId")]
+    order_id: String,
+    symbol: String,
+    side: String,
+    #[serde(rename = "orderType")]
+    order_type: String,
+    #[serde(default)]
+    price: String,
+    qty: String,
+    #[serde(rename = "cumExecQty", default)]
+    cum_exec_qty: String,
+    #[serde(rename = "avgPrice", default)]
+    avg_price: String,
+    #[serde(rename = "cumExecFee", default)]
+    cum_exec_fee: String,
+    #[serde(rename = "orderStatus")]
+    order_status: String,
+    #[serde(rename = "createdTime")]
+    created_time: String,
+    #[serde(rename = "updatedTime")]
+    updated_time: String,
+}
+
+impl BybitOrder {
+    fn into_order(self) -> Order {
+        let amount: Decimal = self.qty.parse().unwrap_or(Decimal::ZERO);
+        let filled: Decimal = self.cum_exec_qty.parse().unwrap_or(Decimal::ZERO);
+        let avg_price: Decimal = self.avg_price.parse().unwrap_or(Decimal::ZERO);
+        let limit_price: Decimal = self.price.parse().unwrap_or(Decimal::ZERO);
+        // Prefer the average fill price; market orders report a zero limit price
+        let price = [avg_price, limit_price].into_iter().find(|p| *p > Decimal::ZERO);
+        let status = parse_order_status(&self.order_status);
+
+        Order {
+            id: self.order_id,
+            symbol: self.symbol,
+            side: parse_trade_side(&self.side),
+            order_type: parse_order_type(&self.order_type),
+            status,
+            price,
+            amount,
+            filled,
+            remaining: if status == OrderStatus::Filled {
+                Decimal::ZERO
+            } else {
+                amount - filled
+            },
+            fee: self.cum_exec_fee.parse().ok(),
+            created_at: parse_millis(&self.created_time),
+            updated_at: parse_millis(&self.updated_time),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BybitInstrument {
+    symbol: String,
+    #[serde(rename = "baseCoin")]
+    base_coin: String,
+    #[serde(rename = "quoteCoin")]
+    quote_coin: String,
+    status: String,
+    #[serde(rename = "lotSizeFilter")]
+    lot_size_filter: serde_json::Value,
+    #[serde(rename = "priceFilter")]
+    price_filter: serde_json::Value,
+}
+
+impl BybitInstrument {
+    fn into_market(self) -> Market {
+        let decimal = |filter: &serde_json::Value, field: &str| filter[field].as_str().and_then(|v| v.parse().ok());
+
+        Market {
+            symbol: format!("{}/{}", self.base_coin, self.quote_coin),
+            amount_step: decimal(&self.lot_size_filter, "basePrecision"),
+            price_tick: decimal(&self.price_filter, "tickSize"),
+            min_amount: decimal(&self.lot_size_filter, "minOrderQty"),
+            max_amount: decimal(&self.lot_size_filter, "maxOrderQty"),
+            min_notional: decimal(&self.lot_size_filter, "minOrderAmt"),
+            id: self.symbol,
+            base: self.base_coin,
+            quote: self.quote_coin,
+            active: self.status == "Trading",
+        }
+    }
+}
+
+/// Bybit spot trading through the v5 unified API.
+///
+/// Requests are signed with the `X-BAPI-*` headers. Bybit needs the symbol to cancel an order,
+/// so ids of orders placed through this instance are remembered together with their symbol.
+/// Orders placed before a restart are looked up in the repository, if one is set.
+pub struct BybitExchange {
+    api_key: String,
+    api_secret: String,
+    base_url: String,
+    client: reqwest::Client,
+    markets: RwLock<Option<HashMap<String, Market>>>,
+    order_symbols: RwLock<HashMap<String, String>>,
+    repository: Option<Arc<Repository>>,
+}
+
+impl BybitExchange {
+    pub fn new(api_key: String, api_secret: String) -> Self {
+        Self {
+            api_key,
+            api_secret,
+            base_url: "https://api.bybit.com".to_string(),
+            client: reqwest::Client::new(),
+            markets: RwLock::new(None),
+            order_symbols: RwLock::new(HashMap::new()),
+            repository: None,
+        }
+    }
+
+    /// Point the client at another REST endpoint, e.g. "https://api-testnet.bybit.com"
+    pub fn with_base_url(mut self, url: String) -> Self {
+        self.base_url = url;
+        self
+    }
+
+    /// Resolve the symbol of orders this instance did not place from the stored orders
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Send a request and unwrap the v5 envelope.
+    /// `payload` is the query string for GET requests and the JSON body otherwise.
+    async fn send<T: DeserializeOwned>(&self, method: Method, path: &str, payload: String, signed: bool) -> Result<T> {
+        let is_get = method == Method::GET;
+        let url = if is_get && !payload.is_empty() {
+            format!("{}{}?{}", self.base_url, path, payload)
+        } else {
+            format!("{}{}", self.base_url, path)
+        };
+
+        let mut request = self.client.request(method, &url);
+        if signed {
+            let timestamp = Utc::now().timestamp_millis().to_string();
+            let signature = sign_request(&self.api_secret, &timestamp, &self.api_key, &payload);
+            request = request
+                .header("X-BAPI-API-KEY", &self.api_key)
+                .header("X-BAPI-TIMESTAMP", timestamp)
+                .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+                .header("X-BAPI-SIGN", signature);
+        }
+        if !is_get {
+            request = request.header("Content-Type", "application/json").body(payload);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(AppError::Exchange(format!("Bybit error: {}", body)));
+        }
+
+        let data: BybitResponse = serde_json::from_str(&body)?;
+        if data.ret_code != 0 {
+            return Err(AppError::Exchange(format!(
+                "Bybit error {}: {}",
+                data.ret_code, data.ret_msg
+            )));
+        }
+        Ok(serde_json::from_value(data.result)?)
+    }
+
+    async fn order_symbol(&self, order_id: &str) -> Result<String> {
+        if let Some(symbol) = self.order_symbols.read().await.get(order_id) {
+            return Ok(symbol.clone());
+        }
+        if let Some(repository) = &self.repository
+            && let Some(symbol) = repository.get_order_symbol(order_id).await?
+        {
+            return Ok(symbol);
+        }
+        Err(AppError::NotFound(format!("Unknown Bybit order {}", order_id)))
+    }
+
+    async fn query_orders(&self, query: String) -> Result<Vec<Order>> {
+        let orders: BybitList<BybitOrder> = self.send(Method::GET, "/v5/order/realtime", query, true).await?;
+        Ok(orders.list.into_iter().map(BybitOrder::into_order).collect())
+    }
+
+    async fn apply_market_filters(&self, order: OrderRequest) -> Result<OrderRequest> {
+        let markets = self.load_markets().await?;
+        let Some(market) = find_market(&markets, &order.symbol) else {
+            return Ok(order);
+        };
+
+        let reference_price = match order.price {
+            Some(price) => price,
+            None if market.min_notional.is_some() => self.fetch_ticker(&market.id).await?.price,
+            None => Decimal::ZERO,
+        };
+        market.prepare_order(order, reference_price)
+    }
+}
+
+/// HMAC-SHA256 over `timestamp + api_key + recv_window + payload`, hex encoded
+fn sign_request(api_secret: &str, timestamp: &str, api_key: &str, payload: &str) -> String {
+    type HmacSha256 = Hmac<Sha256>;
+    let mut mac = HmacSha256::new_from_slice(api_secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(api_key.as_bytes());
+    mac.update(RECV_WINDOW.as_bytes());
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Bybit uses concatenated symbols ("BTCUSDT"); accept unified ones ("BTC/USDT") as well
+fn bybit_symbol(symbol: &str) -> String {
+    symbol.replace('/', "")
+}
+
+/// Map our timeframe strings onto Bybit kline intervals
+fn bybit_interval(timeframe: &str) -> Result<&'static str> {
+    Ok(match timeframe {
+        "1m" => "1",
+        "3m" => "3",
+        "5m" => "5",
+        "15m" => "15",
+        "30m" => "30",
+        "1h" => "60",
+        "2h" => "120",
+        "4h" => "240",
+        "6h" => "360",
+        "12h" => "720",
+        "1d" => "D",
+        "1w" => "W",
+        "1M" => "M",
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unsupported Bybit timeframe: {}",
+                timeframe
+            )));
+        }
+    })
+}
+
+fn parse_millis(value: &str) -> chrono::DateTime<Utc> {
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .unwrap_or_else(Utc::now)
+}
+
+/// Helper function to parse Bybit order status string to OrderStatus enum
+fn parse_order_status(status: &str) -> OrderStatus {
+    match status {
+        "New" | "Untriggered" | "Triggered" | "Created" => OrderStatus::New,
+        "PartiallyFilled" => OrderStatus::PartiallyFilled,
+        "Filled" => OrderStatus::Filled,
+        "Cancelled" | "PartiallyFilledCanceled" => OrderStatus::Canceled,
+        "Rejected" => OrderStatus::Rejected,
+        "Deactivated" => OrderStatus::Expired,
+        _ => OrderStatus::New,
+    }
+}
+
+/// Helper function to parse Bybit trade side string to TradeSide enum
+fn parse_trade_side(side: &str) -> TradeSide {
+    match side {
+        "Sell" => TradeSide::Sell,
+        _ => TradeSide::Buy,
+    }
+}
+
+/// Helper function to parse Bybit order type string to OrderType enum
+fn parse_order_type(order_type: &str) -> OrderType {
+    match order_type {
+        "Limit" => OrderType::Limit,
+        _ => OrderType::Market,
+    }
+}
+
+#[async_trait]
+impl Exchange for BybitExchange {
+    async fn fetch_ticker(&self, symbol: &str) -> Result<Ticker> {
+        let query = format!("category=spot&symbol={}", bybit_symbol(symbol));
+        let tickers: BybitList<BybitTicker> = self.send(Method::GET, "/v5/market/tickers", query, false).await?;
+        let ticker = tickers
+            .list
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound(format!("No Bybit ticker for {}", symbol)))?;
+
+        let change: Decimal = ticker.price_24h_pcnt.parse().unwrap_or(Decimal::ZERO);
+        Ok(Ticker {
+            symbol: ticker.symbol,
+            price: ticker.last_price.parse().unwrap_or(Decimal::ZERO),
+            volume_24h: ticker.volume_24h.parse().unwrap_or(Decimal::ZERO),
+            // Bybit reports a fraction, Ticker uses percent like Binance
+            change_24h: change * Decimal::ONE_HUNDRED,
+        })
+    }
+
+    async fn fetch_ohlcv(&self, symbol: &str, timeframe: &str, limit: usize) -> Result<Vec<OHLCV>> {
+        let query = format!(
+            "category=spot&symbol={}&interval={}&limit={}",
+            bybit_symbol(symbol),
+            bybit_interval(timeframe)?,
+            limit
+        );
+        let data: BybitList<Vec<String>> = self.send(Method::GET, "/v5/market/kline", query, false).await?;
+
+        // Bybit returns the newest candle first
+        let mut klines: Vec<OHLCV> = data
+            .list
+            .iter()
+            .filter(|k| k.len() >= 6)
+            .map(|k| {
+                let field = |i: usize| k[i].parse().unwrap_or(Decimal::ZERO);
+                OHLCV {
+                    timestamp: parse_millis(&k[0]),
+                    open: field(1),
+                    high: field(2),
+                    low: field(3),
+                    close: field(4),
+                    volume: field(5),
+                }
+            })
+            .collect();
+        klines.sort_by_key(|k| k.timestamp);
+        Ok(klines)
+    }
+
+    async fn fetch_balance(&self) -> Result<Balance> {
+        let balances = self.fetch_balances().await?;
+        Ok(balances
+            .into_iter()
+            .find(|b| b.currency == "USDT")
+            .unwrap_or_else(|| Balance {
+                currency: "USDT".to_string(),
+                total: Decimal::ZERO,
+                free: Decimal::ZERO,
+                used: Decimal::ZERO,
+            }))
+    }
+
+    async fn fetch_balances(&self) -> Result<Vec<Balance>> {
+        let wallets: BybitList<BybitWallet> = self
+            .send(
+                Method::GET,
+                "/v5/account/wallet-balance",
+                "accountType=UNIFIED".to_string(),
+                true,
+            )
+            .await?;
+
+        Ok(wallets
+            .list
+            .into_iter()
+            .flat_map(|w| w.coin)
+            .map(|c| {
+                let total: Decimal = c.wallet_balance.parse().unwrap_or(Decimal::ZERO);
+                let locked: Decimal = c.locked.parse().unwrap_or(Decimal::ZERO);
+                Balance {
+                    currency: c.coin,
+                    total,
+                    free: total - locked,
+                    used: locked,
+                }
+            })
+            .filter(|b| b.total > Decimal::ZERO)
+            .collect())
+    }
+
+    async fn fetch_positions(&self) -> Result<Vec<Position>> {
+        // Spot accounts hold balances, not positions
+        Ok(vec![])
+    }
+
+    async fn create_order(&self, order: OrderRequest) -> Result<Order> {
+        let order = self.apply_market_filters(order).await?;
+        let symbol = bybit_symbol(&order.symbol);
+
+        let order_type = match order.order_type {
+            OrderType::Market => "Market",
+            OrderType::Limit => "Limit",
+            OrderType::StopLimit | OrderType::StopMarket => {
+                return Err(AppError::InvalidInput(
+                    "Bybit spot stop orders need a trigger price and are not supported".to_string(),
+                ));
+            }
+        };
+        let side = match order.side {
+            TradeSide::Buy => "Buy",
+            TradeSide::Sell => "Sell",
+        };
+
+        let mut body = serde_json::json!({
+            "category": "spot",
+            "symbol": symbol,
+            "side": side,
+            "orderType": order_type,
+            "qty": order.amount.to_string(),
+            // Market buys are sized in quote currency unless told otherwise
+            "marketUnit": "baseCoin",
+        });
+        if order.order_type == OrderType::Limit {
+            let price = order
+                .price
+                .ok_or_else(|| AppError::Exchange("Limit order requires price".to_string()))?;
+            body["price"] = serde_json::Value::String(price.to_string());
+            body["timeInForce"] = serde_json::Value::String("GTC".to_string());
+        }
+
+        let created: BybitOrderId = self
+            .send(Method::POST, "/v5/order/create", body.to_string(), true)
+            .await?;
+        self.order_symbols
+            .write()
+            .await
+            .insert(created.order_id.clone(), symbol.clone());
+
+        // Bybit only acknowledges the order; read back its state
+        match self.fetch_order(&created.order_id).await {
+            Ok(placed) => Ok(placed),
+            Err(_) => {
+                let now = Utc::now();
+                Ok(Order {
+                    id: created.order_id,
+                    symbol,
+                    side: order.side,
+                    order_type: order.order_type,
+                    status: OrderStatus::New,
+                    price: order.price,
+                    amount: order.amount,
+                    filled: Decimal::ZERO,
+                    remaining: order.amount,
+                    fee: None,
+                    created_at: now,
+                    updated_at: now,
+                })
+            }
+        }
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "category": "spot",
+            "symbol": self.order_symbol(order_id).await?,
+            "orderId": order_id,
+        });
+        let _: BybitOrderId = self
+            .send(Method::POST, "/v5/order/cancel", body.to_string(), true)
+            .await?;
+        Ok(())
+    }
+
+    async fn fetch_order(&self, order_id: &str) -> Result<Order> {
+        let query = format!("category=spot&orderId={}", order_id);
+        if let Some(order) = self.query_orders(query.clone()).await?.into_iter().next() {
+            return Ok(order);
+        }
+
+        // Closed orders drop out of the realtime endpoint
+        let history: BybitList<BybitOrder> = self.send(Method::GET, "/v5/order/history", query, true).await?;
+        history
+            .list
+            .into_iter()
+            .next()
+            .map(BybitOrder::into_order)
+            .ok_or_else(|| AppError::NotFound(format!("Bybit order {} not found", order_id)))
+    }
+
+    async fn fetch_orders(&self, symbol: &str) -> Result<Vec<Order>> {
+        // Open orders of the symbol
+        self.query_orders(format!("category=spot&symbol={}", bybit_symbol(symbol)))
+            .await
+    }
+
+    fn get_name(&self) -> &str {
+        "bybit"
+    }
+
+    async fn fetch_markets(&self) -> Result<Vec<Market>> {
+        let instruments: BybitList<BybitInstrument> = self
+            .send(
+                Method::GET,
+                "/v5/market/instruments-info",
+                "category=spot".to_string(),
+                false,
+            )
+            .await?;
+        Ok(instruments.list.into_iter().map(BybitInstrument::into_market).collect())
+    }
+
+    async fn load_markets(&self) -> Result<HashMap<String, Market>> {
+        if let Some(markets) = self.markets.read().await.as_ref() {
+            return Ok(markets.clone());
+        }
+
+        let markets: HashMap<String, Market> = self
+            .fetch_markets()
+            .await?
+            .into_iter()
+            .map(|m| (m.symbol.clone(), m))
+            .collect();
+        *self.markets.write().await = Some(markets.clone());
+        Ok(markets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server, ServerGuard};
+    use std::str::FromStr;
+
+    const INSTRUMENTS: &str = r#"{
+        "retCode": 0, "retMsg": "OK",
+        "result": {"category": "spot", "list": [{
+            "symbol": "BTCUSDT", "baseCoin": "BTC", "quoteCoin": "USDT", "status": "Trading",
+            "lotSizeFilter": {"basePrecision": "0.000001", "quotePrecision": "0.00000001",
+                              "minOrderQty": "0.000048", "maxOrderQty": "71.73956243", "minOrderAmt": "1"},
+            "priceFilter": {"tickSize": "0.01"}
+        }]}
+    }"#;
+
+    const OPEN_ORDER: &str = r#"{
+        "retCode": 0, "retMsg": "OK",
+        "result": {"category": "spot", "list": [{
+            "orderId": "1523347543495541248", "symbol": "BTCUSDT", "side": "Buy", "orderType": "Limit",
+            "price": "30000.00", "qty": "0.010000", "cumExecQty": "0.004000", "avgPrice": "30000",
+            "cumExecFee": "0.000004", "orderStatus": "PartiallyFilled",
+            "createdTime": "1700000000000", "updatedTime": "1700000060000"
+        }]}
+    }"#;
+
+    fn exchange(url: String) -> BybitExchange {
+        BybitExchange::new("test_key".to_string(), "test_secret".to_string()).with_base_url(url)
+    }
+
+    async fn mock_instruments(server: &mut ServerGuard) -> mockito::Mock {
+        server
+            .mock("GET", "/v5/market/instruments-info")
+            .match_query(Matcher::UrlEncoded("category".into(), "spot".into()))
+            .with_status(200)
+            .with_body(INSTRUMENTS)
+            .create_async()
+            .await
+    }
+
+    #[test]
+    fn test_sign_request() {
+        let signature = sign_request(
+            "test_secret",
+            "1700000000000",
+            "test_key",
+            "category=spot&symbol=BTCUSDT",
+        );
+        assert_eq!(
+            signature,
+            "a04c472e89f841b28f950a4fe5b68224b8bb8468dfe801a00eb308813c03ad50"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_ticker_and_klines() {
+        let mut server = Server::new_async().await;
+        let ticker_mock = server
+            .mock("GET", "/v5/market/tickers")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("category".into(), "spot".into()),
+                Matcher::UrlEncoded("symbol".into(), "BTCUSDT".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{"retCode": 0, "retMsg": "OK", "result": {"category": "spot", "list": [
+                    {"symbol": "BTCUSDT", "lastPrice": "42000.50", "volume24h": "1234.5", "price24hPcnt": "0.0125"}
+                ]}}"#,
+            )
+            .create_async()
+            .await;
+        let kline_mock = server
+            .mock("GET", "/v5/market/kline")
+            .match_query(Matcher::UrlEncoded("interval".into(), "60".into()))
+            .with_status(200)
+            .with_body(
+                r#"{"retCode": 0, "retMsg": "OK", "result": {"symbol": "BTCUSDT", "category": "spot", "list": [
+                    ["1700003600000", "101", "103", "100", "102", "5", "510"],
+                    ["1700000000000", "100", "102", "99", "101", "4", "404"]
+                ]}}"#,
+            )
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        let ticker = exchange.fetch_ticker("BTC/USDT").await.unwrap();
+        let klines = exchange.fetch_ohlcv("BTCUSDT", "1h", 2).await.unwrap();
+
+        ticker_mock.assert_async().await;
+        kline_mock.assert_async().await;
+        assert_eq!(ticker.price, Decimal::from_str("42000.50").unwrap());
+        assert_eq!(ticker.change_24h, Decimal::from_str("1.25").unwrap());
+        assert_eq!(klines.len(), 2);
+        assert_eq!(klines[0].close, Decimal::from(101));
+        assert_eq!(klines[1].close, Decimal::from(102));
+        assert!(matches!(
+            exchange.fetch_ohlcv("BTCUSDT", "7m", 2).await,
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_balances_signed() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v5/account/wallet-balance")
+            .match_query(Matcher::UrlEncoded("accountType".into(), "UNIFIED".into()))
+            .match_header("X-BAPI-API-KEY", "test_key")
+            .match_header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .match_header("X-BAPI-SIGN", Matcher::Regex("^[0-9a-f]{64}$".to_string()))
+            .match_header("X-BAPI-TIMESTAMP", Matcher::Regex("^[0-9]{13}$".to_string()))
+            .with_status(200)
+            .with_body(
+                r#"{"retCode": 0, "retMsg": "OK", "result": {"list": [{"accountType": "UNIFIED", "coin": [
+                    {"coin": "USDT", "walletBalance": "1000.5", "locked": "200"},
+                    {"coin": "BTC", "walletBalance": "0.5", "locked": "0"},
+                    {"coin": "ETH", "walletBalance": "0", "locked": "0"}
+                ]}]}}"#,
+            )
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        let balances = exchange.fetch_balances().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(balances.len(), 2);
+        let usdt = exchange.fetch_balance().await.unwrap();
+        assert_eq!(usdt.total, Decimal::from_str("1000.5").unwrap());
+        assert_eq!(usdt.free, Decimal::from_str("800.5").unwrap());
+        assert_eq!(usdt.used, Decimal::from(200));
+    }
+
+    #[tokio::test]
+    async fn test_create_fetch_and_cancel_order() {
+        let mut server = Server::new_async().await;
+        let _instruments = mock_instruments(&mut server).await;
+        let create_mock = server
+            .mock("POST", "/v5/order/create")
+            .match_header("X-BAPI-API-KEY", "test_key")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "category": "spot",
+                "symbol": "BTCUSDT",
+                "side": "Buy",
+                "orderType": "Limit",
+                "qty": "0.01",
+                "price": "30000",
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"retCode": 0, "retMsg": "OK", "result": {"orderId": "1523347543495541248", "orderLinkId": ""}}"#,
+            )
+            .create_async()
+            .await;
+        let query_mock = server
+            .mock("GET", "/v5/order/realtime")
+            .match_query(Matcher::UrlEncoded("orderId".into(), "1523347543495541248".into()))
+            .with_status(200)
+            .with_body(OPEN_ORDER)
+            .expect(1)
+            .create_async()
+            .await;
+        let cancel_mock = server
+            .mock("POST", "/v5/order/cancel")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "symbol": "BTCUSDT",
+                "orderId": "1523347543495541248",
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"retCode": 0, "retMsg": "OK", "result": {"orderId": "1523347543495541248", "orderLinkId": ""}}"#,
+            )
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Limit,
+                amount: Decimal::from_str("0.0100004").unwrap(),
+                price: Some(Decimal::from_str("30000.001").unwrap()),
+            })
+            .await
+            .unwrap();
+        exchange.cancel_order(&order.id).await.unwrap();
+
+        create_mock.assert_async().await;
+        query_mock.assert_async().await;
+        cancel_mock.assert_async().await;
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.order_type, OrderType::Limit);
+        assert_eq!(order.filled, Decimal::from_str("0.004").unwrap());
+        assert_eq!(order.remaining, Decimal::from_str("0.006").unwrap());
+        assert!(matches!(
+            exchange.cancel_order("unknown").await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_order_from_history_and_error_code() {
+        let mut server = Server::new_async().await;
+        let _realtime = server
+            .mock("GET", "/v5/order/realtime")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"retCode": 0, "retMsg": "OK", "result": {"category": "spot", "list": []}}"#)
+            .create_async()
+            .await;
+        let _history = server
+            .mock("GET", "/v5/order/history")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"retCode": 0, "retMsg": "OK", "result": {"category": "spot", "list": [{
+                    "orderId": "42", "symbol": "BTCUSDT", "side": "Sell", "orderType": "Market",
+                    "price": "0", "qty": "0.5", "cumExecQty": "0.5", "avgPrice": "41000",
+                    "cumExecFee": "0.01", "orderStatus": "Filled",
+                    "createdTime": "1700000000000", "updatedTime": "1700000000500"
+                }]}}"#,
+            )
+            .create_async()
+            .await;
+        let _create = server
+            .mock("POST", "/v5/order/create")
+            .with_status(200)
+            .with_body(r#"{"retCode": 170131, "retMsg": "Insufficient balance.", "result": {}}"#)
+            .create_async()
+            .await;
+        let _instruments = server
+            .mock("GET", "/v5/market/instruments-info")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"retCode": 0, "retMsg": "OK", "result": {"category": "spot", "list": []}}"#)
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        let order = exchange.fetch_order("42").await.unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.side, TradeSide::Sell);
+        assert_eq!(order.price, Some(Decimal::from(41000)));
+        assert_eq!(order.remaining, Decimal::ZERO);
+
+        let err = exchange
+            .create_order(OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Market,
+                amount: Decimal::ONE,
+                price: None,
+            })
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("170131"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_placed_before_restart() {
+        let mut server = Server::new_async().await;
+        let cancel_mock = server
+            .mock("POST", "/v5/order/cancel")
+            .match_body(Matcher::PartialJson(
+                serde_json::json!({"symbol": "ETHUSDT", "orderId": "4102"}),
+            ))
+            .with_status(200)
+            .with_body(r#"{"retCode": 0, "retMsg": "OK", "result": {"orderId": "4102", "orderLinkId": ""}}"#)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Arc::new(Repository::new(dir.path().join("test.db")).await.unwrap());
+        let order = Order {
+            id: "4102".to_string(),
+            symbol: "ETHUSDT".to_string(),
+            side: TradeSide::Buy,
+            order_type: OrderType::Limit,
+            status: OrderStatus::New,
+            price: Some(Decimal::from(2000)),
+            amount: Decimal::ONE,
+            filled: Decimal::ZERO,
+            remaining: Decimal::ONE,
+            fee: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        repository.save_order(&order, None).await.unwrap();
+
+        // A fresh instance has not placed the order itself
+        let exchange = exchange(server.url()).with_repository(repository);
+        exchange.cancel_order("4102").await.unwrap();
+        cancel_mock.assert_async().await;
+        assert!(matches!(exchange.cancel_order("999").await, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        assert_eq!(parse_order_status("PartiallyFilledCanceled"), OrderStatus::Canceled);
+        assert_eq!(parse_order_status("Deactivated"), OrderStatus::Expired);
+        assert_eq!(parse_order_status("Untriggered"), OrderStatus::New);
+        assert_eq!(parse_order_type("Limit"), OrderType::Limit);
+        assert_eq!(bybit_symbol("BTC/USDT"), "BTCUSDT");
+    }
+}
@@ -0,0 +1,164 @@
+use crate::error::{AppError, Result};
+use crate::types::OrderRequest;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Trading rules for a single market as published by the exchange
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Market {
+    /// Unified symbol, e.g. "BTC/USDT"
+    pub symbol: String,
+    /// Exchange-native symbol, e.g. "BTCUSDT"
+    pub id: String,
+    pub base: String,
+    pub quote: String,
+    pub active: bool,
+    /// Amount increment (LOT_SIZE stepSize)
+    pub amount_step: Option<Decimal>,
+    /// Price increment (PRICE_FILTER tickSize)
+    pub price_tick: Option<Decimal>,
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+    /// Minimum order value in quote currency (MIN_NOTIONAL / NOTIONAL)
+    pub min_notional: Option<Decimal>,
+}
+
+impl Market {
+    /// Round an amount down to the step size so the order never exceeds the requested size
+    pub fn amount_to_precision(&self, amount: Decimal) -> Decimal {
+        match self.amount_step {
+            Some(step) if step > Decimal::ZERO => ((amount / step).floor() * step).normalize(),
+            _ => amount,
+        }
+    }
+
+    /// Round a price to the nearest tick
+    pub fn price_to_precision(&self, price: Decimal) -> Decimal {
+        match self.price_tick {
+            Some(tick) if tick > Decimal::ZERO => ((price / tick).round() * tick).normalize(),
+            _ => price,
+        }
+    }
+
+    /// Check amount and notional limits for an order at `price`
+    pub fn validate_order(&self, amount: Decimal, price: Decimal) -> Result<()> {
+        if !self.active {
+            return Err(AppError::InvalidInput(format!("Market {} is not active", self.symbol)));
+        }
+        if amount <= Decimal::ZERO {
+            return Err(AppError::InvalidInput(format!(
+                "Order amount for {} rounds to zero",
+                self.symbol
+            )));
+        }
+        if let Some(min_amount) = self.min_amount
+            && amount < min_amount
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Order amount {} below minimum {} for {}",
+                amount, min_amount, self.symbol
+            )));
+        }
+        if let Some(max_amount) = self.max_amount
+            && max_amount > Decimal::ZERO
+            && amount > max_amount
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Order amount {} above maximum {} for {}",
+                amount, max_amount, self.symbol
+            )));
+        }
+        if let Some(min_notional) = self.min_notional
+            && price > Decimal::ZERO
+            && amount * price < min_notional
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Order value {} below minimum notional {} for {}",
+                amount * price,
+                min_notional,
+                self.symbol
+            )));
+        }
+        Ok(())
+    }
+
+    /// Round an order to the market precision and validate it.
+    /// `reference_price` is used for the notional check of orders without a limit price.
+    pub fn prepare_order(&self, mut order: OrderRequest, reference_price: Decimal) -> Result<OrderRequest> {
+        order.amount = self.amount_to_precision(order.amount);
+        order.price = order.price.map(|p| self.price_to_precision(p));
+        self.validate_order(order.amount, order.price.unwrap_or(reference_price))?;
+        Ok(order)
+    }
+}
+
+/// Look up a market by unified symbol ("BTC/USDT") or exchange id ("BTCUSDT")
+pub fn find_market<'a>(markets: &'a HashMap<String, Market>, symbol: &str) -> Option<&'a Market> {
+    markets
+        .get(symbol)
+        .or_else(|| markets.values().find(|m| m.id == symbol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderType, TradeSide};
+    use std::str::FromStr;
+
+    fn btc_usdt() -> Market {
+        Market {
+            symbol: "BTC/USDT".to_string(),
+            id: "BTCUSDT".to_string(),
+            base: "BTC".to_string(),
+            quote: "USDT".to_string(),
+            active: true,
+            amount_step: Some(Decimal::from_str("0.00001").unwrap()),
+            price_tick: Some(Decimal::from_str("0.01").unwrap()),
+            min_amount: Some(Decimal::from_str("0.00001").unwrap()),
+            max_amount: Some(Decimal::from(9000)),
+            min_notional: Some(Decimal::from(5)),
+        }
+    }
+
+    #[test]
+    fn test_precision_rounding() {
+        let market = btc_usdt();
+        assert_eq!(
+            market.amount_to_precision(Decimal::from_str("0.0012345678").unwrap()),
+            Decimal::from_str("0.00123").unwrap()
+        );
+        assert_eq!(
+            market.price_to_precision(Decimal::from_str("43210.126").unwrap()),
+            Decimal::from_str("43210.13").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_prepare_order_rejects_below_min_notional() {
+        let market = btc_usdt();
+        let order = OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: TradeSide::Buy,
+            order_type: OrderType::Market,
+            amount: Decimal::from_str("0.0000999").unwrap(),
+            price: None,
+        };
+
+        // 0.00009 * 50000 = 4.5 < 5
+        let result = market.prepare_order(order.clone(), Decimal::from(50000));
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
+
+        let prepared = market.prepare_order(order, Decimal::from(60000)).unwrap();
+        assert_eq!(prepared.amount, Decimal::from_str("0.00009").unwrap());
+    }
+
+    #[test]
+    fn test_find_market_by_id_or_symbol() {
+        let market = btc_usdt();
+        let markets = HashMap::from([(market.symbol.clone(), market)]);
+        assert!(find_market(&markets, "BTC/USDT").is_some());
+        assert!(find_market(&markets, "BTCUSDT").is_some());
+        assert!(find_market(&markets, "ETHUSDT").is_none());
+    }
+}
@@ -1,4 +1,11 @@
 pub mod binance;
+pub mod bybit;
+pub mod market;
+pub mod okx;
+pub mod paper;
+pub mod registry;
+pub mod replay;
 pub mod traits;
 
+pub use registry::{ExchangeContext, ExchangeRegistry};
 pub use traits::Exchange;
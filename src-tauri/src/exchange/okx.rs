@@ -0,0 +1,857 @@
+use crate::error::{AppError, Result};
+use crate::exchange::Exchange;
+use crate::exchange::market::{Market, find_market};
+use crate::persistence::Repository;
+use crate::types::*;
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Method;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Common envelope of every v5 response; `data` is only decoded once `code` is "0"
+#[derive(Deserialize)]
+struct OkxResponse {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct OkxTicker {
+    #[serde(rename = "instId")]
+    inst_id: String,
+    last: String,
+    #[serde(rename = "vol24h", default)]
+    vol_24h: String,
+    #[serde(rename = "open24h", default)]
+    open_24h: String,
+}
+
+#[derive(Deserialize)]
+struct OkxAccount {
+    details: Vec<OkxBalanceDetail>,
+}
+
+#[derive(Deserialize)]
+struct OkxBalanceDetail {
+    ccy: String,
+    #[serde(rename = "cashBal", default)]
+    cash_bal: String,
+    #[serde(rename = "availBal", default)]
+    avail_bal: String,
+    #[serde(rename = "frozenBal", default)]
+    frozen_bal: String,
+}
+
+/// Per-order result of place/cancel requests
+#[derive(Deserialize)]
+struct OkxOrderAck {
+    #[serde(rename = "ordId")]
+    ord_id: String,
+    #[serde(rename = "sCode")]
+    s_code: String,
+    #[serde(rename = "sMsg", default)]
+    s_msg: String,
+}
+
+impl OkxOrderAck {
+    fn check(self) -> Result<String> {
+        if self.s_code != "0" {
+            return Err(AppError::Exchange(format!("OKX error {}: {}", self.s_code, self.s_msg)));
+        }
+        Ok(self.ord_id)
+    }
+}
+
+#[derive(Deserialize)]
+struct OkxOrder {
+    #[serde(rename = "instId")]
+    inst_id: String,
+    #[serde(rename = "ordId")]
+    ord_id: String,
+    side: String,
+    #[serde(rename = "ordType")]
+    ord_type: String,
+    #[serde(default)]
+    px: String,
+    sz: String,
+    #[serde(rename = "accFillSz", default)]
+    acc_fill_sz: String,
+    #[serde(rename = "avgPx", default)]
+    avg_px: String,
+    /// Fees are reported as negative numbers
+    #[serde(default)]
+    fee: String,
+    state: String,
+    #[serde(rename = "cTime")]
+    c_time: String,
+    #[serde(rename = "uTime")]
+    u_time: String,
+}
+
+impl OkxOrder {
+    fn into_order(self) -> Order {
+        let amount: Decimal = self.sz.parse().unwrap_or(Decimal::ZERO);
+        let filled: Decimal = self.acc_fill_sz.parse().unwrap_or(Decimal::ZERO);
+        let avg_price: Decimal = self.avg_px.parse().unwrap_or(Decimal::ZERO);
+        let limit_price: Decimal = self.px.parse().unwrap_or(Decimal::ZERO);
+        // Prefer the average fill price; market orders have no limit price
+        let price = [avg_price, limit_price].into_iter().find(|p| *p > Decimal::ZERO);
+
+        Order {
+            id: self.ord_id,
+            symbol: self.inst_id,
+            side: parse_trade_side(&self.side),
+            order_type: parse_order_type(&self.ord_type),
+            status: parse_order_status(&self.state),
+            price,
+            amount,
+            filled,
+            remaining: amount - filled,
+            fee: self.fee.parse::<Decimal>().ok().map(|f| f.abs()),
+            created_at: parse_millis(&self.c_time),
+            updated_at: parse_millis(&self.u_time),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OkxInstrument {
+    #[serde(rename = "instId")]
+    inst_id: String,
+    #[serde(rename = "baseCcy")]
+    base_ccy: String,
+    #[serde(rename = "quoteCcy")]
+    quote_ccy: String,
+    state: String,
+    #[serde(rename = "lotSz", default)]
+    lot_sz: String,
+    #[serde(rename = "tickSz", default)]
+    tick_sz: String,
+    #[serde(rename = "minSz", default)]
+    min_sz: String,
+    #[serde(rename = "maxLmtSz", default)]
+    max_lmt_sz: String,
+}
+
+impl OkxInstrument {
+    fn into_market(self) -> Market {
+        Market {
+            symbol: format!("{}/{}", self.base_ccy, self.quote_ccy),
+            amount_step: self.lot_sz.parse().ok(),
+            price_tick: self.tick_sz.parse().ok(),
+            min_amount: self.min_sz.parse().ok(),
+            max_amount: self.max_lmt_sz.parse().ok(),
+            min_notional: None,
+            id: self.inst_id,
+            base: self.base_ccy,
+            quote: self.quote_ccy,
+            active: self.state == "live",
+        }
+    }
+}
+
+/// OKX spot trading through the v5 API.
+///
+/// Requests are signed with the API key, secret and passphrase. OKX identifies markets by
+/// `instId` ("BTC-USDT") and needs it to query or cancel an order, so ids of orders placed
+/// through this instance are remembered together with their instrument. Orders placed before
+/// a restart are looked up in the repository, if one is set.
+pub struct OkxExchange {
+    api_key: String,
+    api_secret: String,
+    passphrase: String,
+    base_url: String,
+    client: reqwest::Client,
+    markets: RwLock<Option<HashMap<String, Market>>>,
+    order_symbols: RwLock<HashMap<String, String>>,
+    repository: Option<Arc<Repository>>,
+}
+
+impl OkxExchange {
+    pub fn new(api_key: String, api_secret: String, passphrase: String) -> Self {
+        Self {
+            api_key,
+            api_secret,
+            passphrase,
+            base_url: "https://www.okx.com".to_string(),
+            client: reqwest::Client::new(),
+            markets: RwLock::new(None),
+            order_symbols: RwLock::new(HashMap::new()),
+            repository: None,
+        }
+    }
+
+    /// Point the client at another REST endpoint
+    pub fn with_base_url(mut self, url: String) -> Self {
+        self.base_url = url;
+        self
+    }
+
+    /// Resolve the instrument of orders this instance did not place from the stored orders
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Send a request and unwrap the v5 envelope.
+    /// `query` is appended to `path` and signed with it; `body` is sent as JSON.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &str,
+        body: Option<serde_json::Value>,
+        signed: bool,
+    ) -> Result<T> {
+        let request_path = if query.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}?{}", path, query)
+        };
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+
+        let mut request = self
+            .client
+            .request(method.clone(), format!("{}{}", self.base_url, request_path));
+        if signed {
+            let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+            let signature = sign_request(&self.api_secret, &timestamp, method.as_str(), &request_path, &body);
+            request = request
+                .header("OK-ACCESS-KEY", &self.api_key)
+                .header("OK-ACCESS-SIGN", signature)
+                .header("OK-ACCESS-TIMESTAMP", timestamp)
+                .header("OK-ACCESS-PASSPHRASE", &self.passphrase);
+        }
+        if !body.is_empty() {
+            request = request.header("Content-Type", "application/json").body(body);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        let data: OkxResponse =
+            serde_json::from_str(&text).map_err(|_| AppError::Exchange(format!("OKX error ({}): {}", status, text)))?;
+        if data.code != "0" {
+            // Order endpoints put the reason into the per-order sCode/sMsg
+            let detail = data.data[0]["sMsg"]
+                .as_str()
+                .filter(|m| !m.is_empty())
+                .unwrap_or(&data.msg);
+            return Err(AppError::Exchange(format!("OKX error {}: {}", data.code, detail)));
+        }
+        Ok(serde_json::from_value(data.data)?)
+    }
+
+    /// Translate "BTC/USDT" or "BTCUSDT" into an OKX `instId` ("BTC-USDT")
+    async fn inst_id(&self, symbol: &str) -> Result<String> {
+        if symbol.contains('-') {
+            return Ok(symbol.to_string());
+        }
+        if let Some((base, quote)) = symbol.split_once('/') {
+            return Ok(format!("{}-{}", base, quote));
+        }
+
+        // Concatenated symbols are ambiguous; resolve them through the market list
+        let markets = self.load_markets().await?;
+        markets
+            .values()
+            .find(|m| format!("{}{}", m.base, m.quote) == symbol)
+            .map(|m| m.id.clone())
+            .ok_or_else(|| AppError::InvalidInput(format!("Unknown OKX symbol {}", symbol)))
+    }
+
+    async fn order_symbol(&self, order_id: &str) -> Result<String> {
+        if let Some(symbol) = self.order_symbols.read().await.get(order_id) {
+            return Ok(symbol.clone());
+        }
+        if let Some(repository) = &self.repository
+            && let Some(symbol) = repository.get_order_symbol(order_id).await?
+        {
+            return Ok(symbol);
+        }
+        Err(AppError::NotFound(format!("Unknown OKX order {}", order_id)))
+    }
+
+    async fn apply_market_filters(&self, order: OrderRequest) -> Result<OrderRequest> {
+        let markets = self.load_markets().await?;
+        let Some(market) = find_market(&markets, &order.symbol) else {
+            return Ok(order);
+        };
+
+        let reference_price = match order.price {
+            Some(price) => price,
+            None if market.min_notional.is_some() => self.fetch_ticker(&market.id).await?.price,
+            None => Decimal::ZERO,
+        };
+        market.prepare_order(order, reference_price)
+    }
+}
+
+/// Base64 HMAC-SHA256 over `timestamp + method + request_path + body`
+fn sign_request(api_secret: &str, timestamp: &str, method: &str, request_path: &str, body: &str) -> String {
+    type HmacSha256 = Hmac<Sha256>;
+    let mut mac = HmacSha256::new_from_slice(api_secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(method.as_bytes());
+    mac.update(request_path.as_bytes());
+    mac.update(body.as_bytes());
+    BASE64.encode(mac.finalize().into_bytes())
+}
+
+/// Map our timeframe strings onto OKX candle bars
+fn okx_bar(timeframe: &str) -> Result<&'static str> {
+    Ok(match timeframe {
+        "1m" => "1m",
+        "3m" => "3m",
+        "5m" => "5m",
+        "15m" => "15m",
+        "30m" => "30m",
+        "1h" => "1H",
+        "2h" => "2H",
+        "4h" => "4H",
+        "6h" => "6Hutc",
+        "12h" => "12Hutc",
+        "1d" => "1Dutc",
+        "1w" => "1Wutc",
+        "1M" => "1Mutc",
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unsupported OKX timeframe: {}",
+                timeframe
+            )));
+        }
+    })
+}
+
+fn parse_millis(value: &str) -> chrono::DateTime<Utc> {
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .unwrap_or_else(Utc::now)
+}
+
+/// Helper function to parse OKX order state string to OrderStatus enum
+fn parse_order_status(state: &str) -> OrderStatus {
+    match state {
+        "live" => OrderStatus::New,
+        "partially_filled" => OrderStatus::PartiallyFilled,
+        "filled" => OrderStatus::Filled,
+        "canceled" | "mmp_canceled" => OrderStatus::Canceled,
+        _ => OrderStatus::New,
+    }
+}
+
+/// Helper function to parse OKX side string to TradeSide enum
+fn parse_trade_side(side: &str) -> TradeSide {
+    match side {
+        "sell" => TradeSide::Sell,
+        _ => TradeSide::Buy,
+    }
+}
+
+/// Helper function to parse OKX order type string to OrderType enum
+fn parse_order_type(order_type: &str) -> OrderType {
+    match order_type {
+        "limit" | "post_only" | "fok" | "ioc" => OrderType::Limit,
+        _ => OrderType::Market,
+    }
+}
+
+#[async_trait]
+impl Exchange for OkxExchange {
+    async fn fetch_ticker(&self, symbol: &str) -> Result<Ticker> {
+        let query = format!("instId={}", self.inst_id(symbol).await?);
+        let tickers: Vec<OkxTicker> = self
+            .send(Method::GET, "/api/v5/market/ticker", &query, None, false)
+            .await?;
+        let ticker = tickers
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound(format!("No OKX ticker for {}", symbol)))?;
+
+        let last: Decimal = ticker.last.parse().unwrap_or(Decimal::ZERO);
+        let open: Decimal = ticker.open_24h.parse().unwrap_or(Decimal::ZERO);
+        let change_24h = if open > Decimal::ZERO {
+            (last - open) / open * Decimal::ONE_HUNDRED
+        } else {
+            Decimal::ZERO
+        };
+        Ok(Ticker {
+            symbol: ticker.inst_id,
+            price: last,
+            volume_24h: ticker.vol_24h.parse().unwrap_or(Decimal::ZERO),
+            change_24h,
+        })
+    }
+
+    async fn fetch_ohlcv(&self, symbol: &str, timeframe: &str, limit: usize) -> Result<Vec<OHLCV>> {
+        let query = format!(
+            "instId={}&bar={}&limit={}",
+            self.inst_id(symbol).await?,
+            okx_bar(timeframe)?,
+            limit
+        );
+        let data: Vec<Vec<String>> = self
+            .send(Method::GET, "/api/v5/market/candles", &query, None, false)
+            .await?;
+
+        // OKX returns the newest candle first
+        let mut klines: Vec<OHLCV> = data
+            .iter()
+            .filter(|k| k.len() >= 6)
+            .map(|k| {
+                let field = |i: usize| k[i].parse().unwrap_or(Decimal::ZERO);
+                OHLCV {
+                    timestamp: parse_millis(&k[0]),
+                    open: field(1),
+                    high: field(2),
+                    low: field(3),
+                    close: field(4),
+                    volume: field(5),
+                }
+            })
+            .collect();
+        klines.sort_by_key(|k| k.timestamp);
+        Ok(klines)
+    }
+
+    async fn fetch_balance(&self) -> Result<Balance> {
+        let balances = self.fetch_balances().await?;
+        Ok(balances
+            .into_iter()
+            .find(|b| b.currency == "USDT")
+            .unwrap_or_else(|| Balance {
+                currency: "USDT".to_string(),
+                total: Decimal::ZERO,
+                free: Decimal::ZERO,
+                used: Decimal::ZERO,
+            }))
+    }
+
+    async fn fetch_balances(&self) -> Result<Vec<Balance>> {
+        let accounts: Vec<OkxAccount> = self
+            .send(Method::GET, "/api/v5/account/balance", "", None, true)
+            .await?;
+
+        Ok(accounts
+            .into_iter()
+            .flat_map(|a| a.details)
+            .map(|d| Balance {
+                currency: d.ccy,
+                total: d.cash_bal.parse().unwrap_or(Decimal::ZERO),
+                free: d.avail_bal.parse().unwrap_or(Decimal::ZERO),
+                used: d.frozen_bal.parse().unwrap_or(Decimal::ZERO),
+            })
+            .filter(|b| b.total > Decimal::ZERO)
+            .collect())
+    }
+
+    async fn fetch_positions(&self) -> Result<Vec<Position>> {
+        // Spot accounts hold balances, not positions
+        Ok(vec![])
+    }
+
+    async fn create_order(&self, order: OrderRequest) -> Result<Order> {
+        let order = self.apply_market_filters(order).await?;
+        let inst_id = self.inst_id(&order.symbol).await?;
+
+        let ord_type = match order.order_type {
+            OrderType::Market => "market",
+            OrderType::Limit => "limit",
+            OrderType::StopLimit | OrderType::StopMarket => {
+                return Err(AppError::InvalidInput(
+                    "OKX stop orders go through the algo order API and are not supported".to_string(),
+                ));
+            }
+        };
+        let side = match order.side {
+            TradeSide::Buy => "buy",
+            TradeSide::Sell => "sell",
+        };
+
+        let mut body = serde_json::json!({
+            "instId": inst_id,
+            "tdMode": "cash",
+            "side": side,
+            "ordType": ord_type,
+            "sz": order.amount.to_string(),
+            // Market buys are sized in quote currency unless told otherwise
+            "tgtCcy": "base_ccy",
+        });
+        if order.order_type == OrderType::Limit {
+            let price = order
+                .price
+                .ok_or_else(|| AppError::Exchange("Limit order requires price".to_string()))?;
+            body["px"] = serde_json::Value::String(price.to_string());
+        }
+
+        let acks: Vec<OkxOrderAck> = self
+            .send(Method::POST, "/api/v5/trade/order", "", Some(body), true)
+            .await?;
+        let order_id = acks
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Exchange("OKX returned no order".to_string()))?
+            .check()?;
+        self.order_symbols
+            .write()
+            .await
+            .insert(order_id.clone(), inst_id.clone());
+
+        // OKX only acknowledges the order; read back its state
+        match self.fetch_order(&order_id).await {
+            Ok(placed) => Ok(placed),
+            Err(_) => {
+                let now = Utc::now();
+                Ok(Order {
+                    id: order_id,
+                    symbol: inst_id,
+                    side: order.side,
+                    order_type: order.order_type,
+                    status: OrderStatus::New,
+                    price: order.price,
+                    amount: order.amount,
+                    filled: Decimal::ZERO,
+                    remaining: order.amount,
+                    fee: None,
+                    created_at: now,
+                    updated_at: now,
+                })
+            }
+        }
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "instId": self.order_symbol(order_id).await?,
+            "ordId": order_id,
+        });
+        let acks: Vec<OkxOrderAck> = self
+            .send(Method::POST, "/api/v5/trade/cancel-order", "", Some(body), true)
+            .await?;
+        for ack in acks {
+            ack.check()?;
+        }
+        Ok(())
+    }
+
+    async fn fetch_order(&self, order_id: &str) -> Result<Order> {
+        let query = format!("instId={}&ordId={}", self.order_symbol(order_id).await?, order_id);
+        let orders: Vec<OkxOrder> = self
+            .send(Method::GET, "/api/v5/trade/order", &query, None, true)
+            .await?;
+        orders
+            .into_iter()
+            .next()
+            .map(OkxOrder::into_order)
+            .ok_or_else(|| AppError::NotFound(format!("OKX order {} not found", order_id)))
+    }
+
+    async fn fetch_orders(&self, symbol: &str) -> Result<Vec<Order>> {
+        // Open orders of the instrument
+        let query = format!("instType=SPOT&instId={}", self.inst_id(symbol).await?);
+        let orders: Vec<OkxOrder> = self
+            .send(Method::GET, "/api/v5/trade/orders-pending", &query, None, true)
+            .await?;
+        Ok(orders.into_iter().map(OkxOrder::into_order).collect())
+    }
+
+    fn get_name(&self) -> &str {
+        "okx"
+    }
+
+    async fn fetch_markets(&self) -> Result<Vec<Market>> {
+        let instruments: Vec<OkxInstrument> = self
+            .send(Method::GET, "/api/v5/public/instruments", "instType=SPOT", None, false)
+            .await?;
+        Ok(instruments.into_iter().map(OkxInstrument::into_market).collect())
+    }
+
+    async fn load_markets(&self) -> Result<HashMap<String, Market>> {
+        if let Some(markets) = self.markets.read().await.as_ref() {
+            return Ok(markets.clone());
+        }
+
+        let markets: HashMap<String, Market> = self
+            .fetch_markets()
+            .await?
+            .into_iter()
+            .map(|m| (m.symbol.clone(), m))
+            .collect();
+        *self.markets.write().await = Some(markets.clone());
+        Ok(markets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server, ServerGuard};
+    use std::str::FromStr;
+
+    const INSTRUMENTS: &str = r#"{"code": "0", "msg": "", "data": [{
+        "instType": "SPOT", "instId": "BTC-USDT", "baseCcy": "BTC", "quoteCcy": "USDT", "state": "live",
+        "lotSz": "0.00000001", "tickSz": "0.1", "minSz": "0.00001", "maxLmtSz": "9999999999"
+    }]}"#;
+
+    fn exchange(url: String) -> OkxExchange {
+        OkxExchange::new(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            "test_passphrase".to_string(),
+        )
+        .with_base_url(url)
+    }
+
+    async fn mock_instruments(server: &mut ServerGuard) -> mockito::Mock {
+        server
+            .mock("GET", "/api/v5/public/instruments")
+            .match_query(Matcher::UrlEncoded("instType".into(), "SPOT".into()))
+            .with_status(200)
+            .with_body(INSTRUMENTS)
+            .create_async()
+            .await
+    }
+
+    #[test]
+    fn test_sign_request() {
+        let signature = sign_request(
+            "test_secret",
+            "2020-12-08T09:08:57.715Z",
+            "GET",
+            "/api/v5/account/balance?ccy=USDT",
+            "",
+        );
+        assert_eq!(signature, "mflABNRLeoIw+WUULr8mRiPwNmLK17212nHw+swOlNM=");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_ticker_and_ohlcv() {
+        let mut server = Server::new_async().await;
+        let _instruments = mock_instruments(&mut server).await;
+        let ticker_mock = server
+            .mock("GET", "/api/v5/market/ticker")
+            .match_query(Matcher::UrlEncoded("instId".into(), "BTC-USDT".into()))
+            .with_status(200)
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [
+                    {"instId": "BTC-USDT", "last": "42000", "vol24h": "1500.5", "open24h": "40000"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let candles_mock = server
+            .mock("GET", "/api/v5/market/candles")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("instId".into(), "BTC-USDT".into()),
+                Matcher::UrlEncoded("bar".into(), "1H".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [
+                    ["1700003600000", "101", "103", "100", "102", "5", "510", "510", "0"],
+                    ["1700000000000", "100", "102", "99", "101", "4", "404", "404", "1"]
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        let ticker = exchange.fetch_ticker("BTC/USDT").await.unwrap();
+        // Concatenated symbols are resolved through the instrument list
+        let klines = exchange.fetch_ohlcv("BTCUSDT", "1h", 2).await.unwrap();
+
+        ticker_mock.assert_async().await;
+        candles_mock.assert_async().await;
+        assert_eq!(ticker.price, Decimal::from(42000));
+        assert_eq!(ticker.change_24h, Decimal::from(5));
+        assert_eq!(klines.len(), 2);
+        assert_eq!(klines[0].close, Decimal::from(101));
+        assert_eq!(klines[1].close, Decimal::from(102));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_balance_success() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v5/account/balance")
+            .match_header("OK-ACCESS-KEY", "test_key")
+            .match_header("OK-ACCESS-PASSPHRASE", "test_passphrase")
+            .match_header("OK-ACCESS-SIGN", Matcher::Regex("^[A-Za-z0-9+/]{43}=$".to_string()))
+            .match_header(
+                "OK-ACCESS-TIMESTAMP",
+                Matcher::Regex(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z$".to_string()),
+            )
+            .with_status(200)
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [{"totalEq": "1000", "details": [
+                    {"ccy": "USDT", "cashBal": "1000.5", "availBal": "800.5", "frozenBal": "200"},
+                    {"ccy": "ETH", "cashBal": "0", "availBal": "0", "frozenBal": "0"}
+                ]}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let balance = exchange(server.url()).fetch_balance().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(balance.total, Decimal::from_str("1000.5").unwrap());
+        assert_eq!(balance.free, Decimal::from_str("800.5").unwrap());
+        assert_eq!(balance.used, Decimal::from(200));
+    }
+
+    #[tokio::test]
+    async fn test_create_order_success() {
+        let mut server = Server::new_async().await;
+        let _instruments = mock_instruments(&mut server).await;
+        let create_mock = server
+            .mock("POST", "/api/v5/trade/order")
+            .match_header("OK-ACCESS-PASSPHRASE", "test_passphrase")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "instId": "BTC-USDT",
+                "tdMode": "cash",
+                "side": "buy",
+                "ordType": "limit",
+                "sz": "0.01",
+                "px": "30000.1",
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [{"ordId": "312269865356374016", "clOrdId": "", "sCode": "0", "sMsg": ""}]}"#,
+            )
+            .create_async()
+            .await;
+        let fetch_mock = server
+            .mock("GET", "/api/v5/trade/order")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("instId".into(), "BTC-USDT".into()),
+                Matcher::UrlEncoded("ordId".into(), "312269865356374016".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [{
+                    "instId": "BTC-USDT", "ordId": "312269865356374016", "side": "buy", "ordType": "limit",
+                    "px": "30000.1", "sz": "0.01", "accFillSz": "0.01", "avgPx": "30000", "fee": "-0.00001",
+                    "state": "filled", "cTime": "1700000000000", "uTime": "1700000005000"
+                }]}"#,
+            )
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Limit,
+                amount: Decimal::from_str("0.01").unwrap(),
+                price: Some(Decimal::from_str("30000.12").unwrap()),
+            })
+            .await
+            .unwrap();
+
+        create_mock.assert_async().await;
+        fetch_mock.assert_async().await;
+        assert_eq!(order.id, "312269865356374016");
+        assert_eq!(order.symbol, "BTC-USDT");
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.filled, Decimal::from_str("0.01").unwrap());
+        assert_eq!(order.price, Some(Decimal::from(30000)));
+        assert_eq!(order.fee, Some(Decimal::from_str("0.00001").unwrap()));
+        assert_eq!(order.remaining, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_create_order_rejected_and_cancel() {
+        let mut server = Server::new_async().await;
+        let _instruments = mock_instruments(&mut server).await;
+        let _create = server
+            .mock("POST", "/api/v5/trade/order")
+            .with_status(200)
+            .with_body(
+                r#"{"code": "1", "msg": "Operation failed.", "data": [{"ordId": "", "clOrdId": "", "sCode": "51008", "sMsg": "Order failed. Insufficient USDT balance"}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let exchange = exchange(server.url());
+        let err = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC-USDT".to_string(),
+                side: TradeSide::Sell,
+                order_type: OrderType::Market,
+                amount: Decimal::ONE,
+                price: None,
+            })
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Insufficient USDT balance"));
+
+        assert!(matches!(
+            exchange.cancel_order("unknown").await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_placed_before_restart() {
+        let mut server = Server::new_async().await;
+        let cancel_mock = server
+            .mock("POST", "/api/v5/trade/cancel-order")
+            .match_body(Matcher::Json(
+                serde_json::json!({"instId": "ETH-USDT", "ordId": "4102"}),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{"code": "0", "msg": "", "data": [{"ordId": "4102", "clOrdId": "", "sCode": "0", "sMsg": ""}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Arc::new(Repository::new(dir.path().join("test.db")).await.unwrap());
+        let order = Order {
+            id: "4102".to_string(),
+            symbol: "ETH-USDT".to_string(),
+            side: TradeSide::Buy,
+            order_type: OrderType::Limit,
+            status: OrderStatus::New,
+            price: Some(Decimal::from(2000)),
+            amount: Decimal::ONE,
+            filled: Decimal::ZERO,
+            remaining: Decimal::ONE,
+            fee: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        repository.save_order(&order, None).await.unwrap();
+
+        // A fresh instance has not placed the order itself
+        let exchange = exchange(server.url()).with_repository(repository);
+        exchange.cancel_order("4102").await.unwrap();
+        cancel_mock.assert_async().await;
+        assert!(matches!(exchange.cancel_order("999").await, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        assert_eq!(parse_order_status("partially_filled"), OrderStatus::PartiallyFilled);
+        assert_eq!(parse_order_status("mmp_canceled"), OrderStatus::Canceled);
+        assert_eq!(parse_order_type("post_only"), OrderType::Limit);
+        assert!(okx_bar("7m").is_err());
+    }
+}
@@ -0,0 +1,747 @@
+use crate::config::BotConfig;
+use crate::error::{AppError, Result};
+use crate::exchange::Exchange;
+use crate::exchange::market::{Market, find_market};
+use crate::trade::fee::{Fee, calculate_fee};
+use crate::types::*;
+use async_trait::async_trait;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Simulated order together with the funds locked for it while it is open
+struct PaperOrder {
+    order: Order,
+    reserved: Decimal,
+}
+
+/// Simulated wallet, one `Balance` per currency
+#[derive(Default)]
+struct PaperWallet {
+    balances: HashMap<String, Balance>,
+}
+
+impl PaperWallet {
+    fn entry(&mut self, currency: &str) -> &mut Balance {
+        self.balances.entry(currency.to_string()).or_insert_with(|| Balance {
+            currency: currency.to_string(),
+            total: Decimal::ZERO,
+            free: Decimal::ZERO,
+            used: Decimal::ZERO,
+        })
+    }
+
+    fn get(&self, currency: &str) -> Balance {
+        self.balances.get(currency).cloned().unwrap_or_else(|| Balance {
+            currency: currency.to_string(),
+            total: Decimal::ZERO,
+            free: Decimal::ZERO,
+            used: Decimal::ZERO,
+        })
+    }
+
+    fn credit(&mut self, currency: &str, amount: Decimal) {
+        let balance = self.entry(currency);
+        balance.free += amount;
+        balance.total += amount;
+    }
+
+    fn debit(&mut self, currency: &str, amount: Decimal) -> Result<()> {
+        let balance = self.entry(currency);
+        if balance.free < amount {
+            return Err(AppError::Exchange(format!(
+                "Insufficient {} balance: required {}, available {}",
+                currency, amount, balance.free
+            )));
+        }
+        balance.free -= amount;
+        balance.total -= amount;
+        Ok(())
+    }
+
+    fn lock(&mut self, currency: &str, amount: Decimal) -> Result<()> {
+        let balance = self.entry(currency);
+        if balance.free < amount {
+            return Err(AppError::Exchange(format!(
+                "Insufficient {} balance: required {}, available {}",
+                currency, amount, balance.free
+            )));
+        }
+        balance.free -= amount;
+        balance.used += amount;
+        Ok(())
+    }
+
+    fn unlock(&mut self, currency: &str, amount: Decimal) {
+        let balance = self.entry(currency);
+        balance.used -= amount;
+        balance.free += amount;
+    }
+
+    fn spend_locked(&mut self, currency: &str, amount: Decimal) {
+        let balance = self.entry(currency);
+        balance.used -= amount;
+        balance.total -= amount;
+    }
+}
+
+#[derive(Default)]
+struct PaperState {
+    wallet: PaperWallet,
+    orders: HashMap<String, PaperOrder>,
+}
+
+/// Paper-trading exchange used for dry-run mode.
+///
+/// Market data (tickers, candles) is pulled from the wrapped exchange, while orders and balances
+/// are simulated locally. Market orders fill immediately at the last ticker price, limit orders
+/// fill once a later candle trades through the limit price.
+pub struct PaperExchange {
+    inner: Arc<dyn Exchange>,
+    stake_currency: String,
+    fee: Fee,
+    state: RwLock<PaperState>,
+}
+
+impl PaperExchange {
+    /// Create a paper exchange seeded with `dry_run_wallet` units of `stake_currency`
+    pub fn new(inner: Arc<dyn Exchange>, config: &BotConfig) -> Self {
+        let mut wallet = PaperWallet::default();
+        wallet.credit(
+            &config.stake_currency,
+            Decimal::try_from(config.dry_run_wallet).unwrap_or(Decimal::ZERO),
+        );
+
+        Self {
+            inner,
+            stake_currency: config.stake_currency.clone(),
+            fee: Fee::default(),
+            state: RwLock::new(PaperState {
+                wallet,
+                orders: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn with_fee(mut self, fee: Fee) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Rebuild base-currency holdings for trades that were opened before a restart,
+    /// so they can still be sold from the simulated wallet.
+    pub async fn restore_open_trades(&self, trades: &[Trade]) -> Result<()> {
+        let mut state = self.state.write().await;
+        for trade in trades.iter().filter(|t| t.is_open) {
+            let (base, quote) = split_symbol(&trade.pair, &self.stake_currency)?;
+            state.wallet.debit(&quote, trade.stake_amount)?;
+            state.wallet.credit(&base, trade.amount);
+        }
+        Ok(())
+    }
+
+    /// Get the simulated balance of a single currency
+    pub async fn balance_of(&self, currency: &str) -> Balance {
+        self.state.read().await.wallet.get(currency)
+    }
+
+    /// Apply the same precision and notional rules the real exchange would enforce
+    async fn apply_market_filters(&self, order: &OrderRequest, price: Decimal) -> Result<OrderRequest> {
+        let markets = self.inner.load_markets().await?;
+        match find_market(&markets, &order.symbol) {
+            Some(market) => market.prepare_order(order.clone(), price),
+            None => Ok(order.clone()),
+        }
+    }
+
+    async fn fill_market_order(&self, order: &OrderRequest) -> Result<Order> {
+        let (base, quote) = split_symbol(&order.symbol, &self.stake_currency)?;
+        let price = self.inner.fetch_ticker(&order.symbol).await?.price;
+        if price <= Decimal::ZERO {
+            return Err(AppError::Exchange(format!("No price available for {}", order.symbol)));
+        }
+        let order = &self.apply_market_filters(order, price).await?;
+
+        let cost = order.amount * price;
+        let fee = calculate_fee(order.amount, price, self.fee.taker_fee, "quote");
+
+        let mut state = self.state.write().await;
+        match order.side {
+            TradeSide::Buy => {
+                state.wallet.debit(&quote, cost + fee)?;
+                state.wallet.credit(&base, order.amount);
+            }
+            TradeSide::Sell => {
+                state.wallet.debit(&base, order.amount)?;
+                state.wallet.credit(&quote, cost - fee);
+            }
+        }
+
+        let now = Utc::now();
+        let filled = Order {
+            id: format!("dry_run_{}", Uuid::new_v4()),
+            symbol: order.symbol.clone(),
+            side: order.side,
+            order_type: order.order_type,
+            status: OrderStatus::Filled,
+            price: Some(price),
+            amount: order.amount,
+            filled: order.amount,
+            remaining: Decimal::ZERO,
+            fee: Some(fee),
+            created_at: now,
+            updated_at: now,
+        };
+        state.orders.insert(
+            filled.id.clone(),
+            PaperOrder {
+                order: filled.clone(),
+                reserved: Decimal::ZERO,
+            },
+        );
+
+        Ok(filled)
+    }
+
+    async fn place_limit_order(&self, order: &OrderRequest) -> Result<Order> {
+        let (base, quote) = split_symbol(&order.symbol, &self.stake_currency)?;
+        let price = order
+            .price
+            .ok_or_else(|| AppError::Exchange("Limit order requires price".to_string()))?;
+        let order = &self.apply_market_filters(order, price).await?;
+        let price = order.price.unwrap_or(price);
+
+        let mut state = self.state.write().await;
+        let reserved = match order.side {
+            TradeSide::Buy => {
+                let reserved = order.amount * price + calculate_fee(order.amount, price, self.fee.maker_fee, "quote");
+                state.wallet.lock(&quote, reserved)?;
+                reserved
+            }
+            TradeSide::Sell => {
+                state.wallet.lock(&base, order.amount)?;
+                order.amount
+            }
+        };
+
+        let now = Utc::now();
+        let placed = Order {
+            id: format!("dry_run_{}", Uuid::new_v4()),
+            symbol: order.symbol.clone(),
+            side: order.side,
+            order_type: order.order_type,
+            status: OrderStatus::New,
+            price: Some(price),
+            amount: order.amount,
+            filled: Decimal::ZERO,
+            remaining: order.amount,
+            fee: None,
+            created_at: now,
+            updated_at: now,
+        };
+        state.orders.insert(
+            placed.id.clone(),
+            PaperOrder {
+                order: placed.clone(),
+                reserved,
+            },
+        );
+
+        Ok(placed)
+    }
+
+    /// Fill open limit orders on `symbol` whose price was crossed by a candle opened after the order
+    async fn fill_crossed_orders(&self, symbol: &str, candles: &[OHLCV]) -> Result<()> {
+        let (base, quote) = split_symbol(symbol, &self.stake_currency)?;
+        let mut state = self.state.write().await;
+        let PaperState { wallet, orders } = &mut *state;
+
+        for paper in orders.values_mut() {
+            let order = &mut paper.order;
+            if order.symbol != symbol || order.status != OrderStatus::New {
+                continue;
+            }
+            let Some(price) = order.price else {
+                continue;
+            };
+
+            let crossed = candles.iter().find(|c| {
+                c.timestamp >= order.created_at
+                    && match order.side {
+                        TradeSide::Buy => c.low <= price,
+                        TradeSide::Sell => c.high >= price,
+                    }
+            });
+            let Some(candle) = crossed else {
+                continue;
+            };
+
+            let fee = calculate_fee(order.amount, price, self.fee.maker_fee, "quote");
+            match order.side {
+                TradeSide::Buy => {
+                    wallet.spend_locked(&quote, paper.reserved);
+                    wallet.credit(&base, order.amount);
+                }
+                TradeSide::Sell => {
+                    wallet.spend_locked(&base, paper.reserved);
+                    wallet.credit(&quote, order.amount * price - fee);
+                }
+            }
+
+            paper.reserved = Decimal::ZERO;
+            order.status = OrderStatus::Filled;
+            order.filled = order.amount;
+            order.remaining = Decimal::ZERO;
+            order.fee = Some(fee);
+            order.updated_at = candle.timestamp;
+        }
+
+        Ok(())
+    }
+}
+
+/// Split a pair into (base, quote), accepting both "BTC/USDT" and "BTCUSDT" forms
+fn split_symbol(symbol: &str, stake_currency: &str) -> Result<(String, String)> {
+    if let Some((base, quote)) = symbol.split_once('/') {
+        return Ok((base.to_string(), quote.to_string()));
+    }
+    match symbol.strip_suffix(stake_currency) {
+        Some(base) if !base.is_empty() => Ok((base.to_string(), stake_currency.to_string())),
+        _ => Err(AppError::InvalidInput(format!(
+            "Cannot determine base/quote currency for {}",
+            symbol
+        ))),
+    }
+}
+
+#[async_trait]
+impl Exchange for PaperExchange {
+    async fn fetch_ticker(&self, symbol: &str) -> Result<Ticker> {
+        self.inner.fetch_ticker(symbol).await
+    }
+
+    async fn fetch_ohlcv(&self, symbol: &str, timeframe: &str, limit: usize) -> Result<Vec<OHLCV>> {
+        let klines = self.inner.fetch_ohlcv(symbol, timeframe, limit).await?;
+        self.fill_crossed_orders(symbol, &klines).await?;
+        Ok(klines)
+    }
+
+    async fn observe_candles(&self, symbol: &str, candles: &[OHLCV]) -> Result<()> {
+        self.fill_crossed_orders(symbol, candles).await
+    }
+
+    async fn fetch_ohlcv_range(
+        &self,
+        symbol: &str,
+        timeframe: &str,
+        since: chrono::DateTime<Utc>,
+        until: chrono::DateTime<Utc>,
+    ) -> Result<Vec<OHLCV>> {
+        self.inner.fetch_ohlcv_range(symbol, timeframe, since, until).await
+    }
+
+    async fn fetch_balance(&self) -> Result<Balance> {
+        Ok(self.balance_of(&self.stake_currency).await)
+    }
+
+    async fn fetch_balances(&self) -> Result<Vec<Balance>> {
+        Ok(self.state.read().await.wallet.balances.values().cloned().collect())
+    }
+
+    async fn fetch_positions(&self) -> Result<Vec<Position>> {
+        // Spot paper trading holds currencies, not positions
+        Ok(vec![])
+    }
+
+    async fn create_order(&self, order: OrderRequest) -> Result<Order> {
+        if order.amount <= Decimal::ZERO {
+            return Err(AppError::InvalidInput("Order amount must be positive".to_string()));
+        }
+
+        match order.order_type {
+            OrderType::Market => self.fill_market_order(&order).await,
+            OrderType::Limit => self.place_limit_order(&order).await,
+            OrderType::StopLimit | OrderType::StopMarket => Err(AppError::NotImplemented(
+                "Stop orders are not supported in dry-run".to_string(),
+            )),
+        }
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let mut state = self.state.write().await;
+        let PaperState { wallet, orders } = &mut *state;
+
+        let paper = orders
+            .get_mut(order_id)
+            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_id)))?;
+        if paper.order.status != OrderStatus::New {
+            return Err(AppError::Exchange(format!(
+                "Failed to cancel order: order {} is {:?}",
+                order_id, paper.order.status
+            )));
+        }
+
+        let (base, quote) = split_symbol(&paper.order.symbol, &self.stake_currency)?;
+        match paper.order.side {
+            TradeSide::Buy => wallet.unlock(&quote, paper.reserved),
+            TradeSide::Sell => wallet.unlock(&base, paper.reserved),
+        }
+
+        paper.reserved = Decimal::ZERO;
+        paper.order.status = OrderStatus::Canceled;
+        paper.order.updated_at = Utc::now();
+        Ok(())
+    }
+
+    async fn fetch_order(&self, order_id: &str) -> Result<Order> {
+        self.state
+            .read()
+            .await
+            .orders
+            .get(order_id)
+            .map(|p| p.order.clone())
+            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_id)))
+    }
+
+    async fn fetch_orders(&self, symbol: &str) -> Result<Vec<Order>> {
+        let state = self.state.read().await;
+        let mut orders: Vec<Order> = state
+            .orders
+            .values()
+            .filter(|p| p.order.symbol == symbol)
+            .map(|p| p.order.clone())
+            .collect();
+        orders.sort_by_key(|o| o.created_at);
+        Ok(orders)
+    }
+
+    fn get_name(&self) -> &str {
+        self.inner.get_name()
+    }
+
+    async fn fetch_markets(&self) -> Result<Vec<Market>> {
+        self.inner.fetch_markets().await
+    }
+
+    async fn load_markets(&self) -> Result<HashMap<String, Market>> {
+        self.inner.load_markets().await
+    }
+
+    async fn fetch_funding_rate(&self, symbol: &str) -> Result<FundingRate> {
+        self.inner.fetch_funding_rate(symbol).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    struct PriceFeed {
+        price: Decimal,
+        candles: Vec<OHLCV>,
+    }
+
+    #[async_trait]
+    impl Exchange for PriceFeed {
+        async fn fetch_ticker(&self, symbol: &str) -> Result<Ticker> {
+            Ok(Ticker {
+                symbol: symbol.to_string(),
+                price: self.price,
+                volume_24h: Decimal::ZERO,
+                change_24h: Decimal::ZERO,
+            })
+        }
+
+        async fn fetch_ohlcv(&self, _symbol: &str, _timeframe: &str, _limit: usize) -> Result<Vec<OHLCV>> {
+            Ok(self.candles.clone())
+        }
+
+        async fn fetch_balance(&self) -> Result<Balance> {
+            Err(AppError::NotImplemented("fetch_balance".to_string()))
+        }
+
+        async fn fetch_balances(&self) -> Result<Vec<Balance>> {
+            Err(AppError::NotImplemented("fetch_balances".to_string()))
+        }
+
+        async fn fetch_positions(&self) -> Result<Vec<Position>> {
+            Ok(vec![])
+        }
+
+        async fn create_order(&self, _order: OrderRequest) -> Result<Order> {
+            panic!("paper exchange must not place real orders");
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<()> {
+            panic!("paper exchange must not cancel real orders");
+        }
+
+        async fn fetch_order(&self, _order_id: &str) -> Result<Order> {
+            Err(AppError::NotImplemented("fetch_order".to_string()))
+        }
+
+        async fn fetch_orders(&self, _symbol: &str) -> Result<Vec<Order>> {
+            Ok(vec![])
+        }
+
+        fn get_name(&self) -> &str {
+            "binance"
+        }
+
+        async fn fetch_markets(&self) -> Result<Vec<Market>> {
+            Ok(vec![Market {
+                symbol: "BTC/USDT".to_string(),
+                id: "BTCUSDT".to_string(),
+                base: "BTC".to_string(),
+                quote: "USDT".to_string(),
+                active: true,
+                amount_step: Some(Decimal::from_str("0.001").unwrap()),
+                price_tick: Some(Decimal::from_str("0.1").unwrap()),
+                min_amount: None,
+                max_amount: None,
+                min_notional: Some(Decimal::from(5)),
+            }])
+        }
+    }
+
+    fn candle(minutes_from_now: i64, low: &str, high: &str) -> OHLCV {
+        OHLCV {
+            timestamp: Utc::now() + Duration::minutes(minutes_from_now),
+            open: Decimal::from(100),
+            high: Decimal::from_str(high).unwrap(),
+            low: Decimal::from_str(low).unwrap(),
+            close: Decimal::from(100),
+            volume: Decimal::from(10),
+        }
+    }
+
+    fn paper(candles: Vec<OHLCV>) -> PaperExchange {
+        let feed = Arc::new(PriceFeed {
+            price: Decimal::from(100),
+            candles,
+        });
+        let config = BotConfig {
+            dry_run_wallet: 1000.0,
+            ..Default::default()
+        };
+        PaperExchange::new(feed, &config)
+    }
+
+    #[tokio::test]
+    async fn test_market_buy_debits_wallet_with_fee() {
+        let exchange = paper(vec![]);
+
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Market,
+                amount: Decimal::from(2),
+                price: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.price, Some(Decimal::from(100)));
+        assert_eq!(order.fee, Some(Decimal::from_str("0.2").unwrap()));
+
+        // 1000 - 2 * 100 - 0.2 fee
+        let usdt = exchange.fetch_balance().await.unwrap();
+        assert_eq!(usdt.free, Decimal::from_str("799.8").unwrap());
+        assert_eq!(exchange.balance_of("BTC").await.free, Decimal::from(2));
+        assert_eq!(exchange.get_name(), "binance");
+    }
+
+    #[tokio::test]
+    async fn test_orders_follow_market_precision() {
+        let exchange = paper(vec![]);
+
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Market,
+                amount: Decimal::from_str("1.23456").unwrap(),
+                price: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(order.amount, Decimal::from_str("1.234").unwrap());
+
+        // 0.04 * 100 = 4 < 5 min notional
+        let result = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Market,
+                amount: Decimal::from_str("0.04").unwrap(),
+                price: None,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_market_buy_insufficient_balance() {
+        let exchange = paper(vec![]);
+
+        let result = exchange
+            .create_order(OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Market,
+                amount: Decimal::from(20),
+                price: None,
+            })
+            .await;
+
+        assert!(matches!(result, Err(AppError::Exchange(_))));
+        assert_eq!(exchange.fetch_balance().await.unwrap().free, Decimal::from(1000));
+    }
+
+    #[tokio::test]
+    async fn test_limit_buy_fills_when_candle_crosses() {
+        let exchange = paper(vec![candle(1, "96", "104"), candle(2, "89", "101")]);
+
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Limit,
+                amount: Decimal::from(1),
+                price: Some(Decimal::from(90)),
+            })
+            .await
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::New);
+        assert_eq!(
+            exchange.fetch_balance().await.unwrap().used,
+            Decimal::from_str("90.09").unwrap()
+        );
+
+        exchange.fetch_ohlcv("BTC/USDT", "1m", 2).await.unwrap();
+
+        let filled = exchange.fetch_order(&order.id).await.unwrap();
+        assert_eq!(filled.status, OrderStatus::Filled);
+        assert_eq!(filled.filled, Decimal::from(1));
+
+        let usdt = exchange.fetch_balance().await.unwrap();
+        assert_eq!(usdt.used, Decimal::ZERO);
+        assert_eq!(usdt.total, Decimal::from_str("909.91").unwrap());
+        assert_eq!(exchange.balance_of("BTC").await.total, Decimal::from(1));
+    }
+
+    #[tokio::test]
+    async fn test_limit_buy_fills_from_observed_candles() {
+        let exchange = paper(vec![]);
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Limit,
+                amount: Decimal::from(1),
+                price: Some(Decimal::from(90)),
+            })
+            .await
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::New);
+
+        // Candles from the market stream never pass through fetch_ohlcv
+        exchange
+            .observe_candles("BTC/USDT", &[candle(1, "96", "104")])
+            .await
+            .unwrap();
+        assert_eq!(exchange.fetch_order(&order.id).await.unwrap().status, OrderStatus::New);
+
+        exchange
+            .observe_candles("BTC/USDT", &[candle(2, "89", "101")])
+            .await
+            .unwrap();
+        assert_eq!(
+            exchange.fetch_order(&order.id).await.unwrap().status,
+            OrderStatus::Filled
+        );
+        assert_eq!(exchange.balance_of("BTC").await.total, Decimal::from(1));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_limit_order_releases_funds() {
+        let exchange = paper(vec![candle(1, "96", "104")]);
+
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Buy,
+                order_type: OrderType::Limit,
+                amount: Decimal::from(1),
+                price: Some(Decimal::from(90)),
+            })
+            .await
+            .unwrap();
+
+        exchange.fetch_ohlcv("BTC/USDT", "1m", 1).await.unwrap();
+        assert_eq!(exchange.fetch_order(&order.id).await.unwrap().status, OrderStatus::New);
+
+        exchange.cancel_order(&order.id).await.unwrap();
+
+        let usdt = exchange.fetch_balance().await.unwrap();
+        assert_eq!(usdt.free, Decimal::from(1000));
+        assert_eq!(usdt.used, Decimal::ZERO);
+        assert_eq!(
+            exchange.fetch_order(&order.id).await.unwrap().status,
+            OrderStatus::Canceled
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_open_trades_allows_selling() {
+        let exchange = paper(vec![]);
+        let trade = Trade {
+            id: Uuid::new_v4(),
+            pair: "BTC/USDT".to_string(),
+            is_open: true,
+            exchange: "binance".to_string(),
+            open_rate: Decimal::from(100),
+            open_date: Utc::now(),
+            close_rate: None,
+            close_date: None,
+            amount: Decimal::from(1),
+            stake_amount: Decimal::from(100),
+            strategy: "Test".to_string(),
+            timeframe: Timeframe::OneHour,
+            stop_loss: None,
+            take_profit: None,
+            exit_reason: None,
+            profit_abs: None,
+            profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
+        };
+        exchange.restore_open_trades(&[trade]).await.unwrap();
+
+        let order = exchange
+            .create_order(OrderRequest {
+                symbol: "BTC/USDT".to_string(),
+                side: TradeSide::Sell,
+                order_type: OrderType::Market,
+                amount: Decimal::from(1),
+                price: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(exchange.balance_of("BTC").await.total, Decimal::ZERO);
+        assert_eq!(
+            exchange.fetch_balance().await.unwrap().total,
+            Decimal::from_str("999.9").unwrap()
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use crate::config::{BotConfig, ExchangeConfig};
+use crate::error::{AppError, Result};
+use crate::exchange::Exchange;
+use crate::exchange::binance::BinanceExchange;
+use crate::exchange::binance::futures::BinanceFuturesExchange;
+use crate::exchange::bybit::BybitExchange;
+use crate::exchange::okx::OkxExchange;
+use crate::exchange::paper::PaperExchange;
+use crate::exchange::replay::ReplayExchange;
+use crate::persistence::Repository;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Everything an exchange constructor may need
+pub struct ExchangeContext {
+    pub exchange: ExchangeConfig,
+    pub bot: BotConfig,
+    /// Database used by the replay feed, for restoring simulated holdings and for looking up
+    /// the symbol of stored futures, Bybit and OKX orders
+    pub repository: Option<Arc<Repository>>,
+}
+
+/// Builds the market-data/trading venue for a registered exchange name
+pub type ExchangeConstructor = fn(&ExchangeContext) -> Result<Arc<dyn Exchange>>;
+
+struct RegistryEntry {
+    constructor: ExchangeConstructor,
+    /// Orders are always simulated, regardless of `dry_run`
+    simulated: bool,
+}
+
+/// Maps `ExchangeConfig::name` to exchange constructors.
+///
+/// `ExchangeRegistry::default()` knows the built-in venues:
+/// - `binance`: Binance spot
+/// - `binance_futures`: Binance USDⓈ-M futures
+/// - `bybit`: Bybit spot (v5 API)
+/// - `okx`: OKX spot, needs `ExchangeConfig::password`
+/// - `paper`: Binance spot market data with simulated orders
+/// - `replay`: candles stored in the database with simulated orders
+///
+/// With `dry_run` enabled every venue is wrapped in a [`PaperExchange`].
+pub struct ExchangeRegistry {
+    entries: HashMap<String, RegistryEntry>,
+}
+
+impl Default for ExchangeRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register("binance", build_binance);
+        registry.register("binance_futures", build_binance_futures);
+        registry.register("bybit", build_bybit);
+        registry.register("okx", build_okx);
+        registry.register_simulated("paper", build_binance);
+        registry.register_simulated("replay", build_replay);
+        registry
+    }
+}
+
+impl ExchangeRegistry {
+    /// Empty registry without any built-in exchanges
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Register a live venue under `name`, replacing any previous entry
+    pub fn register(&mut self, name: &str, constructor: ExchangeConstructor) {
+        self.entries.insert(
+            name.to_string(),
+            RegistryEntry {
+                constructor,
+                simulated: false,
+            },
+        );
+    }
+
+    /// Register a venue whose orders are always paper-traded
+    pub fn register_simulated(&mut self, name: &str, constructor: ExchangeConstructor) {
+        self.entries.insert(
+            name.to_string(),
+            RegistryEntry {
+                constructor,
+                simulated: true,
+            },
+        );
+    }
+
+    /// Registered exchange names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(|n| n.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Build the exchange selected by `context.exchange.name`.
+    ///
+    /// Simulated venues and dry-run mode return a [`PaperExchange`]; when a repository is
+    /// available its open trades are restored into the simulated wallet.
+    pub async fn create(&self, context: &ExchangeContext) -> Result<Arc<dyn Exchange>> {
+        let name = context.exchange.name.as_str();
+        let entry = self.entries.get(name).ok_or_else(|| {
+            AppError::Config(format!(
+                "Unknown exchange '{}', available: {}",
+                name,
+                self.names().join(", ")
+            ))
+        })?;
+
+        let venue = (entry.constructor)(context)?;
+        if !entry.simulated && !context.bot.dry_run {
+            return Ok(venue);
+        }
+
+        let paper = PaperExchange::new(venue, &context.bot);
+        if let Some(repository) = &context.repository {
+            paper.restore_open_trades(&repository.get_open_trades().await?).await?;
+        }
+        Ok(Arc::new(paper))
+    }
+}
+
+fn build_binance(context: &ExchangeContext) -> Result<Arc<dyn Exchange>> {
+    let config = &context.exchange;
+    let mut exchange = BinanceExchange::new(config.key.clone(), config.secret.clone());
+    if !config.enable_rate_limit {
+        exchange = exchange.with_rate_limit(std::time::Duration::ZERO);
+    }
+    if let Some(url) = &config.base_url {
+        exchange = exchange.with_base_url(url.clone());
+    }
+    Ok(Arc::new(exchange))
+}
+
+fn build_binance_futures(context: &ExchangeContext) -> Result<Arc<dyn Exchange>> {
+    let config = &context.exchange;
+    let mut exchange = BinanceFuturesExchange::new(config.key.clone(), config.secret.clone());
+    if let Some(url) = &config.base_url {
+        exchange = exchange.with_base_url(url.clone());
+    }
+    if let Some(repository) = &context.repository {
+        exchange = exchange.with_repository(repository.clone());
+    }
+    Ok(Arc::new(exchange))
+}
+
+fn build_bybit(context: &ExchangeContext) -> Result<Arc<dyn Exchange>> {
+    let config = &context.exchange;
+    let mut exchange = BybitExchange::new(config.key.clone(), config.secret.clone());
+    if let Some(url) = &config.base_url {
+        exchange = exchange.with_base_url(url.clone());
+    }
+    if let Some(repository) = &context.repository {
+        exchange = exchange.with_repository(repository.clone());
+    }
+    Ok(Arc::new(exchange))
+}
+
+fn build_okx(context: &ExchangeContext) -> Result<Arc<dyn Exchange>> {
+    let config = &context.exchange;
+    let passphrase = config
+        .password
+        .clone()
+        .ok_or_else(|| AppError::Config("OKX requires an API passphrase (exchange.password)".to_string()))?;
+    let mut exchange = OkxExchange::new(config.key.clone(), config.secret.clone(), passphrase);
+    if let Some(url) = &config.base_url {
+        exchange = exchange.with_base_url(url.clone());
+    }
+    if let Some(repository) = &context.repository {
+        exchange = exchange.with_repository(repository.clone());
+    }
+    Ok(Arc::new(exchange))
+}
+
+fn build_replay(context: &ExchangeContext) -> Result<Arc<dyn Exchange>> {
+    let repository = context
+        .repository
+        .clone()
+        .ok_or_else(|| AppError::Config("The replay exchange requires a database".to_string()))?;
+    Ok(Arc::new(ReplayExchange::new(repository)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn context(name: &str, dry_run: bool) -> ExchangeContext {
+        ExchangeContext {
+            exchange: ExchangeConfig {
+                name: name.to_string(),
+                ..ExchangeConfig::default()
+            },
+            bot: BotConfig {
+                dry_run,
+                ..BotConfig::default()
+            },
+            repository: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_registered_exchanges() {
+        let registry = ExchangeRegistry::default();
+        assert_eq!(
+            registry.names(),
+            vec!["binance", "binance_futures", "bybit", "okx", "paper", "replay"]
+        );
+
+        let live = registry.create(&context("binance", false)).await.unwrap();
+        assert_eq!(live.get_name(), "binance");
+
+        let futures = registry.create(&context("binance_futures", false)).await.unwrap();
+        assert_eq!(futures.get_name(), "binance_futures");
+
+        let bybit = registry.create(&context("bybit", false)).await.unwrap();
+        assert_eq!(bybit.get_name(), "bybit");
+
+        let mut okx = context("okx", false);
+        okx.exchange.password = Some("passphrase".to_string());
+        assert_eq!(registry.create(&okx).await.unwrap().get_name(), "okx");
+
+        // "paper" always simulates orders, funded from dry_run_wallet
+        let paper = registry.create(&context("paper", false)).await.unwrap();
+        assert_eq!(paper.fetch_balance().await.unwrap().total, Decimal::from(10000));
+
+        let dry_run = registry.create(&context("binance", true)).await.unwrap();
+        assert_eq!(dry_run.fetch_balance().await.unwrap().total, Decimal::from(10000));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_exchange_is_config_error() {
+        let registry = ExchangeRegistry::default();
+        let err = registry.create(&context("kraken", false)).await.err().unwrap();
+        match err {
+            AppError::Config(message) => {
+                assert!(message.contains("kraken"));
+                assert!(message.contains("binance_futures"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+
+        // "okx" needs a passphrase, "replay" a database to read candles from
+        let err = registry.create(&context("okx", false)).await.err().unwrap();
+        assert!(matches!(err, AppError::Config(_)));
+        let err = registry.create(&context("replay", true)).await.err().unwrap();
+        assert!(matches!(err, AppError::Config(_)));
+    }
+
+    #[tokio::test]
+    async fn test_testnet_base_url() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v3/ticker/24hr")
+            .match_query(mockito::Matcher::UrlEncoded("symbol".into(), "BTCUSDT".into()))
+            .with_status(200)
+            .with_body(r#"{"symbol":"BTCUSDT","lastPrice":"42000.0","volume":"1.0","priceChangePercent":"0.5"}"#)
+            .create_async()
+            .await;
+
+        let mut context = context("binance", false);
+        context.exchange.base_url = Some(server.url());
+        let exchange = ExchangeRegistry::default().create(&context).await.unwrap();
+
+        let ticker = exchange.fetch_ticker("BTCUSDT").await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(ticker.price, Decimal::from(42000));
+    }
+}
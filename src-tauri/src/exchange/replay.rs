@@ -0,0 +1,198 @@
+use crate::error::{AppError, Result};
+use crate::exchange::Exchange;
+use crate::exchange::market::Market;
+use crate::persistence::Repository;
+use crate::types::*;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Maximum number of stored candles loaded per pair and timeframe
+const REPLAY_HISTORY_LIMIT: usize = 100_000;
+
+/// Stored candles of one pair/timeframe and how far they have been replayed
+struct ReplaySeries {
+    candles: Vec<OHLCV>,
+    position: usize,
+}
+
+/// Market-data feed that replays candles stored in the database.
+///
+/// Every `fetch_ohlcv` call moves the series forward by one candle, so a bot running against
+/// this feed walks through the stored history one cycle at a time. Tickers report the close of
+/// the most recently replayed candle. Orders and balances are not supported here; wrap the feed
+/// in a `PaperExchange` to simulate them.
+pub struct ReplayExchange {
+    repository: Arc<Repository>,
+    series: RwLock<HashMap<(String, String), ReplaySeries>>,
+    last_price: RwLock<HashMap<String, Decimal>>,
+}
+
+impl ReplayExchange {
+    pub fn new(repository: Arc<Repository>) -> Self {
+        Self {
+            repository,
+            series: RwLock::new(HashMap::new()),
+            last_price: RwLock::new(HashMap::new()),
+        }
+    }
+
+    async fn load_series(&self, symbol: &str, timeframe: &str) -> Result<Vec<OHLCV>> {
+        let mut candles = self
+            .repository
+            .get_klines(symbol, timeframe, REPLAY_HISTORY_LIMIT)
+            .await?;
+        candles.reverse();
+        if candles.is_empty() {
+            return Err(AppError::NotFound(format!(
+                "No stored {} klines for {} to replay",
+                timeframe, symbol
+            )));
+        }
+        Ok(candles)
+    }
+}
+
+#[async_trait]
+impl Exchange for ReplayExchange {
+    async fn fetch_ticker(&self, symbol: &str) -> Result<Ticker> {
+        let price = self
+            .last_price
+            .read()
+            .await
+            .get(symbol)
+            .copied()
+            .ok_or_else(|| AppError::Exchange(format!("No replayed candle for {} yet", symbol)))?;
+        Ok(Ticker {
+            symbol: symbol.to_string(),
+            price,
+            volume_24h: Decimal::ZERO,
+            change_24h: Decimal::ZERO,
+        })
+    }
+
+    async fn fetch_ohlcv(&self, symbol: &str, timeframe: &str, limit: usize) -> Result<Vec<OHLCV>> {
+        let key = (symbol.to_string(), timeframe.to_string());
+        if !self.series.read().await.contains_key(&key) {
+            let candles = self.load_series(symbol, timeframe).await?;
+            self.series.write().await.entry(key.clone()).or_insert(ReplaySeries {
+                // The first request already returns `limit` candles as warm-up history
+                position: limit.clamp(1, candles.len()),
+                candles,
+            });
+        }
+
+        let mut series = self.series.write().await;
+        let series = series
+            .get_mut(&key)
+            .ok_or_else(|| AppError::Exchange(format!("Replay series for {} missing", symbol)))?;
+        let end = series.position;
+        let window = series.candles[end.saturating_sub(limit)..end].to_vec();
+        series.position = (end + 1).min(series.candles.len());
+
+        if let Some(last) = window.last() {
+            self.last_price.write().await.insert(symbol.to_string(), last.close);
+        }
+        Ok(window)
+    }
+
+    async fn fetch_balance(&self) -> Result<Balance> {
+        Err(AppError::NotImplemented("fetch_balance on replay exchange".to_string()))
+    }
+
+    async fn fetch_balances(&self) -> Result<Vec<Balance>> {
+        Err(AppError::NotImplemented(
+            "fetch_balances on replay exchange".to_string(),
+        ))
+    }
+
+    async fn fetch_positions(&self) -> Result<Vec<Position>> {
+        Ok(vec![])
+    }
+
+    async fn create_order(&self, _order: OrderRequest) -> Result<Order> {
+        Err(AppError::NotImplemented("create_order on replay exchange".to_string()))
+    }
+
+    async fn cancel_order(&self, _order_id: &str) -> Result<()> {
+        Err(AppError::NotImplemented("cancel_order on replay exchange".to_string()))
+    }
+
+    async fn fetch_order(&self, _order_id: &str) -> Result<Order> {
+        Err(AppError::NotImplemented("fetch_order on replay exchange".to_string()))
+    }
+
+    async fn fetch_orders(&self, _symbol: &str) -> Result<Vec<Order>> {
+        Ok(vec![])
+    }
+
+    fn get_name(&self) -> &str {
+        "replay"
+    }
+
+    async fn fetch_markets(&self) -> Result<Vec<Market>> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    async fn repository_with_klines(count: i64) -> (tempfile::TempDir, Arc<Repository>) {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Repository::new(dir.path().join("replay.db")).await.unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let klines: Vec<OHLCV> = (0..count)
+            .map(|i| {
+                let price = Decimal::from(100 + i);
+                OHLCV {
+                    timestamp: start + Duration::hours(i),
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: Decimal::ONE,
+                }
+            })
+            .collect();
+        repository.save_klines("BTC/USDT", "1h", &klines).await.unwrap();
+        (dir, Arc::new(repository))
+    }
+
+    #[tokio::test]
+    async fn test_replay_advances_one_candle_per_fetch() {
+        let (_dir, repository) = repository_with_klines(5).await;
+        let exchange = ReplayExchange::new(repository);
+
+        let first = exchange.fetch_ohlcv("BTC/USDT", "1h", 3).await.unwrap();
+        assert_eq!(first.len(), 3);
+        assert_eq!(first.last().unwrap().close, Decimal::from(102));
+        assert_eq!(
+            exchange.fetch_ticker("BTC/USDT").await.unwrap().price,
+            Decimal::from(102)
+        );
+
+        let second = exchange.fetch_ohlcv("BTC/USDT", "1h", 3).await.unwrap();
+        assert_eq!(second.first().unwrap().close, Decimal::from(101));
+        assert_eq!(second.last().unwrap().close, Decimal::from(103));
+
+        // Once exhausted the replay stays on the last candle
+        exchange.fetch_ohlcv("BTC/USDT", "1h", 3).await.unwrap();
+        let end = exchange.fetch_ohlcv("BTC/USDT", "1h", 3).await.unwrap();
+        assert_eq!(end.last().unwrap().close, Decimal::from(104));
+    }
+
+    #[tokio::test]
+    async fn test_replay_without_stored_data() {
+        let (_dir, repository) = repository_with_klines(0).await;
+        let exchange = ReplayExchange::new(repository);
+
+        let result = exchange.fetch_ohlcv("ETH/USDT", "1h", 10).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        assert!(exchange.fetch_ticker("ETH/USDT").await.is_err());
+    }
+}
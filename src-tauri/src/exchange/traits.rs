@@ -1,16 +1,79 @@
-use crate::error::Result;
+use crate::error::{AppError, Result};
+use crate::exchange::market::Market;
 use crate::types::*;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
 #[async_trait]
 pub trait Exchange: Send + Sync {
     async fn fetch_ticker(&self, symbol: &str) -> Result<Ticker>;
     async fn fetch_ohlcv(&self, symbol: &str, timeframe: &str, limit: usize) -> Result<Vec<OHLCV>>;
     async fn fetch_balance(&self) -> Result<Balance>;
+    /// Balances of every currency held in the account
+    async fn fetch_balances(&self) -> Result<Vec<Balance>>;
     async fn fetch_positions(&self) -> Result<Vec<Position>>;
     async fn create_order(&self, order: OrderRequest) -> Result<Order>;
     async fn cancel_order(&self, order_id: &str) -> Result<()>;
     async fn fetch_order(&self, order_id: &str) -> Result<Order>;
     async fn fetch_orders(&self, symbol: &str) -> Result<Vec<Order>>;
     fn get_name(&self) -> &str;
+
+    /// Candles whose open time lies in `[since, until)`, oldest first.
+    /// Implementations page through the exchange history as needed.
+    async fn fetch_ohlcv_range(
+        &self,
+        _symbol: &str,
+        _timeframe: &str,
+        _since: DateTime<Utc>,
+        _until: DateTime<Utc>,
+    ) -> Result<Vec<OHLCV>> {
+        Err(AppError::NotImplemented(format!(
+            "{} does not support historical candle ranges",
+            self.get_name()
+        )))
+    }
+
+    /// Candles of `symbol` the caller received elsewhere than from `fetch_ohlcv`, e.g. from a
+    /// market stream. Simulated exchanges use them to fill resting orders.
+    async fn observe_candles(&self, _symbol: &str, _candles: &[OHLCV]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Fetch trading rules for every market listed on the exchange
+    async fn fetch_markets(&self) -> Result<Vec<Market>>;
+
+    /// Markets keyed by unified symbol. Implementations should cache the result.
+    async fn load_markets(&self) -> Result<HashMap<String, Market>> {
+        Ok(self
+            .fetch_markets()
+            .await?
+            .into_iter()
+            .map(|m| (m.symbol.clone(), m))
+            .collect())
+    }
+
+    /// Set the leverage used for new positions on a futures symbol
+    async fn set_leverage(&self, _symbol: &str, _leverage: u32) -> Result<()> {
+        Err(AppError::NotImplemented(format!(
+            "{} does not support leverage",
+            self.get_name()
+        )))
+    }
+
+    /// Switch a futures symbol between isolated and cross margin
+    async fn set_margin_mode(&self, _symbol: &str, _mode: MarginMode) -> Result<()> {
+        Err(AppError::NotImplemented(format!(
+            "{} does not support margin modes",
+            self.get_name()
+        )))
+    }
+
+    /// Current funding rate of a perpetual futures symbol
+    async fn fetch_funding_rate(&self, _symbol: &str) -> Result<FundingRate> {
+        Err(AppError::NotImplemented(format!(
+            "{} does not support funding rates",
+            self.get_name()
+        )))
+    }
 }
@@ -3,9 +3,11 @@
 #![allow(clippy::upper_case_acronyms)]
 #![allow(clippy::collapsible_if)]
 
+pub mod api;
 pub mod backtest;
 pub mod bot;
 pub mod config;
+pub mod data;
 pub mod error;
 pub mod exchange;
 pub mod optimize;
@@ -17,5 +19,6 @@ pub mod tests;
 pub mod trade;
 pub mod types;
 pub mod utils;
+pub mod wallets;
 
 pub use error::{AppError, Result};
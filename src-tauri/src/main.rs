@@ -18,17 +18,22 @@ use crate::risk_commands::remove_protection;
 
 use std::sync::Arc;
 
+mod api;
 mod backtest;
 mod bot;
 mod commands;
 mod config;
+mod data;
 mod error;
 mod exchange;
 mod persistence;
 mod risk;
 mod risk_commands;
 mod strategy;
+#[allow(unused_imports)]
+mod trade;
 mod types;
+mod wallets;
 
 #[tokio::main]
 async fn main() {
@@ -82,10 +87,35 @@ async fn main() {
         repository: repository.clone(),
         bot: Arc::new(tokio::sync::Mutex::new(None)),
         risk_manager: Arc::new(tokio::sync::RwLock::new(Some(risk_manager))),
+        events: tokio::sync::broadcast::channel(bot::events::EVENT_CHANNEL_CAPACITY).0,
     };
 
+    // api_server.enabled 时在后台提供兼容 freqtrade 的 REST API
+    api::spawn_if_enabled(&app_state).await;
+
+    let mut bot_events = app_state.subscribe_events();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .setup(move |app| {
+            // 将机器人事件转发给前端，仪表盘据此实时刷新
+            let handle = app.handle().clone();
+            tokio::spawn(async move {
+                loop {
+                    match bot_events.recv().await {
+                        Ok(event) => {
+                            use tauri::Emitter;
+                            if let Err(e) = handle.emit(bot::events::BOT_EVENT_NAME, event) {
+                                eprintln!("Failed to emit bot event: {}", e);
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+            Ok(())
+        })
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             get_bot_status,
@@ -94,10 +124,18 @@ async fn main() {
             get_open_trades,
             get_all_trades,
             run_backtest,
+            get_backtest_results,
+            get_backtest_result,
+            get_backtest_report,
+            run_monte_carlo,
+            export_backtest_result,
+            import_backtest_results,
             get_dashboard_stats,
             get_equity_curve,
             get_config,
             update_config,
+            download_data,
+            get_data_downloads,
             // 风险管理命令
             add_cooldown_protection,
             add_low_profit_protection,
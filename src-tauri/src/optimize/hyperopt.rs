@@ -1,9 +1,13 @@
 use crate::backtest::BacktestConfig;
 use crate::backtest::BacktestEngine;
-use crate::error::Result;
+use crate::error::{AppError, Result};
 use crate::optimize::{HyperoptParams, HyperoptValue};
 use crate::persistence::Repository;
-use crate::types::BacktestResult;
+use crate::strategy::Strategy;
+use crate::types::{BacktestResult, OHLCV};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -11,6 +15,9 @@ pub struct HyperoptConfig {
     pub epochs: usize,
     pub spaces: Vec<String>,
     pub strategy: String,
+    /// Makes the sampled parameters reproducible
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -31,16 +38,27 @@ impl Hyperopt {
         Self { config }
     }
 
-    pub async fn run(&self) -> Result<OptimizerResult> {
+    /// Backtest `epochs` random samples of the configured spaces on `data`, keeping the parameters
+    /// with the lowest loss. Sampled `stoploss` and `roi` values replace those of `config`.
+    pub async fn optimize(
+        &self,
+        config: &BacktestConfig,
+        strategy: Arc<dyn Strategy>,
+        data: &BTreeMap<String, Vec<OHLCV>>,
+    ) -> Result<OptimizerResult> {
+        let mut rng = match self.config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
         let mut results = Vec::new();
         let mut best_loss = f64::MAX;
         let mut best_epoch = 0;
         let mut best_params = HyperoptParams::new();
 
         for epoch in 0..self.config.epochs {
-            let params = self.generate_random_params()?;
+            let params = self.generate_random_params(&mut rng)?;
 
-            let result = match self.run_backtest(&params).await {
+            let result = match self.run_backtest(config, strategy.clone(), data, &params).await {
                 Ok(r) => r,
                 Err(e) => {
                     eprintln!("Epoch {} failed: {}", epoch, e);
@@ -70,6 +88,9 @@ impl Hyperopt {
             }
         }
 
+        if results.is_empty() {
+            return Err(AppError::Backtest("All hyperopt epochs failed".to_string()));
+        }
         Ok(OptimizerResult {
             best_epoch,
             best_loss,
@@ -78,16 +99,19 @@ impl Hyperopt {
         })
     }
 
-    fn generate_random_params(&self) -> Result<HyperoptParams> {
+    fn generate_random_params(&self, rng: &mut StdRng) -> Result<HyperoptParams> {
         let mut params = HyperoptParams::new();
 
         for space in &self.config.spaces {
             let value = match space.as_str() {
-                "buy" => HyperoptValue::Float(1.0),
-                "sell" => HyperoptValue::Float(1.0),
-                "roi" => HyperoptValue::Float(0.5),
-                "stoploss" => HyperoptValue::Float(-0.05),
-                _ => HyperoptValue::Float(0.0),
+                "roi" => HyperoptValue::Float(round3(rng.gen_range(0.01..0.30))),
+                "stoploss" => HyperoptValue::Float(round3(rng.gen_range(-0.35..-0.02))),
+                _ => {
+                    return Err(AppError::InvalidInput(format!(
+                        "Unknown hyperopt space '{}', available: stoploss, roi",
+                        space
+                    )));
+                }
             };
             params.insert(space.clone(), value);
         }
@@ -95,17 +119,15 @@ impl Hyperopt {
         Ok(params)
     }
 
-    async fn run_backtest(&self, _params: &HyperoptParams) -> Result<BacktestResult> {
-        let config = BacktestConfig {
-            start_date: chrono::Utc::now() - chrono::Duration::days(30),
-            end_date: chrono::Utc::now(),
-            stake_amount: 100.0,
-            commission: 0.0,
-        };
-
-        let data = Vec::new();
-        let strategy = Arc::new(StubStrategy);
-        let mut engine = BacktestEngine::new(config, strategy, data);
+    async fn run_backtest(
+        &self,
+        config: &BacktestConfig,
+        strategy: Arc<dyn Strategy>,
+        data: &BTreeMap<String, Vec<OHLCV>>,
+        params: &HyperoptParams,
+    ) -> Result<BacktestResult> {
+        let config = apply_params(config, params);
+        let mut engine = BacktestEngine::new(config, strategy, data.clone());
         engine.run().await
     }
 
@@ -122,27 +144,57 @@ pub struct OptimizerResult {
     pub epoch_results: Vec<HyperoptEpoch>,
 }
 
-struct StubStrategy;
-
-#[async_trait::async_trait]
-impl crate::strategy::Strategy for StubStrategy {
-    fn name(&self) -> &str {
-        "Stub"
+/// `config` with the sampled `stoploss` and `roi` (take-profit) applied
+pub fn apply_params(config: &BacktestConfig, params: &HyperoptParams) -> BacktestConfig {
+    let mut config = config.clone();
+    if let Some(stoploss) = params.get("stoploss").and_then(HyperoptValue::as_float) {
+        config.stoploss = Some(stoploss);
     }
-
-    fn timeframes(&self) -> &[crate::types::Timeframe] {
-        &[crate::types::Timeframe::OneHour]
+    if let Some(take_profit) = params.get("roi").and_then(HyperoptValue::as_float) {
+        config.take_profit = Some(take_profit);
     }
+    config
+}
 
-    async fn populate_indicators(&mut self, _data: &mut Vec<crate::types::OHLCV>) -> Result<()> {
-        Ok(())
-    }
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
 
-    async fn populate_buy_trend(&self, _data: &[crate::types::OHLCV]) -> Result<Vec<crate::types::Signal>> {
-        Ok(vec![])
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Timeframe;
+
+    #[tokio::test]
+    async fn test_unknown_space_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Arc::new(Repository::new(dir.path().join("hyperopt.db")).await.unwrap());
+        let hyperopt = Hyperopt::new(
+            repository,
+            HyperoptConfig {
+                epochs: 3,
+                spaces: vec!["stoploss".to_string(), "buy".to_string()],
+                strategy: "SimpleStrategy".to_string(),
+                seed: Some(1),
+            },
+        );
+        let config = BacktestConfig {
+            timeframe: Timeframe::OneHour,
+            start_date: chrono::Utc::now(),
+            end_date: chrono::Utc::now(),
+            stake_amount: 100.0,
+            starting_balance: 1000.0,
+            max_open_trades: 1,
+            commission: 0.0,
+            slippage: 0.0,
+            stoploss: None,
+            take_profit: None,
+        };
+        let data = BTreeMap::from([("BTC/USDT".to_string(), Vec::new())]);
 
-    async fn populate_sell_trend(&self, _data: &[crate::types::OHLCV]) -> Result<Vec<crate::types::Signal>> {
-        Ok(vec![])
+        let result = hyperopt
+            .optimize(&config, Arc::new(crate::strategy::SimpleStrategy::new()), &data)
+            .await;
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
     }
 }
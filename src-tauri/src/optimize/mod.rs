@@ -3,9 +3,11 @@ pub mod hyperopt;
 pub mod loss_functions;
 pub mod optimizer;
 pub mod space;
+pub mod walk_forward;
 
 pub use hyperopt::{Hyperopt, HyperoptConfig, HyperoptEpoch};
 pub use loss_functions::{CalmarLoss, LossFunction, LossFunctionType, ProfitFactorLoss, SharpeLoss, SortinoLoss};
 pub use optimizer::{EpochResult, Optimizer, OptimizerConfig, OptimizerResult, RandomOptimizer};
 pub use space::HyperoptParams;
 pub use space::{HyperoptSpace, HyperoptValue};
+pub use walk_forward::{WalkForward, WalkForwardConfig, WalkForwardResult, WalkForwardWindow};
@@ -0,0 +1,461 @@
+use crate::backtest::metrics;
+use crate::backtest::{BacktestConfig, BacktestEngine};
+use crate::error::{AppError, Result};
+use crate::optimize::hyperopt::apply_params;
+use crate::optimize::{Hyperopt, HyperoptConfig, HyperoptParams};
+use crate::persistence::Repository;
+use crate::strategy::Strategy;
+use crate::types::*;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WalkForwardConfig {
+    /// Length of each optimization window
+    pub in_sample_days: i64,
+    /// Length of the window each optimum is validated on; windows roll forward by this much
+    pub out_of_sample_days: i64,
+    pub hyperopt: HyperoptConfig,
+}
+
+/// Metrics of one backtest within a walk-forward window
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WindowStats {
+    pub total_trades: usize,
+    pub win_rate: f64,
+    pub total_profit: Decimal,
+    /// Total profit relative to the starting balance
+    pub profit_ratio: f64,
+    pub max_drawdown: f64,
+    pub sharpe_ratio: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WalkForwardWindow {
+    pub index: usize,
+    pub in_sample_start: DateTime<Utc>,
+    pub in_sample_end: DateTime<Utc>,
+    pub out_of_sample_start: DateTime<Utc>,
+    pub out_of_sample_end: DateTime<Utc>,
+    pub best_params: HyperoptParams,
+    pub in_sample_loss: f64,
+    pub in_sample: WindowStats,
+    pub out_of_sample: WindowStats,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WalkForwardResult {
+    pub windows: Vec<WalkForwardWindow>,
+    /// All out-of-sample trades evaluated together
+    pub out_of_sample: BacktestResult,
+    /// Balance after every out-of-sample trade, the windows chained one after another
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+pub struct WalkForward {
+    config: WalkForwardConfig,
+    hyperopt: Hyperopt,
+}
+
+impl WalkForward {
+    pub fn new(repository: Arc<Repository>, config: WalkForwardConfig) -> Self {
+        let hyperopt = Hyperopt::new(repository, config.hyperopt.clone());
+        Self { config, hyperopt }
+    }
+
+    /// Split the period of `data` into rolling windows, optimize on each in-sample window and
+    /// backtest the best parameters on the out-of-sample window that follows it. The last
+    /// out-of-sample window is cut short at the end of the data.
+    pub async fn run(
+        &self,
+        config: &BacktestConfig,
+        strategy: Arc<dyn Strategy>,
+        data: &BTreeMap<String, Vec<OHLCV>>,
+    ) -> Result<WalkForwardResult> {
+        if self.config.in_sample_days <= 0 || self.config.out_of_sample_days <= 0 {
+            return Err(AppError::InvalidInput(
+                "In-sample and out-of-sample windows must be at least one day".to_string(),
+            ));
+        }
+        let start = data.values().filter_map(|c| c.first()).map(|c| c.timestamp).min();
+        let end = data.values().filter_map(|c| c.last()).map(|c| c.timestamp).max();
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(AppError::Backtest("No candles to walk forward on".to_string()));
+        };
+        // Candles before the configured start are startup candles for the first window
+        let start = start.max(config.start_date);
+
+        let in_sample = Duration::days(self.config.in_sample_days);
+        let out_of_sample = Duration::days(self.config.out_of_sample_days);
+        let mut windows = Vec::new();
+        let mut oos_trades = Vec::new();
+
+        let startup_candles = strategy.startup_candle_count();
+        let mut window_start = start;
+        while window_start + in_sample < end {
+            let split = window_start + in_sample;
+            let window_end = (split + out_of_sample).min(end);
+
+            let is_config = window_config(config, window_start, split);
+            let is_data = window_data(data, window_start, split, startup_candles);
+            let optimum = self.hyperopt.optimize(&is_config, strategy.clone(), &is_data).await?;
+            let best = optimum
+                .epoch_results
+                .iter()
+                .find(|e| e.epoch == optimum.best_epoch)
+                .and_then(|e| e.result.as_ref());
+
+            let oos_config = apply_params(&window_config(config, split, window_end), &optimum.best_params);
+            let oos_data = window_data(data, split, window_end, startup_candles);
+            let oos_result = BacktestEngine::new(oos_config, strategy.clone(), oos_data)
+                .run()
+                .await?;
+
+            windows.push(WalkForwardWindow {
+                index: windows.len(),
+                in_sample_start: window_start,
+                in_sample_end: split,
+                out_of_sample_start: split,
+                out_of_sample_end: window_end,
+                best_params: optimum.best_params,
+                in_sample_loss: optimum.best_loss,
+                in_sample: best.map(window_stats).unwrap_or_else(empty_stats),
+                out_of_sample: window_stats(&oos_result),
+            });
+            oos_trades.extend(oos_result.trades);
+            window_start += out_of_sample;
+        }
+
+        let Some(first) = windows.first() else {
+            return Err(AppError::Backtest(format!(
+                "{} days of data do not cover an in-sample window of {} days",
+                (end - start).num_days(),
+                self.config.in_sample_days
+            )));
+        };
+        let last = windows.last().expect("windows is not empty");
+        let oos_config = window_config(config, first.out_of_sample_start, last.out_of_sample_end);
+        let pairs: Vec<String> = data.keys().cloned().collect();
+        let oos_data: BTreeMap<String, Vec<OHLCV>> = data
+            .iter()
+            .map(|(pair, candles)| {
+                let in_window =
+                    |c: &&OHLCV| c.timestamp >= first.out_of_sample_start && c.timestamp < last.out_of_sample_end;
+                (pair.clone(), candles.iter().filter(in_window).cloned().collect())
+            })
+            .collect();
+        let out_of_sample = metrics::backtest_result(
+            strategy.name(),
+            &oos_config,
+            &pairs,
+            oos_trades,
+            metrics::market_change(&oos_data, first.out_of_sample_start),
+        );
+
+        Ok(WalkForwardResult {
+            equity_curve: equity_curve(&out_of_sample),
+            out_of_sample,
+            windows,
+        })
+    }
+}
+
+fn window_config(config: &BacktestConfig, start: DateTime<Utc>, end: DateTime<Utc>) -> BacktestConfig {
+    BacktestConfig {
+        start_date: start,
+        end_date: end,
+        ..config.clone()
+    }
+}
+
+/// Candles from `start` up to but excluding `end`, preceded by the strategy's startup candles the
+/// engine skips so signals can fire from the start of the window
+fn window_data(
+    data: &BTreeMap<String, Vec<OHLCV>>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    startup_candles: usize,
+) -> BTreeMap<String, Vec<OHLCV>> {
+    data.iter()
+        .map(|(pair, candles)| {
+            let from = candles.partition_point(|c| c.timestamp < start);
+            let to = candles.partition_point(|c| c.timestamp < end);
+            let from = from.saturating_sub(startup_candles).min(to);
+            (pair.clone(), candles[from..to].to_vec())
+        })
+        .collect()
+}
+
+fn window_stats(result: &BacktestResult) -> WindowStats {
+    let starting_balance = Decimal::try_from(result.starting_balance).unwrap_or(Decimal::ZERO);
+    WindowStats {
+        total_trades: result.total_trades,
+        win_rate: result.win_rate,
+        total_profit: result.total_profit,
+        profit_ratio: if starting_balance.is_zero() {
+            0.0
+        } else {
+            (result.total_profit / starting_balance).to_f64().unwrap_or(0.0)
+        },
+        max_drawdown: result.max_drawdown,
+        sharpe_ratio: result.sharpe_ratio,
+    }
+}
+
+fn empty_stats() -> WindowStats {
+    WindowStats {
+        total_trades: 0,
+        win_rate: 0.0,
+        total_profit: Decimal::ZERO,
+        profit_ratio: 0.0,
+        max_drawdown: 0.0,
+        sharpe_ratio: 0.0,
+    }
+}
+
+fn equity_curve(result: &BacktestResult) -> Vec<EquityPoint> {
+    let mut balance = result.starting_balance;
+    let mut closed: Vec<&Trade> = result.trades.iter().filter(|t| !t.is_open).collect();
+    closed.sort_by_key(|t| t.close_date);
+
+    let mut curve = vec![EquityPoint {
+        time: result.start_date.to_rfc3339(),
+        value: balance,
+    }];
+    for trade in closed {
+        if let Some(close_date) = trade.close_date {
+            balance += trade.profit_abs.and_then(|p| p.to_f64()).unwrap_or(0.0);
+            curve.push(EquityPoint {
+                time: close_date.to_rfc3339(),
+                value: balance,
+            });
+        }
+    }
+    curve
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Buys every 24th candle and sells 12 candles later
+    struct Daily;
+
+    #[async_trait::async_trait]
+    impl Strategy for Daily {
+        fn name(&self) -> &str {
+            "Daily"
+        }
+
+        fn timeframes(&self) -> &[Timeframe] {
+            &[Timeframe::OneHour]
+        }
+
+        fn startup_candle_count(&self) -> usize {
+            24
+        }
+
+        async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn populate_buy_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(signal_at(data, 0, SignalType::Buy))
+        }
+
+        async fn populate_sell_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>> {
+            Ok(signal_at(data, 12, SignalType::Sell))
+        }
+    }
+
+    fn signal_at(data: &[OHLCV], hour: u32, r#type: SignalType) -> Vec<Signal> {
+        use chrono::Timelike;
+        let index = data.len() - 1;
+        if data[index].timestamp.hour() != hour {
+            return vec![];
+        }
+        vec![Signal {
+            index,
+            r#type,
+            strength: 1.0,
+        }]
+    }
+
+    fn data(days: i64) -> BTreeMap<String, Vec<OHLCV>> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let candles = (0..days * 24)
+            .map(|i| {
+                let price = Decimal::from(100 + (i % 24) - (i % 5) * 2);
+                OHLCV {
+                    timestamp: start + Duration::hours(i),
+                    open: price,
+                    high: price + Decimal::from(2),
+                    low: price - Decimal::from(4),
+                    close: price,
+                    volume: Decimal::ONE,
+                }
+            })
+            .collect();
+        BTreeMap::from([("BTC/USDT".to_string(), candles)])
+    }
+
+    fn config() -> BacktestConfig {
+        BacktestConfig {
+            timeframe: Timeframe::OneHour,
+            start_date: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            end_date: Utc::now(),
+            stake_amount: 100.0,
+            starting_balance: 1000.0,
+            max_open_trades: 1,
+            commission: 0.001,
+            slippage: 0.0,
+            stoploss: None,
+            take_profit: None,
+        }
+    }
+
+    async fn walk_forward(in_sample_days: i64, out_of_sample_days: i64) -> WalkForward {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Arc::new(Repository::new(dir.path().join("wf.db")).await.unwrap());
+        WalkForward::new(
+            repository,
+            WalkForwardConfig {
+                in_sample_days,
+                out_of_sample_days,
+                hyperopt: HyperoptConfig {
+                    epochs: 5,
+                    spaces: vec!["stoploss".to_string(), "roi".to_string()],
+                    strategy: "Daily".to_string(),
+                    seed: Some(7),
+                },
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_candles_before_start_date_are_startup_candles() {
+        let data = data(20);
+        let start = Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap();
+        let config = BacktestConfig {
+            start_date: start,
+            ..config()
+        };
+        let result = walk_forward(10, 7)
+            .await
+            .run(&config, Arc::new(Daily), &data)
+            .await
+            .unwrap();
+
+        assert_eq!(result.windows[0].in_sample_start, start);
+        assert_eq!(result.windows[0].out_of_sample_start, start + Duration::days(10));
+    }
+
+    #[tokio::test]
+    async fn test_rolling_windows_and_stitched_out_of_sample() {
+        let data = data(40);
+        let result = walk_forward(10, 7)
+            .await
+            .run(&config(), Arc::new(Daily), &data)
+            .await
+            .unwrap();
+
+        // In-sample windows start on days 0, 7, 14, 21 and 28; the last out-of-sample window
+        // is cut short at the end of the data
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bounds: Vec<(i64, i64, i64)> = result
+            .windows
+            .iter()
+            .map(|w| {
+                (
+                    (w.in_sample_start - start).num_days(),
+                    (w.out_of_sample_start - start).num_days(),
+                    (w.out_of_sample_end - start).num_hours(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            bounds,
+            vec![
+                (0, 10, 17 * 24),
+                (7, 17, 24 * 24),
+                (14, 24, 31 * 24),
+                (21, 31, 38 * 24),
+                (28, 38, 40 * 24 - 1)
+            ]
+        );
+
+        for window in &result.windows {
+            assert!(window.best_params.get("stoploss").is_some());
+            assert!(window.out_of_sample.total_trades > 0);
+        }
+
+        // Every stitched trade lies in an out-of-sample window
+        let oos = &result.out_of_sample;
+        assert_eq!(
+            oos.total_trades,
+            result
+                .windows
+                .iter()
+                .map(|w| w.out_of_sample.total_trades)
+                .sum::<usize>()
+        );
+        assert!(
+            oos.trades
+                .iter()
+                .all(|t| t.open_date >= result.windows[0].out_of_sample_start)
+        );
+        assert_eq!(oos.start_date, result.windows[0].out_of_sample_start);
+
+        assert_eq!(result.equity_curve.len(), oos.total_trades + 1);
+        let final_balance = result.equity_curve.last().unwrap().value;
+        let expected = 1000.0 + oos.total_profit.to_f64().unwrap();
+        assert!((final_balance - expected).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_runs_are_reproducible() {
+        let data = data(30);
+        let first = walk_forward(10, 10)
+            .await
+            .run(&config(), Arc::new(Daily), &data)
+            .await
+            .unwrap();
+        let second = walk_forward(10, 10)
+            .await
+            .run(&config(), Arc::new(Daily), &data)
+            .await
+            .unwrap();
+
+        let params = |r: &WalkForwardResult| -> Vec<Option<f64>> {
+            r.windows
+                .iter()
+                .map(|w| w.best_params.get("stoploss").and_then(|v| v.as_float()))
+                .collect()
+        };
+        assert_eq!(params(&first), params(&second));
+        assert_eq!(first.out_of_sample.total_profit, second.out_of_sample.total_profit);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_data_shorter_than_in_sample_window() {
+        let err = walk_forward(10, 5)
+            .await
+            .run(&config(), Arc::new(Daily), &data(8))
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, AppError::Backtest(_)));
+
+        let err = walk_forward(0, 5)
+            .await
+            .run(&config(), Arc::new(Daily), &data(8))
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, AppError::InvalidInput(_)));
+    }
+}
@@ -3,15 +3,15 @@ use crate::types::*;
 use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
-use sqlx::Row;
 use sqlx::sqlite::SqlitePool;
+use sqlx::{Connection, Row};
 use std::path::Path;
 use std::sync::Arc;
 use uuid::Uuid;
 
 /// Parse timeframe string to Duration
 /// Returns error for unrecognized timeframes to prevent silent data corruption
-fn parse_timeframe_to_duration(timeframe: &str) -> Result<Duration> {
+pub fn parse_timeframe_to_duration(timeframe: &str) -> Result<Duration> {
     let tf = timeframe.trim().to_lowercase();
     match tf.as_str() {
         "1m" | "1 minute" => Ok(Duration::minutes(1)),
@@ -67,19 +67,45 @@ impl Repository {
     }
 
     async fn run_migrations(pool: &SqlitePool) -> Result<()> {
-        let migration_sql = include_str!("../../../migrations/001_initial.sql");
-        for statement in migration_sql.split(";").map(|s| s.trim()).filter(|s| !s.is_empty()) {
-            sqlx::query(statement)
-                .execute(pool)
-                .await
-                .map_err(|e| AppError::Database(e.to_string()))?;
+        let migrations = [
+            (1, include_str!("../../../migrations/001_initial.sql")),
+            (2, include_str!("../../../migrations/002_futures.sql")),
+        ];
+        // 所有迁移在同一个连接上执行，避免其他连接缓存旧表结构
+        let mut conn = pool.acquire().await.map_err(|e| AppError::Database(e.to_string()))?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (version INTEGER PRIMARY KEY, applied_at TEXT NOT NULL DEFAULT (datetime('now')))",
+        )
+        .execute(&mut *conn)
+        .await?;
+        let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM schema_migrations")
+            .fetch_all(&mut *conn)
+            .await?;
+
+        // 记录迁移版本之前创建的数据库没有版本记录，001 全部为 IF NOT EXISTS，可安全重跑
+        for (version, migration_sql) in migrations {
+            if applied.contains(&version) {
+                continue;
+            }
+            let mut tx = conn.begin().await?;
+            for statement in migration_sql.split(";").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                sqlx::query(statement)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+            }
+            sqlx::query("INSERT INTO schema_migrations (version) VALUES (?)")
+                .bind(version)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
         }
         Ok(())
     }
 
     pub async fn create_trade(&self, trade: &Trade) -> Result<()> {
         let exit_reason = trade.exit_reason.map(|e| e.to_string());
-        sqlx::query("INSERT INTO trades (id, pair, is_open, exchange, open_rate, open_date, close_rate, close_date, amount, stake_amount, strategy, timeframe, stop_loss, take_profit, exit_reason, profit_abs, profit_ratio) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+        sqlx::query("INSERT INTO trades (id, pair, is_open, exchange, open_rate, open_date, close_rate, close_date, amount, stake_amount, strategy, timeframe, stop_loss, take_profit, exit_reason, profit_abs, profit_ratio, is_short, leverage, liquidation_price) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
             .bind(trade.id.to_string()).bind(&trade.pair).bind(trade.is_open as i32)
             .bind(&trade.exchange).bind(trade.open_rate.to_string()).bind(trade.open_date.to_rfc3339())
             .bind(trade.close_rate.map(|v| v.to_string())).bind(trade.close_date.map(|d| d.to_rfc3339()))
@@ -87,6 +113,8 @@ impl Repository {
             .bind(trade.timeframe.as_str()).bind(trade.stop_loss.map(|v| v.to_string()))
             .bind(trade.take_profit.map(|v| v.to_string())).bind(exit_reason)
             .bind(trade.profit_abs.map(|v| v.to_string())).bind(trade.profit_ratio.map(|v| v.to_string()))
+            .bind(trade.is_short as i32).bind(trade.leverage.to_string())
+            .bind(trade.liquidation_price.map(|v| v.to_string()))
             .execute(&*self.pool).await?;
         Ok(())
     }
@@ -105,7 +133,9 @@ impl Repository {
         rows.iter().map(|row| self.row_to_trade(row)).collect()
     }
 
-    pub async fn get_dashboard_stats(&self) -> Result<DashboardStats> {
+    /// Trade statistics; drawdown and `total_balance` are derived from closed-trade
+    /// profits on top of `starting_balance`
+    pub async fn get_dashboard_stats(&self, starting_balance: Decimal) -> Result<DashboardStats> {
         let stats_row = sqlx::query(
             r#"
             SELECT
@@ -137,8 +167,8 @@ impl Repository {
             .await?;
 
         // Same logic as in commands.rs
-        let mut peak_balance = Decimal::from(10000_i64);
-        let mut current_balance = Decimal::from(10000_i64);
+        let mut peak_balance = starting_balance;
+        let mut current_balance = starting_balance;
         let mut max_drawdown = 0.0;
 
         for row in rows {
@@ -154,6 +184,9 @@ impl Repository {
             if current_balance > peak_balance {
                 peak_balance = current_balance;
             }
+            if peak_balance <= Decimal::ZERO {
+                continue;
+            }
             let drawdown = (peak_balance - current_balance) / peak_balance * Decimal::from(100_i64);
             if drawdown > Decimal::try_from(max_drawdown).unwrap_or(Decimal::ZERO) {
                 max_drawdown = drawdown.to_f64().unwrap_or(0.0);
@@ -169,17 +202,99 @@ impl Repository {
         })
     }
 
+    pub async fn get_trade(&self, id: Uuid) -> Result<Option<Trade>> {
+        let row = sqlx::query("SELECT * FROM trades WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&*self.pool)
+            .await?;
+        row.map(|row| self.row_to_trade(&row)).transpose()
+    }
+
+    pub async fn delete_trade(&self, id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM trades WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
     pub async fn update_trade(&self, trade: &Trade) -> Result<()> {
         let exit_reason = trade.exit_reason.map(|e| e.to_string());
-        sqlx::query("UPDATE trades SET close_rate = ?, close_date = ?, stop_loss = ?, take_profit = ?, exit_reason = ?, profit_abs = ?, profit_ratio = ?, is_open = ?, updated_at = datetime('now') WHERE id = ?")
+        sqlx::query("UPDATE trades SET open_rate = ?, amount = ?, stake_amount = ?, close_rate = ?, close_date = ?, stop_loss = ?, take_profit = ?, exit_reason = ?, profit_abs = ?, profit_ratio = ?, is_open = ?, liquidation_price = ?, updated_at = datetime('now') WHERE id = ?")
+            .bind(trade.open_rate.to_string()).bind(trade.amount.to_string()).bind(trade.stake_amount.to_string())
             .bind(trade.close_rate.map(|v| v.to_string())).bind(trade.close_date.map(|d| d.to_rfc3339()))
             .bind(trade.stop_loss.map(|v| v.to_string())).bind(trade.take_profit.map(|v| v.to_string()))
             .bind(exit_reason).bind(trade.profit_abs.map(|v| v.to_string()))
             .bind(trade.profit_ratio.map(|v| v.to_string())).bind(trade.is_open as i32)
+            .bind(trade.liquidation_price.map(|v| v.to_string()))
             .bind(trade.id.to_string()).execute(&*self.pool).await?;
         Ok(())
     }
 
+    /// Insert or update an order. An existing trade link is kept when `trade_id` is `None`,
+    /// so stream updates can refresh an order without knowing which trade placed it.
+    pub async fn save_order(&self, order: &Order, trade_id: Option<Uuid>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO orders (id, trade_id, symbol, side, order_type, status, price, amount, filled, remaining, fee, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                trade_id = COALESCE(excluded.trade_id, orders.trade_id),
+                status = excluded.status,
+                price = excluded.price,
+                amount = excluded.amount,
+                filled = excluded.filled,
+                remaining = excluded.remaining,
+                fee = COALESCE(excluded.fee, orders.fee),
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&order.id)
+        .bind(trade_id.map(|id| id.to_string()))
+        .bind(&order.symbol)
+        .bind(order.side.to_string())
+        .bind(order.order_type.to_string())
+        .bind(order.status.to_string())
+        .bind(order.price.map(|v| v.to_string()))
+        .bind(order.amount.to_string())
+        .bind(order.filled.to_string())
+        .bind(order.remaining.to_string())
+        .bind(order.fee.map(|v| v.to_string()))
+        .bind(order.created_at.to_rfc3339())
+        .bind(order.updated_at.to_rfc3339())
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Trade an order belongs to, if the order is known and linked
+    pub async fn get_order_trade_id(&self, order_id: &str) -> Result<Option<Uuid>> {
+        let trade_id: Option<Option<String>> = sqlx::query_scalar("SELECT trade_id FROM orders WHERE id = ?")
+            .bind(order_id)
+            .fetch_optional(&*self.pool)
+            .await?;
+        trade_id
+            .flatten()
+            .map(|id| Uuid::parse_str(&id).map_err(|e| AppError::Parse(format!("Invalid UUID: {}", e))))
+            .transpose()
+    }
+
+    /// Symbol an order was placed on, if the order is known
+    pub async fn get_order_symbol(&self, order_id: &str) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar("SELECT symbol FROM orders WHERE id = ?")
+            .bind(order_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+    }
+
+    pub async fn get_orders_by_trade(&self, trade_id: Uuid) -> Result<Vec<Order>> {
+        let rows = sqlx::query("SELECT * FROM orders WHERE trade_id = ? ORDER BY created_at")
+            .bind(trade_id.to_string())
+            .fetch_all(&*self.pool)
+            .await?;
+        rows.iter().map(|row| self.row_to_order(row)).collect()
+    }
+
     pub async fn save_klines(&self, pair: &str, timeframe: &str, klines: &[OHLCV]) -> Result<()> {
         // Early return for empty klines to avoid unnecessary transaction overhead
         if klines.is_empty() {
@@ -225,8 +340,120 @@ impl Repository {
         rows.iter().map(|row| self.row_to_kline(row)).collect()
     }
 
+    /// Candles opened within `[start, end)` in chronological order; `None` leaves that side open
+    pub async fn get_klines_between(
+        &self,
+        pair: &str,
+        timeframe: &str,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<OHLCV>> {
+        let mut query: sqlx::QueryBuilder<sqlx::Sqlite> = sqlx::QueryBuilder::new("SELECT * FROM klines WHERE pair = ");
+        query.push_bind(pair).push(" AND timeframe = ").push_bind(timeframe);
+        // open_time is stored as RFC 3339 in UTC, so string order is time order
+        if let Some(start) = start {
+            query.push(" AND open_time >= ").push_bind(start.to_rfc3339());
+        }
+        if let Some(end) = end {
+            query.push(" AND open_time < ").push_bind(end.to_rfc3339());
+        }
+        query.push(" ORDER BY open_time ASC");
+
+        let rows = query.build().fetch_all(&*self.pool).await?;
+        rows.iter().map(|row| self.row_to_kline(row)).collect()
+    }
+
+    /// Open times of the oldest and newest stored candle of a pair/timeframe
+    pub async fn get_kline_range(&self, pair: &str, timeframe: &str) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        let row = sqlx::query(
+            "SELECT MIN(open_time) AS first, MAX(open_time) AS last FROM klines WHERE pair = ? AND timeframe = ?",
+        )
+        .bind(pair)
+        .bind(timeframe)
+        .fetch_one(&*self.pool)
+        .await?;
+
+        let parse = |col: &str| -> Result<Option<DateTime<Utc>>> {
+            row.get::<Option<&str>, _>(col)
+                .map(|s| {
+                    DateTime::parse_from_rfc3339(s)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .map_err(|e| AppError::Parse(format!("Invalid datetime: {}", e)))
+                })
+                .transpose()
+        };
+        Ok(parse("first")?.zip(parse("last")?))
+    }
+
+    /// Every stored pair/timeframe with its candle range, ordered by pair and timeframe
+    pub async fn get_available_data(&self) -> Result<Vec<AvailableData>> {
+        let rows = sqlx::query(
+            "SELECT pair, timeframe, MIN(open_time) AS first, MAX(open_time) AS last, COUNT(*) AS candles FROM klines GROUP BY pair, timeframe ORDER BY pair, timeframe",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                let date = |col: &str| -> Result<DateTime<Utc>> {
+                    DateTime::parse_from_rfc3339(row.get(col))
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .map_err(|e| AppError::Parse(format!("Invalid datetime: {}", e)))
+                };
+                Ok(AvailableData {
+                    pair: row.get("pair"),
+                    timeframe: row.get("timeframe"),
+                    start_date: date("first")?,
+                    end_date: date("last")?,
+                    candles_count: row.get::<i64, _>("candles") as usize,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn save_data_download(&self, download: &DataDownload) -> Result<i64> {
+        let row = sqlx::query("INSERT INTO data_downloads (pair, timeframe, start_date, end_date, candles_count, download_time) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(&download.pair)
+            .bind(&download.timeframe)
+            .bind(download.start_date.to_rfc3339())
+            .bind(download.end_date.to_rfc3339())
+            .bind(download.candles_count as i64)
+            .bind(download.download_time.to_rfc3339())
+            .execute(&*self.pool)
+            .await?;
+        Ok(row.last_insert_rowid())
+    }
+
+    /// Downloader runs, newest first
+    pub async fn get_data_downloads(&self) -> Result<Vec<DataDownload>> {
+        let rows = sqlx::query("SELECT * FROM data_downloads ORDER BY id DESC")
+            .fetch_all(&*self.pool)
+            .await?;
+        rows.iter()
+            .map(|row| {
+                let date = |col: &str| -> Result<DateTime<Utc>> {
+                    let value: &str = row.get(col);
+                    DateTime::parse_from_rfc3339(value)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        // download_time defaults to SQLite's datetime('now') format
+                        .or_else(|_| {
+                            chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map(|dt| dt.and_utc())
+                        })
+                        .map_err(|e| AppError::Parse(format!("Invalid datetime in {}: {}", col, e)))
+                };
+                Ok(DataDownload {
+                    pair: row.get("pair"),
+                    timeframe: row.get("timeframe"),
+                    start_date: date("start_date")?,
+                    end_date: date("end_date")?,
+                    candles_count: row.get::<i64, _>("candles_count") as usize,
+                    download_time: date("download_time")?,
+                })
+            })
+            .collect()
+    }
+
     pub async fn save_backtest_result(&self, result: &BacktestResult) -> Result<i64> {
-        let row = sqlx::query("INSERT INTO backtest_results (strategy, pair, timeframe, start_date, end_date, total_trades, winning_trades, losing_trades, win_rate, total_profit, max_drawdown, sharpe_ratio, profit_factor, avg_profit, avg_loss, config) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+        let row = sqlx::query("INSERT INTO backtest_results (strategy, pair, timeframe, start_date, end_date, total_trades, winning_trades, losing_trades, win_rate, total_profit, max_drawdown, sharpe_ratio, profit_factor, avg_profit, avg_loss, config) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
             .bind(&result.strategy)
             .bind(&result.pair)
             .bind(result.timeframe.as_str())
@@ -247,19 +474,54 @@ impl Repository {
         Ok(row.last_insert_rowid())
     }
 
-    pub async fn get_backtest_results(&self) -> Result<Vec<BacktestResult>> {
-        let rows = sqlx::query("SELECT * FROM backtest_results ORDER BY created_at DESC")
-            .fetch_all(&*self.pool)
-            .await?;
+    /// Saved runs, newest first, without their trades
+    pub async fn get_backtest_results(&self) -> Result<Vec<BacktestSummary>> {
+        let rows = sqlx::query(
+            "SELECT id, strategy, pair, timeframe, start_date, end_date, total_trades, win_rate, total_profit, \
+             max_drawdown, created_at FROM backtest_results ORDER BY created_at DESC, id DESC",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
         rows.iter()
             .map(|row| {
-                let config_json: String = row.get("config");
-                let result: BacktestResult = serde_json::from_str(&config_json).map_err(AppError::Serialization)?;
-                Ok(result)
+                let date = |col: &str| -> Result<DateTime<Utc>> {
+                    DateTime::parse_from_rfc3339(row.get(col))
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .map_err(|e| AppError::Parse(format!("Invalid datetime: {}", e)))
+                };
+                // created_at is filled by SQLite's datetime('now'), which has no offset
+                let created_at = chrono::NaiveDateTime::parse_from_str(row.get("created_at"), "%Y-%m-%d %H:%M:%S")
+                    .map_err(|e| AppError::Parse(format!("Invalid datetime: {}", e)))?
+                    .and_utc();
+
+                Ok(BacktestSummary {
+                    id: row.get("id"),
+                    strategy: row.get("strategy"),
+                    pair: row.get("pair"),
+                    timeframe: row.get::<&str, _>("timeframe").parse()?,
+                    start_date: date("start_date")?,
+                    end_date: date("end_date")?,
+                    total_trades: row.get::<i64, _>("total_trades") as usize,
+                    win_rate: row.get("win_rate"),
+                    total_profit: self.get_decimal(row, "total_profit")?,
+                    max_drawdown: row.get("max_drawdown"),
+                    created_at,
+                })
             })
             .collect()
     }
 
+    /// A saved run with all its trades
+    pub async fn get_backtest_result(&self, id: i64) -> Result<Option<BacktestResult>> {
+        let row = sqlx::query("SELECT config FROM backtest_results WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&*self.pool)
+            .await?;
+        row.map(|row| serde_json::from_str(row.get("config")).map_err(AppError::Serialization))
+            .transpose()
+    }
+
     fn get_decimal(&self, row: &sqlx::sqlite::SqliteRow, col: &str) -> Result<Decimal> {
         if let Ok(s) = row.try_get::<&str, _>(col) {
             s.parse()
@@ -328,6 +590,47 @@ impl Repository {
             }),
             profit_abs: self.get_opt_decimal(row, "profit_abs")?,
             profit_ratio: self.get_opt_decimal(row, "profit_ratio")?,
+            is_short: row.get::<i32, _>("is_short") != 0,
+            leverage: self.get_decimal(row, "leverage")?,
+            liquidation_price: self.get_opt_decimal(row, "liquidation_price")?,
+        })
+    }
+
+    fn row_to_order(&self, row: &sqlx::sqlite::SqliteRow) -> Result<Order> {
+        let parse_date = |column: &str| {
+            DateTime::parse_from_rfc3339(row.get(column))
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| AppError::Parse(format!("Invalid datetime: {}", e)))
+        };
+
+        Ok(Order {
+            id: row.get("id"),
+            symbol: row.get("symbol"),
+            side: match row.get::<&str, _>("side") {
+                "sell" => TradeSide::Sell,
+                _ => TradeSide::Buy,
+            },
+            order_type: match row.get::<&str, _>("order_type") {
+                "limit" => OrderType::Limit,
+                "stop_limit" => OrderType::StopLimit,
+                "stop_market" => OrderType::StopMarket,
+                _ => OrderType::Market,
+            },
+            status: match row.get::<&str, _>("status") {
+                "partially_filled" => OrderStatus::PartiallyFilled,
+                "filled" => OrderStatus::Filled,
+                "canceled" => OrderStatus::Canceled,
+                "rejected" => OrderStatus::Rejected,
+                "expired" => OrderStatus::Expired,
+                _ => OrderStatus::New,
+            },
+            price: self.get_opt_decimal(row, "price")?,
+            amount: self.get_decimal(row, "amount")?,
+            filled: self.get_decimal(row, "filled")?,
+            remaining: self.get_decimal(row, "remaining")?,
+            fee: self.get_opt_decimal(row, "fee")?,
+            created_at: parse_date("created_at")?,
+            updated_at: parse_date("updated_at")?,
         })
     }
 
@@ -377,6 +680,9 @@ mod tests {
             exit_reason: None,
             profit_abs: None,
             profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         repo.create_trade(&trade).await.unwrap();
@@ -387,4 +693,220 @@ mod tests {
         assert_eq!(trades[0].pair, trade.pair);
         assert_eq!(trades[0].open_rate, trade.open_rate);
     }
+
+    #[tokio::test]
+    async fn test_migrations_upgrade_existing_database_once() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        // 记录迁移版本之前的数据库：只执行过 001，没有合约字段
+        let url = format!("sqlite:{}?mode=rwc", db_path.to_string_lossy());
+        let pool = SqlitePool::connect(&url).await.unwrap();
+        let initial = include_str!("../../../migrations/001_initial.sql");
+        for statement in initial.split(";").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO trades (id, pair, is_open, exchange, open_rate, open_date, amount, stake_amount, strategy, timeframe) \
+             VALUES (?, 'BTC/USDT', 1, 'binance', '100', ?, '1', '100', 'test_strategy', '1h')",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+
+        let repo = Repository::new(&db_path).await.unwrap();
+        let trades = repo.get_open_trades().await.unwrap();
+        assert_eq!(trades.len(), 1);
+        assert!(!trades[0].is_short);
+        assert_eq!(trades[0].leverage, Decimal::ONE);
+        drop(repo);
+
+        // 再次打开时不会重复执行 002 的 ALTER TABLE
+        let repo = Repository::new(&db_path).await.unwrap();
+        let versions: Vec<i64> = sqlx::query_scalar("SELECT version FROM schema_migrations ORDER BY version")
+            .fetch_all(&*repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(versions, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_save_order_upsert_keeps_trade_link() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::new(dir.path().join("test.db")).await.unwrap();
+
+        let trade = Trade {
+            id: Uuid::new_v4(),
+            pair: "BTC/USDT".to_string(),
+            is_open: true,
+            exchange: "binance".to_string(),
+            open_rate: Decimal::from(100),
+            open_date: Utc::now(),
+            close_rate: None,
+            close_date: None,
+            amount: Decimal::from(2),
+            stake_amount: Decimal::from(200),
+            strategy: "test_strategy".to_string(),
+            timeframe: Timeframe::OneHour,
+            stop_loss: None,
+            take_profit: None,
+            exit_reason: None,
+            profit_abs: None,
+            profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
+        };
+        repo.create_trade(&trade).await.unwrap();
+
+        let mut order = Order {
+            id: "42".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: TradeSide::Buy,
+            order_type: OrderType::Limit,
+            status: OrderStatus::New,
+            price: Some(Decimal::from(100)),
+            amount: Decimal::from(2),
+            filled: Decimal::ZERO,
+            remaining: Decimal::from(2),
+            fee: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        repo.save_order(&order, Some(trade.id)).await.unwrap();
+
+        order.status = OrderStatus::PartiallyFilled;
+        order.filled = Decimal::from_str("0.5").unwrap();
+        order.remaining = Decimal::from_str("1.5").unwrap();
+        repo.save_order(&order, None).await.unwrap();
+
+        assert_eq!(repo.get_order_trade_id("42").await.unwrap(), Some(trade.id));
+        assert_eq!(repo.get_order_trade_id("missing").await.unwrap(), None);
+
+        let orders = repo.get_orders_by_trade(trade.id).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].status, OrderStatus::PartiallyFilled);
+        assert_eq!(orders[0].filled, Decimal::from_str("0.5").unwrap());
+        assert_eq!(orders[0].order_type, OrderType::Limit);
+    }
+
+    #[tokio::test]
+    async fn test_get_available_data_groups_by_pair_and_timeframe() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::new(dir.path().join("test.db")).await.unwrap();
+
+        let start = Utc::now() - Duration::hours(10);
+        let klines = |count: i64, step: Duration| -> Vec<OHLCV> {
+            (0..count)
+                .map(|i| OHLCV {
+                    timestamp: start + step * i as i32,
+                    open: Decimal::ONE,
+                    high: Decimal::ONE,
+                    low: Decimal::ONE,
+                    close: Decimal::ONE,
+                    volume: Decimal::ONE,
+                })
+                .collect()
+        };
+        repo.save_klines("ETH/USDT", "1h", &klines(3, Duration::hours(1)))
+            .await
+            .unwrap();
+        repo.save_klines("BTC/USDT", "1h", &klines(5, Duration::hours(1)))
+            .await
+            .unwrap();
+        repo.save_klines("BTC/USDT", "5m", &klines(2, Duration::minutes(5)))
+            .await
+            .unwrap();
+
+        let data = repo.get_available_data().await.unwrap();
+        let summary: Vec<_> = data
+            .iter()
+            .map(|d| (d.pair.as_str(), d.timeframe.as_str(), d.candles_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("BTC/USDT", "1h", 5), ("BTC/USDT", "5m", 2), ("ETH/USDT", "1h", 3)]
+        );
+        assert_eq!(data[0].end_date - data[0].start_date, Duration::hours(4));
+    }
+
+    #[tokio::test]
+    async fn test_get_klines_between_is_chronological_and_half_open() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::new(dir.path().join("test.db")).await.unwrap();
+
+        let start = Utc::now() - Duration::hours(10);
+        let klines: Vec<OHLCV> = (0..6)
+            .map(|i| OHLCV {
+                timestamp: start + Duration::hours(i),
+                open: Decimal::from(i),
+                high: Decimal::from(i),
+                low: Decimal::from(i),
+                close: Decimal::from(i),
+                volume: Decimal::ONE,
+            })
+            .collect();
+        repo.save_klines("BTC/USDT", "1h", &klines).await.unwrap();
+
+        let all = repo.get_klines_between("BTC/USDT", "1h", None, None).await.unwrap();
+        assert_eq!(all.len(), 6);
+        assert!(all.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        let range = repo
+            .get_klines_between("BTC/USDT", "1h", Some(klines[1].timestamp), Some(klines[4].timestamp))
+            .await
+            .unwrap();
+        let closes: Vec<Decimal> = range.iter().map(|k| k.close).collect();
+        assert_eq!(closes, vec![Decimal::from(1), Decimal::from(2), Decimal::from(3)]);
+
+        assert!(
+            repo.get_klines_between("BTC/USDT", "4h", None, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_list_and_reload_backtest_result() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::new(dir.path().join("test.db")).await.unwrap();
+
+        let result = BacktestResult {
+            strategy: "SimpleStrategy".to_string(),
+            pair: "BTC/USDT,ETH/USDT".to_string(),
+            timeframe: Timeframe::FourHours,
+            start_date: Utc::now() - Duration::days(30),
+            end_date: Utc::now(),
+            total_trades: 2,
+            winning_trades: 1,
+            losing_trades: 1,
+            win_rate: 0.5,
+            total_profit: Decimal::from_str("12.5").unwrap(),
+            max_drawdown: 0.02,
+            sharpe_ratio: 1.1,
+            profit_factor: 2.0,
+            avg_profit: Decimal::from(25),
+            avg_loss: Decimal::from_str("-12.5").unwrap(),
+            trades: vec![],
+            per_pair: vec![],
+            starting_balance: 1000.0,
+            market_change: 0.1,
+        };
+        let first = repo.save_backtest_result(&result).await.unwrap();
+        let second = repo.save_backtest_result(&result).await.unwrap();
+
+        let runs = repo.get_backtest_results().await.unwrap();
+        assert_eq!(runs.iter().map(|r| r.id).collect::<Vec<_>>(), vec![second, first]);
+        assert_eq!(runs[0].timeframe, Timeframe::FourHours);
+        assert_eq!(runs[0].total_profit, Decimal::from_str("12.5").unwrap());
+
+        let reloaded = repo.get_backtest_result(first).await.unwrap().unwrap();
+        assert_eq!(reloaded.pair, result.pair);
+        assert_eq!(reloaded.avg_loss, result.avg_loss);
+        assert!(repo.get_backtest_result(first + 100).await.unwrap().is_none());
+    }
 }
@@ -42,6 +42,9 @@ mod repository_tests {
             exit_reason: None,
             profit_abs: None,
             profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         let trade2 = Trade {
@@ -62,6 +65,9 @@ mod repository_tests {
             exit_reason: None,
             profit_abs: None,
             profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         // Same ID and same values should be equal
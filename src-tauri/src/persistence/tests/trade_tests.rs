@@ -33,6 +33,9 @@ mod trade_tests {
             exit_reason: None,
             profit_abs: None,
             profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         assert!(trade.is_open);
@@ -63,6 +66,9 @@ mod trade_tests {
             exit_reason: Some(crate::types::ExitType::Signal),
             profit_abs: Some(Decimal::from_str("5.7").unwrap()),
             profit_ratio: Some(Decimal::from_str("0.095").unwrap()),
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         assert!(!trade.is_open);
@@ -117,6 +123,9 @@ mod trade_tests {
                 exit_reason: None,
                 profit_abs: None,
                 profit_ratio: None,
+                is_short: false,
+                leverage: Decimal::ONE,
+                liquidation_price: None,
             };
 
             assert_eq!(trade.timeframe, tf);
@@ -146,6 +155,9 @@ mod trade_tests {
             exit_reason: None,
             profit_abs: None,
             profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         // Sell trade (closing a position)
@@ -167,6 +179,9 @@ mod trade_tests {
             exit_reason: Some(crate::types::ExitType::Signal),
             profit_abs: Some(Decimal::from_str("5.7").unwrap()),
             profit_ratio: Some(Decimal::from_str("0.095").unwrap()),
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         assert!(buy_trade.is_open);
@@ -306,6 +321,9 @@ mod trade_tests {
             exit_reason: None,
             profit_abs: None,
             profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         // Update SL to higher value (trailing stop)
@@ -380,6 +398,9 @@ mod trade_tests {
             exit_reason: None,
             profit_abs: None,
             profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         let trade_closed = Trade {
@@ -400,6 +421,9 @@ mod trade_tests {
             exit_reason: Some(crate::types::ExitType::Signal),
             profit_abs: Some(Decimal::from(1000)),
             profit_ratio: Some(Decimal::from_str("0.033").unwrap()),
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
         };
 
         let trades = [trade_open, trade_closed];
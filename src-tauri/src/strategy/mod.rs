@@ -1,4 +1,36 @@
 pub mod indicators;
+pub mod simple;
 pub mod traits;
 
+pub use simple::SimpleStrategy;
 pub use traits::Strategy;
+
+use crate::error::{AppError, Result};
+use std::sync::Arc;
+
+/// Strategies selectable through `strategy.name`
+pub const STRATEGIES: &[&str] = &["SimpleStrategy"];
+
+/// Instantiate the strategy configured as `strategy.name`
+pub fn load_strategy(name: &str) -> Result<Arc<dyn Strategy>> {
+    match name {
+        "SimpleStrategy" => Ok(Arc::new(SimpleStrategy::new())),
+        _ => Err(AppError::Strategy(format!(
+            "Unknown strategy '{}', available: {}",
+            name,
+            STRATEGIES.join(", ")
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_strategy_by_name() {
+        assert_eq!(load_strategy("SimpleStrategy").unwrap().name(), "SimpleStrategy");
+        let err = load_strategy("Missing").err().unwrap();
+        assert!(err.to_string().contains("SimpleStrategy"));
+    }
+}
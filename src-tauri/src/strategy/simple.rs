@@ -0,0 +1,45 @@
+use crate::error::Result;
+use crate::types::*;
+use async_trait::async_trait;
+
+/// Placeholder strategy that never signals; the default `strategy.name`
+pub struct SimpleStrategy;
+
+impl SimpleStrategy {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SimpleStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl super::Strategy for SimpleStrategy {
+    fn name(&self) -> &str {
+        "SimpleStrategy"
+    }
+
+    fn timeframes(&self) -> &[Timeframe] {
+        &[Timeframe::OneHour]
+    }
+
+    async fn populate_indicators(&mut self, _data: &mut Vec<OHLCV>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn populate_buy_trend(&self, _data: &[OHLCV]) -> Result<Vec<Signal>> {
+        Ok(vec![])
+    }
+
+    async fn populate_sell_trend(&self, _data: &[OHLCV]) -> Result<Vec<Signal>> {
+        Ok(vec![])
+    }
+
+    fn vectorized(&self) -> bool {
+        true
+    }
+}
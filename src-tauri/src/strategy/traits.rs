@@ -1,14 +1,33 @@
 use crate::error::Result;
 use crate::types::*;
 use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
 
 #[async_trait]
 pub trait Strategy: Send + Sync {
     fn name(&self) -> &str;
     fn timeframes(&self) -> &[Timeframe];
+    /// Candles needed before the indicators, and therefore the signals, are valid. Backtests
+    /// ignore signals on that many candles at the start of every pair and load them from before
+    /// the timerange; the live bot fetches that many candles of history.
+    fn startup_candle_count(&self) -> usize {
+        0
+    }
     async fn populate_indicators(&mut self, data: &mut Vec<OHLCV>) -> Result<()>;
     async fn populate_buy_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>>;
     async fn populate_sell_trend(&self, data: &[OHLCV]) -> Result<Vec<Signal>>;
+    /// Whether backtests may call `populate_*_trend` once over the full dataset instead of on
+    /// every candle's history. Opting in promises that the signal at `index` only depends on
+    /// `data[..=index]`, so the result is the same as evaluating candle by candle.
+    fn vectorized(&self) -> bool {
+        false
+    }
+    /// Indicator series by name, aligned with `data`. Only used by the lookahead analysis, which
+    /// checks that the value at `index` does not change when the candles after it are removed.
+    async fn indicator_values(&self, _data: &[OHLCV]) -> Result<BTreeMap<String, Vec<Option<Decimal>>>> {
+        Ok(BTreeMap::new())
+    }
     async fn confirm_trade_exit(&self, _trade: &Trade, _action: ExitType) -> Result<bool> {
         Ok(true)
     }
@@ -6,23 +6,39 @@ use uuid::Uuid;
 #[allow(dead_code)]
 pub type Pair = String;
 
+/// Serialized as e.g. "onehour"; the exchange notation ("1h") used in config files is accepted as well
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Timeframe {
+    #[serde(alias = "1m")]
     OneMinute,
+    #[serde(alias = "3m")]
     ThreeMinutes,
+    #[serde(alias = "5m")]
     FiveMinutes,
+    #[serde(alias = "15m")]
     FifteenMinutes,
+    #[serde(alias = "30m")]
     ThirtyMinutes,
+    #[serde(alias = "1h")]
     OneHour,
+    #[serde(alias = "2h")]
     TwoHours,
+    #[serde(alias = "4h")]
     FourHours,
+    #[serde(alias = "6h")]
     SixHours,
+    #[serde(alias = "8h")]
     EightHours,
+    #[serde(alias = "12h")]
     TwelveHours,
+    #[serde(alias = "1d")]
     OneDay,
+    #[serde(alias = "3d")]
     ThreeDays,
+    #[serde(alias = "1w")]
     OneWeek,
+    #[serde(alias = "1M")]
     OneMonth,
 }
 
@@ -48,6 +64,35 @@ impl Timeframe {
     }
 }
 
+impl std::str::FromStr for Timeframe {
+    type Err = crate::error::AppError;
+
+    /// Parse the exchange notation, e.g. "1h"
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        use Timeframe::*;
+        [
+            OneMinute,
+            ThreeMinutes,
+            FiveMinutes,
+            FifteenMinutes,
+            ThirtyMinutes,
+            OneHour,
+            TwoHours,
+            FourHours,
+            SixHours,
+            EightHours,
+            TwelveHours,
+            OneDay,
+            ThreeDays,
+            OneWeek,
+            OneMonth,
+        ]
+        .into_iter()
+        .find(|tf| tf.as_str() == s)
+        .ok_or_else(|| crate::error::AppError::InvalidInput(format!("Unknown timeframe: {}", s)))
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum TradeSide {
@@ -75,6 +120,39 @@ pub enum OrderStatus {
     Expired,
 }
 
+impl std::fmt::Display for TradeSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradeSide::Buy => write!(f, "buy"),
+            TradeSide::Sell => write!(f, "sell"),
+        }
+    }
+}
+
+impl std::fmt::Display for OrderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderType::Market => write!(f, "market"),
+            OrderType::Limit => write!(f, "limit"),
+            OrderType::StopLimit => write!(f, "stop_limit"),
+            OrderType::StopMarket => write!(f, "stop_market"),
+        }
+    }
+}
+
+impl std::fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderStatus::New => write!(f, "new"),
+            OrderStatus::PartiallyFilled => write!(f, "partially_filled"),
+            OrderStatus::Filled => write!(f, "filled"),
+            OrderStatus::Canceled => write!(f, "canceled"),
+            OrderStatus::Rejected => write!(f, "rejected"),
+            OrderStatus::Expired => write!(f, "expired"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ExitType {
     Signal,
@@ -161,6 +239,16 @@ pub struct Trade {
     pub exit_reason: Option<ExitType>,
     pub profit_abs: Option<Decimal>,
     pub profit_ratio: Option<Decimal>,
+    #[serde(default)]
+    pub is_short: bool,
+    #[serde(default = "default_leverage")]
+    pub leverage: Decimal,
+    #[serde(default)]
+    pub liquidation_price: Option<Decimal>,
+}
+
+fn default_leverage() -> Decimal {
+    Decimal::ONE
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -181,6 +269,25 @@ pub struct Position {
     pub mark_price: Decimal,
     pub unrealized_pnl: Decimal,
     pub percentage: Decimal,
+    pub leverage: Decimal,
+    pub liquidation_price: Option<Decimal>,
+    pub margin_mode: Option<MarginMode>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MarginMode {
+    #[default]
+    Isolated,
+    Cross,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub symbol: String,
+    pub funding_rate: Decimal,
+    pub mark_price: Decimal,
+    pub next_funding_time: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -215,6 +322,103 @@ pub struct BacktestResult {
     pub avg_profit: Decimal,
     pub avg_loss: Decimal,
     pub trades: Vec<Trade>,
+    /// Breakdown by pair, in the order the pairs were backtested
+    #[serde(default)]
+    pub per_pair: Vec<PairResult>,
+    #[serde(default)]
+    pub starting_balance: f64,
+    /// Mean buy-and-hold return of the backtested pairs over the same candles
+    #[serde(default)]
+    pub market_change: f64,
+}
+
+/// A saved backtest run as listed in the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestSummary {
+    pub id: i64,
+    pub strategy: String,
+    pub pair: String,
+    pub timeframe: Timeframe,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub total_trades: usize,
+    pub win_rate: f64,
+    pub total_profit: Decimal,
+    pub max_drawdown: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Detailed breakdown of a backtest, rendered as tables by the CLI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub strategy: String,
+    pub timeframe: Timeframe,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub starting_balance: f64,
+    pub final_balance: f64,
+    /// All closed trades together
+    pub total: ReportRow,
+    pub per_pair: Vec<ReportRow>,
+    /// Keyed by exit reason, e.g. "stop_loss"
+    pub exit_reasons: Vec<ReportRow>,
+    pub daily: Vec<PeriodRow>,
+    /// Keyed by the Monday starting the week
+    pub weekly: Vec<PeriodRow>,
+    /// Keyed by the first day of the month
+    pub monthly: Vec<PeriodRow>,
+    pub max_consecutive_wins: usize,
+    pub max_consecutive_losses: usize,
+    pub avg_winner_duration_secs: i64,
+    pub avg_loser_duration_secs: i64,
+    /// Share of the backtested period with at least one open trade
+    pub exposure: f64,
+    pub market_change: f64,
+    pub max_drawdown: f64,
+    pub sharpe_ratio: f64,
+    pub profit_factor: f64,
+}
+
+/// Statistics of a group of closed trades
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportRow {
+    pub key: String,
+    pub trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub win_rate: f64,
+    /// Mean profit ratio per trade
+    pub avg_profit_ratio: f64,
+    pub total_profit: Decimal,
+    /// Total profit relative to the starting balance
+    pub total_profit_ratio: f64,
+    pub avg_duration_secs: i64,
+}
+
+/// Profit of the trades closed within one day, week or month
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeriodRow {
+    pub period: chrono::NaiveDate,
+    pub trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub profit: Decimal,
+    /// Profit relative to the starting balance
+    pub profit_ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairResult {
+    pub pair: String,
+    pub total_trades: usize,
+    pub winning_trades: usize,
+    pub losing_trades: usize,
+    pub win_rate: f64,
+    pub total_profit: Decimal,
+    /// Mean profit ratio per trade
+    pub avg_profit_ratio: f64,
+    /// Total profit relative to the starting balance
+    pub total_profit_ratio: f64,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -245,6 +449,28 @@ pub struct DashboardStats {
     pub total_balance: f64,
 }
 
+/// One downloader run for a pair/timeframe, as recorded in `data_downloads`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataDownload {
+    pub pair: String,
+    pub timeframe: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// Candles fetched by this run
+    pub candles_count: usize,
+    pub download_time: DateTime<Utc>,
+}
+
+/// Candles stored in `klines` for a pair/timeframe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableData {
+    pub pair: String,
+    pub timeframe: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub candles_count: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EquityPoint {
     pub time: String,
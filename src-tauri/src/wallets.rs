@@ -0,0 +1,408 @@
+use crate::error::Result;
+use crate::exchange::Exchange;
+use crate::exchange::market::{Market, find_market};
+use crate::types::{Balance, Trade};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Per-currency balances of the trading account.
+///
+/// Balances are refreshed from the exchange with [`Wallets::update`] and can be patched
+/// in between from streamed account updates. Stakes handed out by
+/// [`Wallets::get_trade_stake_amount`] are moved from free to used immediately, so
+/// concurrent entries within one cycle cannot spend the same funds twice.
+pub struct Wallets {
+    exchange: Arc<dyn Exchange>,
+    stake_currency: String,
+    balances: RwLock<Option<HashMap<String, Balance>>>,
+}
+
+impl Wallets {
+    pub fn new(exchange: Arc<dyn Exchange>, stake_currency: &str) -> Self {
+        Self {
+            exchange,
+            stake_currency: stake_currency.to_string(),
+            balances: RwLock::new(None),
+        }
+    }
+
+    pub fn stake_currency(&self) -> &str {
+        &self.stake_currency
+    }
+
+    /// Reload all balances from the exchange
+    pub async fn update(&self) -> Result<()> {
+        let balances = self.exchange.fetch_balances().await?;
+        *self.balances.write().await = Some(balances.into_iter().map(|b| (b.currency.clone(), b)).collect());
+        Ok(())
+    }
+
+    /// Replace the balances of the given currencies, e.g. from a user-data stream
+    pub async fn apply_balances(&self, updates: Vec<Balance>) {
+        let mut balances = self.balances.write().await;
+        let balances = balances.get_or_insert_with(HashMap::new);
+        for balance in updates {
+            balances.insert(balance.currency.clone(), balance);
+        }
+    }
+
+    async fn ensure_loaded(&self) -> Result<()> {
+        if self.balances.read().await.is_none() {
+            self.update().await?;
+        }
+        Ok(())
+    }
+
+    /// Balance of a single currency; zero if the account does not hold it
+    pub async fn get(&self, currency: &str) -> Result<Balance> {
+        self.ensure_loaded().await?;
+        Ok(self
+            .balances
+            .read()
+            .await
+            .as_ref()
+            .and_then(|b| b.get(currency).cloned())
+            .unwrap_or_else(|| Balance {
+                currency: currency.to_string(),
+                total: Decimal::ZERO,
+                free: Decimal::ZERO,
+                used: Decimal::ZERO,
+            }))
+    }
+
+    pub async fn get_free(&self, currency: &str) -> Result<Decimal> {
+        Ok(self.get(currency).await?.free)
+    }
+
+    /// All non-empty balances
+    pub async fn all(&self) -> Result<Vec<Balance>> {
+        self.ensure_loaded().await?;
+        let balances = self.balances.read().await;
+        let mut all: Vec<Balance> = balances
+            .iter()
+            .flat_map(|b| b.values())
+            .filter(|b| b.total > Decimal::ZERO)
+            .cloned()
+            .collect();
+        all.sort_by(|a, b| a.currency.cmp(&b.currency));
+        Ok(all)
+    }
+
+    /// Stake currency tied up in open trades
+    pub fn reserved_by_trades(open_trades: &[Trade]) -> Decimal {
+        open_trades.iter().filter(|t| t.is_open).map(|t| t.stake_amount).sum()
+    }
+
+    /// Free stake currency plus the stake reserved by open trades
+    pub async fn total_stake_amount(&self, open_trades: &[Trade]) -> Result<Decimal> {
+        Ok(self.get_free(&self.stake_currency).await? + Self::reserved_by_trades(open_trades))
+    }
+
+    /// Value of all holdings expressed in the stake currency.
+    /// Currencies without a stake-currency market or ticker are skipped.
+    pub async fn total_value(&self) -> Result<Decimal> {
+        let balances = self.all().await?;
+        let markets = self.exchange.load_markets().await.unwrap_or_default();
+
+        let mut total = Decimal::ZERO;
+        for balance in balances {
+            if balance.currency == self.stake_currency {
+                total += balance.total;
+                continue;
+            }
+
+            let symbol = stake_market(&markets, &balance.currency, &self.stake_currency)
+                .map(|m| m.id.clone())
+                .unwrap_or_else(|| format!("{}/{}", balance.currency, self.stake_currency));
+            match self.exchange.fetch_ticker(&symbol).await {
+                Ok(ticker) => total += balance.total * ticker.price,
+                Err(e) => eprintln!("Cannot value {} in {}: {}", balance.currency, self.stake_currency, e),
+            }
+        }
+
+        Ok(total)
+    }
+
+    /// Stake for a new trade on `pair`: the configured `stake_amount`, or an equal share of the
+    /// free balance across the remaining trade slots when it is zero ("unlimited").
+    /// Capped at the free stake balance; the returned amount is marked as used. Zero when the
+    /// position (stake times `leverage`) would fall below the market's minimum notional.
+    /// Fails when the markets cannot be loaded, since the minimum could not be checked.
+    pub async fn get_trade_stake_amount(
+        &self,
+        pair: &str,
+        stake_amount: Decimal,
+        max_open_trades: usize,
+        open_trades: &[Trade],
+        leverage: Decimal,
+    ) -> Result<Decimal> {
+        self.ensure_loaded().await?;
+        let markets = self.exchange.load_markets().await?;
+        let min_notional = find_market(&markets, pair).and_then(|m| m.min_notional);
+
+        let mut balances = self.balances.write().await;
+        let balances = balances.get_or_insert_with(HashMap::new);
+        let Some(balance) = balances.get_mut(&self.stake_currency) else {
+            return Ok(Decimal::ZERO);
+        };
+
+        let requested = if stake_amount > Decimal::ZERO {
+            stake_amount
+        } else {
+            let open = open_trades.iter().filter(|t| t.is_open).count();
+            match max_open_trades.saturating_sub(open) {
+                0 => Decimal::ZERO,
+                slots => balance.free / Decimal::from(slots),
+            }
+        };
+
+        let stake = requested.min(balance.free).max(Decimal::ZERO);
+        if min_notional.is_some_and(|min| stake * leverage < min) {
+            return Ok(Decimal::ZERO);
+        }
+        balance.free -= stake;
+        balance.used += stake;
+        Ok(stake)
+    }
+}
+
+fn stake_market<'a>(markets: &'a HashMap<String, Market>, currency: &str, stake_currency: &str) -> Option<&'a Market> {
+    markets
+        .values()
+        .find(|m| m.base == currency && m.quote == stake_currency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use crate::types::*;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    struct Account {
+        balances: Vec<Balance>,
+        min_notional: Option<Decimal>,
+        markets_unavailable: bool,
+    }
+
+    #[async_trait]
+    impl Exchange for Account {
+        async fn fetch_ticker(&self, symbol: &str) -> Result<Ticker> {
+            if symbol != "BTCUSDT" {
+                return Err(AppError::Exchange(format!("Unknown symbol {}", symbol)));
+            }
+            Ok(Ticker {
+                symbol: symbol.to_string(),
+                price: Decimal::from(20000),
+                volume_24h: Decimal::ZERO,
+                change_24h: Decimal::ZERO,
+            })
+        }
+
+        async fn fetch_ohlcv(&self, _symbol: &str, _timeframe: &str, _limit: usize) -> Result<Vec<OHLCV>> {
+            Ok(vec![])
+        }
+
+        async fn fetch_balance(&self) -> Result<Balance> {
+            Err(AppError::NotImplemented("fetch_balance".to_string()))
+        }
+
+        async fn fetch_balances(&self) -> Result<Vec<Balance>> {
+            Ok(self.balances.clone())
+        }
+
+        async fn fetch_positions(&self) -> Result<Vec<Position>> {
+            Ok(vec![])
+        }
+
+        async fn create_order(&self, _order: OrderRequest) -> Result<Order> {
+            Err(AppError::NotImplemented("create_order".to_string()))
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn fetch_order(&self, _order_id: &str) -> Result<Order> {
+            Err(AppError::NotImplemented("fetch_order".to_string()))
+        }
+
+        async fn fetch_orders(&self, _symbol: &str) -> Result<Vec<Order>> {
+            Ok(vec![])
+        }
+
+        fn get_name(&self) -> &str {
+            "account"
+        }
+
+        async fn fetch_markets(&self) -> Result<Vec<Market>> {
+            if self.markets_unavailable {
+                return Err(AppError::Exchange("exchangeInfo unavailable".to_string()));
+            }
+            Ok(vec![Market {
+                symbol: "BTC/USDT".to_string(),
+                id: "BTCUSDT".to_string(),
+                base: "BTC".to_string(),
+                quote: "USDT".to_string(),
+                active: true,
+                amount_step: None,
+                price_tick: None,
+                min_amount: None,
+                max_amount: None,
+                min_notional: self.min_notional,
+            }])
+        }
+    }
+
+    fn balance(currency: &str, free: i64, used: i64) -> Balance {
+        Balance {
+            currency: currency.to_string(),
+            total: Decimal::from(free + used),
+            free: Decimal::from(free),
+            used: Decimal::from(used),
+        }
+    }
+
+    fn wallets(balances: Vec<Balance>) -> Wallets {
+        Wallets::new(
+            Arc::new(Account {
+                balances,
+                min_notional: None,
+                markets_unavailable: false,
+            }),
+            "USDT",
+        )
+    }
+
+    fn open_trade(stake_amount: i64) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            pair: "BTC/USDT".to_string(),
+            is_open: true,
+            exchange: "account".to_string(),
+            open_rate: Decimal::from(20000),
+            open_date: Utc::now(),
+            close_rate: None,
+            close_date: None,
+            amount: Decimal::ONE,
+            stake_amount: Decimal::from(stake_amount),
+            strategy: "test".to_string(),
+            timeframe: Timeframe::OneHour,
+            stop_loss: None,
+            take_profit: None,
+            exit_reason: None,
+            profit_abs: None,
+            profit_ratio: None,
+            is_short: false,
+            leverage: Decimal::ONE,
+            liquidation_price: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_total_value_in_stake_currency() {
+        let wallets = wallets(vec![
+            balance("USDT", 900, 100),
+            balance("BTC", 1, 1),
+            balance("DOGE", 50, 0),
+        ]);
+
+        // 1000 USDT + 2 BTC * 20000; DOGE has no USDT market here and is skipped
+        assert_eq!(wallets.total_value().await.unwrap(), Decimal::from(41000));
+        assert_eq!(wallets.get("BTC").await.unwrap().used, Decimal::ONE);
+        assert_eq!(wallets.get("ETH").await.unwrap().total, Decimal::ZERO);
+        assert_eq!(wallets.all().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stake_amount_capped_by_free_balance() {
+        let wallets = wallets(vec![balance("USDT", 150, 0)]);
+
+        let first = wallets
+            .get_trade_stake_amount("BTC/USDT", Decimal::from(100), 3, &[], Decimal::ONE)
+            .await
+            .unwrap();
+        let second = wallets
+            .get_trade_stake_amount("BTC/USDT", Decimal::from(100), 3, &[], Decimal::ONE)
+            .await
+            .unwrap();
+        let third = wallets
+            .get_trade_stake_amount("BTC/USDT", Decimal::from(100), 3, &[], Decimal::ONE)
+            .await
+            .unwrap();
+
+        assert_eq!(first, Decimal::from(100));
+        assert_eq!(second, Decimal::from(50));
+        assert_eq!(third, Decimal::ZERO);
+        assert_eq!(wallets.get_free("USDT").await.unwrap(), Decimal::ZERO);
+
+        wallets.update().await.unwrap();
+        assert_eq!(wallets.get_free("USDT").await.unwrap(), Decimal::from(150));
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_stake_splits_free_balance_across_slots() {
+        let wallets = wallets(vec![balance("USDT", 300, 0)]);
+        let trades = vec![open_trade(200)];
+
+        let stake = wallets
+            .get_trade_stake_amount("BTC/USDT", Decimal::ZERO, 3, &trades, Decimal::ONE)
+            .await
+            .unwrap();
+        assert_eq!(stake, Decimal::from(150));
+        assert_eq!(Wallets::reserved_by_trades(&trades), Decimal::from(200));
+        assert_eq!(wallets.total_stake_amount(&trades).await.unwrap(), Decimal::from(350));
+    }
+
+    #[tokio::test]
+    async fn test_stake_below_min_notional_is_skipped() {
+        let wallets = Wallets::new(
+            Arc::new(Account {
+                balances: vec![balance("USDT", 8, 0)],
+                min_notional: Some(Decimal::from(10)),
+                markets_unavailable: false,
+            }),
+            "USDT",
+        );
+
+        // Capped at the free 8, below the minimum of 10; nothing is reserved
+        let stake = wallets
+            .get_trade_stake_amount("BTC/USDT", Decimal::from(100), 3, &[], Decimal::ONE)
+            .await
+            .unwrap();
+        assert_eq!(stake, Decimal::ZERO);
+        assert_eq!(wallets.get_free("USDT").await.unwrap(), Decimal::from(8));
+
+        // With 2x leverage the position is worth 16
+        let stake = wallets
+            .get_trade_stake_amount("BTC/USDT", Decimal::from(100), 3, &[], Decimal::from(2))
+            .await
+            .unwrap();
+        assert_eq!(stake, Decimal::from(8));
+    }
+
+    #[tokio::test]
+    async fn test_stake_fails_without_markets() {
+        let wallets = Wallets::new(
+            Arc::new(Account {
+                balances: vec![balance("USDT", 1000, 0)],
+                min_notional: None,
+                markets_unavailable: true,
+            }),
+            "USDT",
+        );
+
+        // The minimum notional cannot be checked; nothing is reserved
+        assert!(matches!(
+            wallets
+                .get_trade_stake_amount("BTC/USDT", Decimal::from(100), 3, &[], Decimal::ONE)
+                .await,
+            Err(AppError::Exchange(_))
+        ));
+        assert_eq!(wallets.get_free("USDT").await.unwrap(), Decimal::from(1000));
+    }
+}
@@ -12,6 +12,7 @@ import HyperoptView from './pages/dashboard/HyperoptView';
 import SettingsView from './pages/dashboard/SettingsView';
 import LogsView from './pages/dashboard/LogsView';
 import { useAppStore } from './stores/appStore';
+import { onBotEvent } from './services/api';
 import { Trade } from './types';
 
 const MainLayout = () => {
@@ -42,6 +43,19 @@ const MainLayout = () => {
     }, 5000);
     return () => clearInterval(interval);
   }, []);
+
+  // 状态变化由机器人事件实时推送，无需等待下一次轮询
+  useEffect(() => {
+    const unlisten = onBotEvent((event) => {
+      if (event.type === 'status') {
+        setBotStatus(event.data.status);
+        actions.setBotState(event.data.status);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
   
   const handleAnalyzeTrade = async (trade: Trade) => {
     alert('AI 分析功能开发中\n\n交易对: ' + trade.pair + '\n当前价格: ' + (trade.current || trade.close_rate || 'N/A'));
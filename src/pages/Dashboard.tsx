@@ -1,5 +1,5 @@
 import { useEffect, useState } from 'react';
-import { api } from '../services/api';
+import { api, onBotEvent } from '../services/api';
 import { useAppStore } from '../stores/appStore';
 import { formatCurrency, formatPercentage, formatDate } from '../lib/utils';
 import { Button } from '../ui/button';
@@ -25,7 +25,15 @@ export function Dashboard() {
   useEffect(() => {
     loadStatus();
     const interval = setInterval(loadStatus, 5000);
-    return () => clearInterval(interval);
+    const unlisten = onBotEvent((event) => {
+      if (event.type === 'status' || event.type === 'entry_fill' || event.type === 'exit_fill') {
+        loadStatus();
+      }
+    });
+    return () => {
+      clearInterval(interval);
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   const handleStart = async () => {
@@ -1,40 +1,77 @@
-import { useState } from 'react';
+import { useEffect, useMemo, useState } from 'react';
 import { LineChart, Line, CartesianGrid, XAxis, YAxis, Tooltip as RechartsTooltip, ResponsiveContainer, Legend } from 'recharts';
-import { Clock, Play, Loader, Save, Trash2, Calendar, ChevronDown, Activity } from 'lucide-react';
+import { Clock, Play, Loader, History, Trash2, Calendar, ChevronDown, Activity } from 'lucide-react';
+import { getBacktestResult, getBacktestResults, runBacktest as runBacktestApi } from '../../services/api';
+import type { BacktestResult, BacktestSummary, Timeframe } from '../../types';
 
-const strategies = ['NostalgiaForInfinity', 'BinanceCluc', 'DoesNothingStrategy', 'SMAOffset'];
-const backtestResultsData = [
-    { date: 'Jan', strategy: 10, buyHold: 5 },
-    { date: 'Feb', strategy: 15, buyHold: 8 },
-    { date: 'Mar', strategy: 12, buyHold: 4 },
-    { date: 'Apr', strategy: 25, buyHold: 15 },
-    { date: 'May', strategy: 32, buyHold: 12 },
-    { date: 'Jun', strategy: 45, buyHold: 20 },
-  ];
+// Keep in sync with `strategy::STRATEGIES` on the Rust side
+const strategies = ['SimpleStrategy'];
+const timeframes: Timeframe[] = ['1m', '5m', '15m', '1h', '4h', '1d'];
 
+const formatPercent = (ratio: number) => `${(ratio * 100).toFixed(1)}%`;
 
 const BacktestView = () => {
     const [isRunning, setIsRunning] = useState(false);
-    const [progress, setProgress] = useState(0);
-    const [hasResult, setHasResult] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+    const [result, setResult] = useState<BacktestResult | null>(null);
+    const [history, setHistory] = useState<BacktestSummary[]>([]);
+    const [strategy, setStrategy] = useState(strategies[0]);
+    const [pairs, setPairs] = useState('');
+    const [timerange, setTimerange] = useState('');
+    const [timeframe, setTimeframe] = useState<Timeframe>('5m');
+    const [startingBalance, setStartingBalance] = useState(1000);
+
+    const refreshHistory = () => {
+      getBacktestResults().then(setHistory).catch((e) => setError(String(e)));
+    };
+
+    useEffect(() => {
+      refreshHistory();
+    }, []);
 
-    const runBacktest = () => {
+    const runBacktest = async () => {
       setIsRunning(true);
-      setHasResult(false);
-      setProgress(0);
-      // Simulate backtest process
-      let p = 0;
-      const interval = setInterval(() => {
-        p += 5;
-        setProgress(p);
-        if (p >= 100) {
-          clearInterval(interval);
-          setIsRunning(false);
-          setHasResult(true);
-        }
-      }, 100);
+      setError(null);
+      setResult(null);
+      try {
+        const pairList = pairs.split(',').map(p => p.trim()).filter(Boolean);
+        setResult(await runBacktestApi({
+          strategy,
+          pairs: pairList.length > 0 ? pairList : undefined,
+          timeframe,
+          timerange: timerange || undefined,
+          starting_balance: startingBalance,
+        }));
+        refreshHistory();
+      } catch (e) {
+        setError(String(e));
+      } finally {
+        setIsRunning(false);
+      }
     };
 
+    const loadRun = async (id: number) => {
+      setError(null);
+      try {
+        setResult(await getBacktestResult(id));
+      } catch (e) {
+        setError(String(e));
+      }
+    };
+
+    // Cumulative profit after each closed trade
+    const profitCurve = useMemo(() => {
+      if (!result) return [];
+      let cumulative = 0;
+      return result.trades
+        .filter(t => t.close_date)
+        .sort((a, b) => (a.close_date! < b.close_date! ? -1 : 1))
+        .map(t => {
+          cumulative += Number(t.profit_abs ?? 0);
+          return { date: t.close_date!.slice(0, 10), profit: Number(cumulative.toFixed(2)) };
+        });
+    }, [result]);
+
     return (
       <div className="h-[calc(100vh-140px)] flex flex-col lg:flex-row gap-6 animate-in fade-in duration-500">
 
@@ -48,26 +85,31 @@ const BacktestView = () => {
             <div>
               <label className="text-xs font-semibold text-slate-400 uppercase mb-2 block">Strategy</label>
               <div className="relative">
-                <select className="w-full appearance-none bg-slate-900 border border-slate-700 text-white rounded-lg px-4 py-2.5 text-sm focus:border-indigo-500 outline-none transition-colors">
+                <select value={strategy} onChange={e => setStrategy(e.target.value)} className="w-full appearance-none bg-slate-900 border border-slate-700 text-white rounded-lg px-4 py-2.5 text-sm focus:border-indigo-500 outline-none transition-colors">
                   {strategies.map(s => <option key={s}>{s}</option>)}
                 </select>
                 <ChevronDown size={14} className="absolute right-3 top-3.5 text-slate-500 pointer-events-none" />
               </div>
             </div>
 
+            <div>
+              <label className="text-xs font-semibold text-slate-400 uppercase mb-2 block">Pairs</label>
+              <input type="text" value={pairs} onChange={e => setPairs(e.target.value)} placeholder="BTC/USDT, ETH/USDT" className="w-full bg-slate-900 border border-slate-700 text-white rounded-lg px-4 py-2 text-sm focus:border-indigo-500 outline-none placeholder-slate-600" />
+            </div>
+
             <div>
               <label className="text-xs font-semibold text-slate-400 uppercase mb-2 block">Timerange</label>
               <div className="flex items-center space-x-2 bg-slate-900 border border-slate-700 rounded-lg px-3 py-2">
                 <Calendar size={14} className="text-slate-500" />
-                <input type="text" placeholder="20230101-20231231" className="bg-transparent text-sm text-white w-full outline-none placeholder-slate-600" />
+                <input type="text" value={timerange} onChange={e => setTimerange(e.target.value)} placeholder="20230101-20231231" className="bg-transparent text-sm text-white w-full outline-none placeholder-slate-600" />
               </div>
             </div>
 
             <div>
               <label className="text-xs font-semibold text-slate-400 uppercase mb-2 block">Timeframe</label>
               <div className="grid grid-cols-4 gap-2">
-                {['1m', '5m', '15m', '1h', '4h', '1d'].map(tf => (
-                  <button key={tf} className={`py-1.5 text-xs rounded border ${tf === '5m' ? 'bg-indigo-600 border-indigo-600 text-white' : 'bg-slate-900 border-slate-700 text-slate-400 hover:border-slate-500'}`}>
+                {timeframes.map(tf => (
+                  <button key={tf} onClick={() => setTimeframe(tf)} className={`py-1.5 text-xs rounded border ${tf === timeframe ? 'bg-indigo-600 border-indigo-600 text-white' : 'bg-slate-900 border-slate-700 text-slate-400 hover:border-slate-500'}`}>
                     {tf}
                   </button>
                 ))}
@@ -78,11 +120,30 @@ const BacktestView = () => {
               <label className="text-xs font-semibold text-slate-400 uppercase mb-2 block">Starting Balance</label>
               <div className="relative">
                 <span className="absolute left-3 top-2.5 text-slate-500 text-sm">$</span>
-                <input type="number" defaultValue={1000} className="w-full bg-slate-900 border border-slate-700 text-white rounded-lg pl-7 pr-4 py-2 text-sm focus:border-indigo-500 outline-none" />
+                <input type="number" value={startingBalance} onChange={e => setStartingBalance(Number(e.target.value))} className="w-full bg-slate-900 border border-slate-700 text-white rounded-lg pl-7 pr-4 py-2 text-sm focus:border-indigo-500 outline-none" />
               </div>
             </div>
           </div>
 
+          {history.length > 0 && (
+            <div className="mt-6">
+              <label className="text-xs font-semibold text-slate-400 uppercase mb-2 flex items-center">
+                <History size={12} className="mr-1" /> Past Runs
+              </label>
+              <div className="space-y-1 max-h-48 overflow-y-auto custom-scrollbar">
+                {history.map(run => (
+                  <button key={run.id} onClick={() => loadRun(run.id)} className="w-full text-left px-3 py-2 rounded bg-slate-900/60 hover:bg-slate-700/60 border border-slate-700/50 text-xs transition-colors">
+                    <div className="flex justify-between text-slate-300">
+                      <span>#{run.id} {run.strategy}</span>
+                      <span className={run.total_profit >= 0 ? 'text-emerald-400' : 'text-rose-400'}>{Number(run.total_profit).toFixed(2)}</span>
+                    </div>
+                    <div className="text-slate-500 truncate">{run.pair} • {run.created_at.slice(0, 16).replace('T', ' ')}</div>
+                  </button>
+                ))}
+              </div>
+            </div>
+          )}
+
           <button
             onClick={runBacktest}
             disabled={isRunning}
@@ -98,79 +159,67 @@ const BacktestView = () => {
 
         {/* Results Area */}
         <div className="flex-1 bg-slate-800/50 border border-slate-700/50 rounded-xl p-6 flex flex-col min-h-[500px] relative overflow-hidden">
-          {!hasResult && !isRunning && (
+          {!result && !isRunning && (
             <div className="absolute inset-0 flex flex-col items-center justify-center text-slate-500 opacity-40">
               <Activity size={64} className="mb-4" />
               <p className="text-lg">Ready to backtest</p>
             </div>
           )}
 
+          {error && (
+            <div className="relative z-20 mb-4 p-3 rounded-lg bg-rose-500/10 border border-rose-500/30 text-rose-300 text-sm">{error}</div>
+          )}
+
           {isRunning && (
             <div className="absolute inset-0 flex flex-col items-center justify-center bg-slate-900/50 backdrop-blur-sm z-10">
-              <div className="w-64 bg-slate-700 rounded-full h-2 mb-4 overflow-hidden">
-                <div className="bg-indigo-500 h-full transition-all duration-100 ease-out" style={{ width: `${progress}%` }}></div>
-              </div>
-              <p className="text-indigo-300 font-mono text-sm">Processing candles... {progress}%</p>
-              <div className="mt-4 p-4 bg-black/40 rounded text-xs font-mono text-slate-400 w-96 h-32 overflow-hidden border border-slate-700/50">
-                <div className="text-emerald-400">{'>'} Loading data...</div>
-                <div className="text-slate-300">{'>'} Strategy init: {strategies[0]}</div>
-                <div className="text-slate-500">{'>'} Warmup period: 100 candles</div>
-                {progress > 30 && <div className="text-indigo-300">{'>'} Processing 2023/04...</div>}
-                {progress > 60 && <div className="text-indigo-300">{'>'} Processing 2023/08...</div>}
-                {progress > 90 && <div className="text-yellow-300">{'>'} Calculating metrics...</div>}
-              </div>
+              <Loader size={32} className="animate-spin text-indigo-400 mb-4" />
+              <p className="text-indigo-300 font-mono text-sm">Processing candles...</p>
             </div>
           )}
 
-          {hasResult && (
+          {result && (
             <div className="flex flex-col h-full animate-in fade-in slide-in-from-bottom-4 duration-500">
                {/* Result Header */}
                <div className="flex justify-between items-center mb-6">
                   <div>
                     <h3 className="text-xl font-bold text-white">Backtest Results</h3>
-                    <p className="text-sm text-slate-400">{strategies[0]} • 5m • 2023</p>
-                  </div>
-                  <div className="flex space-x-2">
-                    <button className="flex items-center space-x-1 px-3 py-1.5 bg-slate-700 hover:bg-slate-600 text-white text-xs rounded transition-colors">
-                      <Save size={14} /> <span>Save Report</span>
-                    </button>
-                    <button onClick={() => setHasResult(false)} className="p-1.5 hover:bg-rose-500/20 hover:text-rose-400 text-slate-400 rounded transition-colors">
-                      <Trash2 size={16} />
-                    </button>
+                    <p className="text-sm text-slate-400">{result.strategy} • {result.pair} • {result.start_date.slice(0, 10)} – {result.end_date.slice(0, 10)}</p>
                   </div>
+                  <button onClick={() => setResult(null)} className="p-1.5 hover:bg-rose-500/20 hover:text-rose-400 text-slate-400 rounded transition-colors">
+                    <Trash2 size={16} />
+                  </button>
                </div>
 
                {/* Metrics Grid */}
                <div className="grid grid-cols-2 md:grid-cols-4 gap-4 mb-6">
                  <div className="bg-slate-700/30 p-4 rounded-lg border border-emerald-500/20">
                    <p className="text-slate-400 text-xs uppercase font-semibold">Total Profit</p>
-                   <p className="text-2xl font-bold text-emerald-400 font-mono">+45.2%</p>
+                   <p className={`text-2xl font-bold font-mono ${result.total_profit >= 0 ? 'text-emerald-400' : 'text-rose-400'}`}>{Number(result.total_profit).toFixed(2)}</p>
                  </div>
                  <div className="bg-slate-700/30 p-4 rounded-lg border border-slate-600/30">
                    <p className="text-slate-400 text-xs uppercase font-semibold">Total Trades</p>
-                   <p className="text-2xl font-bold text-white font-mono">342</p>
+                   <p className="text-2xl font-bold text-white font-mono">{result.total_trades}</p>
                  </div>
                  <div className="bg-slate-700/30 p-4 rounded-lg border border-slate-600/30">
                    <p className="text-slate-400 text-xs uppercase font-semibold">Win Rate</p>
-                   <p className="text-2xl font-bold text-indigo-400 font-mono">62.5%</p>
+                   <p className="text-2xl font-bold text-indigo-400 font-mono">{formatPercent(result.win_rate)}</p>
                  </div>
                  <div className="bg-slate-700/30 p-4 rounded-lg border border-rose-500/20">
                    <p className="text-slate-400 text-xs uppercase font-semibold">Max Drawdown</p>
-                   <p className="text-2xl font-bold text-rose-400 font-mono">-12.8%</p>
+                   <p className="text-2xl font-bold text-rose-400 font-mono">-{formatPercent(result.max_drawdown)}</p>
                  </div>
                </div>
 
-               {/* Comparison Chart */}
+               {/* Profit Chart */}
                <div className="flex-1 min-h-0 bg-slate-900/50 rounded-lg p-4 border border-slate-700/30">
                  <ResponsiveContainer width="100%" height="100%">
-                   <LineChart data={backtestResultsData}>
+                   <LineChart data={profitCurve}>
                       <CartesianGrid strokeDasharray="3 3" stroke="#334155" vertical={false} />
                       <XAxis dataKey="date" stroke="#64748b" fontSize={12} tickLine={false} axisLine={false} />
-                      <YAxis stroke="#64748b" fontSize={12} tickLine={false} axisLine={false} unit="%" />
+                      <YAxis stroke="#64748b" fontSize={12} tickLine={false} axisLine={false} />
                       <RechartsTooltip contentStyle={{ backgroundColor: '#1e293b', borderColor: '#334155', color: '#f8fafc' }} />
                       <Legend />
-                      <Line type="monotone" name="Strategy" dataKey="strategy" stroke="#6366f1" strokeWidth={3} dot={{r: 4}} activeDot={{r: 6}} />
-                      <Line type="monotone" name="Buy & Hold" dataKey="buyHold" stroke="#64748b" strokeWidth={2} strokeDasharray="5 5" dot={false} />
+                      <Line type="monotone" name="Cumulative Profit" dataKey="profit" stroke="#6366f1" strokeWidth={3} dot={false} activeDot={{r: 6}} />
                    </LineChart>
                  </ResponsiveContainer>
                </div>
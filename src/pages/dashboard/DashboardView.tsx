@@ -4,6 +4,7 @@ import TradeRow from '../../components/TradeRow';
 import { DollarSign, Activity, Box, TrendingDown, RefreshCw } from 'lucide-react';
 import { AreaChart, Area, ResponsiveContainer, CartesianGrid, XAxis, YAxis, Tooltip as RechartsTooltip } from 'recharts';
 import { useAppStore } from '../../stores/appStore';
+import { onBotEvent } from '../../services/api';
 import type { Trade } from '../../types';
 
 interface DashboardViewProps {
@@ -37,6 +38,20 @@ const DashboardView = ({ onAnalyze }: DashboardViewProps) => {
       return () => clearInterval(interval);
     }
   }, [botState, timeRange]);
+
+  // Refresh immediately when a trade is opened or closed
+  useEffect(() => {
+    const unlisten = onBotEvent((event) => {
+      if (event.type === 'entry_fill' || event.type === 'exit_fill') {
+        actions.fetchDashboardStats();
+        actions.fetchEquityCurve(timeRange);
+        actions.fetchOpenTrades();
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [timeRange]);
   
   // Mock logs (can be replaced with real log API later)
   const logs = [
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type { 
   Trade, 
   DashboardStats, 
@@ -6,7 +7,14 @@ import type {
   AppConfig, 
   BotStatus,
   BacktestConfig,
-  BacktestResult
+  BacktestReport,
+  BacktestResult,
+  BacktestSummary,
+  BotEvent,
+  DataDownload,
+  ExportFormat,
+  MonteCarloConfig,
+  MonteCarloResult
 } from '../types';
 
 // Dashboard APIs
@@ -39,6 +47,11 @@ export const stopBot = async (): Promise<string> => {
   return invoke('stop_bot');
 };
 
+// Live bot events (status changes, signals, fills, protections, errors)
+export const onBotEvent = (callback: (event: BotEvent) => void): Promise<UnlistenFn> => {
+  return listen<BotEvent>('bot-event', (event) => callback(event.payload));
+};
+
 // Trade APIs
 export const getOpenTrades = async (): Promise<Trade[]> => {
   return invoke('get_open_trades');
@@ -59,7 +72,44 @@ export const updateConfig = async (config: Partial<AppConfig>): Promise<void> =>
 
 // Backtest APIs
 export const runBacktest = async (config: BacktestConfig): Promise<BacktestResult> => {
-  return invoke('run_backtest', { config });
+  return invoke('run_backtest', { request: config });
+};
+
+export const getBacktestResults = async (): Promise<BacktestSummary[]> => {
+  return invoke('get_backtest_results');
+};
+
+export const getBacktestResult = async (id: number): Promise<BacktestResult> => {
+  return invoke('get_backtest_result', { id });
+};
+
+export const getBacktestReport = async (id: number): Promise<BacktestReport> => {
+  return invoke('get_backtest_report', { id });
+};
+
+export const runMonteCarlo = async (id: number, config: MonteCarloConfig = {}): Promise<MonteCarloResult> => {
+  return invoke('run_monte_carlo', { id, config });
+};
+
+export const exportBacktestResult = async (id: number, formats: ExportFormat[]): Promise<string[]> => {
+  return invoke('export_backtest_result', { id, formats });
+};
+
+export const importBacktestResults = async (path: string): Promise<number[]> => {
+  return invoke('import_backtest_results', { path });
+};
+
+// Data APIs
+export const downloadData = async (
+  pairs: string[],
+  timeframes: string[],
+  timerange: string
+): Promise<DataDownload[]> => {
+  return invoke('download_data', { pairs, timeframes, timerange });
+};
+
+export const getDataDownloads = async (): Promise<DataDownload[]> => {
+  return invoke('get_data_downloads');
 };
 
 // Export as an object for backward compatibility with some components
@@ -69,9 +119,18 @@ export const api = {
   getBotStatus,
   startBot,
   stopBot,
+  onBotEvent,
   getOpenTrades,
   getAllTrades,
   getConfig,
   updateConfig,
-  runBacktest
+  runBacktest,
+  getBacktestResults,
+  getBacktestResult,
+  getBacktestReport,
+  runMonteCarlo,
+  exportBacktestResult,
+  importBacktestResults,
+  downloadData,
+  getDataDownloads
 };
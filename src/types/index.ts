@@ -37,6 +37,9 @@ export interface Trade {
   exit_reason?: ExitType;
   profit_abs?: number;
   profit_ratio?: number;
+  is_short?: boolean;
+  leverage?: number;
+  liquidation_price?: number;
   // UI compatible fields
   entry?: number;
   current?: number;
@@ -73,6 +76,65 @@ export interface BacktestResult {
   avg_profit: number;
   avg_loss: number;
   trades: Trade[];
+  per_pair: PairResult[];
+  starting_balance: number;
+  market_change: number;
+}
+
+export interface BacktestReport {
+  strategy: string;
+  timeframe: Timeframe;
+  start_date: string;
+  end_date: string;
+  starting_balance: number;
+  final_balance: number;
+  total: ReportRow;
+  per_pair: ReportRow[];
+  exit_reasons: ReportRow[];
+  daily: PeriodRow[];
+  weekly: PeriodRow[];
+  monthly: PeriodRow[];
+  max_consecutive_wins: number;
+  max_consecutive_losses: number;
+  avg_winner_duration_secs: number;
+  avg_loser_duration_secs: number;
+  exposure: number;
+  market_change: number;
+  max_drawdown: number;
+  sharpe_ratio: number;
+  profit_factor: number;
+}
+
+export interface ReportRow {
+  key: string;
+  trades: number;
+  wins: number;
+  losses: number;
+  win_rate: number;
+  avg_profit_ratio: number;
+  total_profit: number;
+  total_profit_ratio: number;
+  avg_duration_secs: number;
+}
+
+export interface PeriodRow {
+  period: string;
+  trades: number;
+  wins: number;
+  losses: number;
+  profit: number;
+  profit_ratio: number;
+}
+
+export interface PairResult {
+  pair: string;
+  total_trades: number;
+  winning_trades: number;
+  losing_trades: number;
+  win_rate: number;
+  total_profit: number;
+  avg_profit_ratio: number;
+  total_profit_ratio: number;
 }
 
 export interface DashboardStats {
@@ -88,6 +150,24 @@ export interface EquityPoint {
   value: number;
 }
 
+// Pushed by the bot on the 'bot-event' Tauri event and the /api/v1/message/ws WebSocket
+export type BotEvent =
+  | { type: 'status'; data: { status: BotStatus; message: string | null } }
+  | { type: 'signal'; data: { pair: string; side: 'buy' | 'sell'; count: number } }
+  | { type: 'entry_fill'; data: { trade: Trade } }
+  | { type: 'exit_fill'; data: { trade: Trade } }
+  | { type: 'protection_trigger'; data: { pair: string | null; protection: string; reason: string; until: string } }
+  | { type: 'error'; data: { pair: string | null; message: string } };
+
+export interface DataDownload {
+  pair: string;
+  timeframe: string;
+  start_date: string;
+  end_date: string;
+  candles_count: number;
+  download_time: string;
+}
+
 export interface AppConfig {
   bot: BotConfig;
   exchange: ExchangeConfig;
@@ -104,13 +184,18 @@ export interface BotConfig {
   dry_run: boolean;
   dry_run_wallet: number;
   process_only_new_candles: boolean;
+  leverage?: number;
+  margin_mode?: 'isolated' | 'cross';
+  can_short?: boolean;
 }
 
 export interface ExchangeConfig {
   name: string;
   key: string;
   secret: string;
+  password?: string;
   enable_rate_limit: boolean;
+  base_url?: string;
 }
 
 export interface StrategyConfig {
@@ -127,6 +212,9 @@ export interface ApiServerConfig {
   enabled: boolean;
   listen_ip: string;
   listen_port: number;
+  username?: string;
+  password?: string;
+  jwt_secret_key?: string;
 }
 
 export interface LogConfig {
@@ -134,9 +222,61 @@ export interface LogConfig {
 }
 
 export interface BacktestConfig {
+  strategy?: string;
+  pairs?: string[];
+  timeframe?: Timeframe;
+  timeframe_detail?: Timeframe;
+  timerange?: string;
+  stake_amount?: number;
+  starting_balance?: number;
+  commission?: number;
+  slippage?: number;
+  stoploss?: number;
+  take_profit?: number;
+}
+
+export type ExportFormat = 'json' | 'csv';
+
+export interface MonteCarloConfig {
+  simulations?: number;
+  method?: 'shuffle' | 'bootstrap';
+  fee_noise?: number;
+  slippage_noise?: number;
+  ruin_threshold?: number;
+  confidence_levels?: number[];
+  seed?: number;
+}
+
+export interface Distribution {
+  mean: number;
+  std_dev: number;
+  min: number;
+  median: number;
+  max: number;
+}
+
+export interface MonteCarloResult {
+  simulations: number;
+  method: 'shuffle' | 'bootstrap';
+  starting_balance: number;
+  original_profit: number;
+  original_max_drawdown: number;
+  final_profit: Distribution;
+  max_drawdown: Distribution;
+  confidence: { level: number; final_profit: number; max_drawdown: number }[];
+  ruin_probability: number;
+}
+
+export interface BacktestSummary {
+  id: number;
   strategy: string;
   pair: string;
   timeframe: Timeframe;
-  timerange?: string;
-  stake_amount?: number;
+  start_date: string;
+  end_date: string;
+  total_trades: number;
+  win_rate: number;
+  total_profit: number;
+  max_drawdown: number;
+  created_at: string;
 }